@@ -10,6 +10,7 @@ use super::{
     },
     values::Value,
 };
+use crate::SemanticError;
 
 /// TODO: documentation
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -25,24 +26,84 @@ pub enum JoinType {
     RightAnti,
 }
 
+impl JoinType {
+    /// Semi and anti joins only output the attributes of one of their children.
+    pub fn is_semi_or_anti(&self) -> bool {
+        matches!(
+            self,
+            JoinType::LeftSemi | JoinType::RightSemi | JoinType::LeftAnti | JoinType::RightAnti
+        )
+    }
+}
+
 impl Display for JoinType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
 
+/// A set operation. The variants without `All` remove duplicated rows from their output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SetOpType {
+    Union,
+    UnionAll,
+    Intersect,
+    IntersectAll,
+    Except,
+    ExceptAll,
+}
+
+impl SetOpType {
+    /// Whether the set operation keeps duplicated rows.
+    pub fn is_all(&self) -> bool {
+        matches!(
+            self,
+            SetOpType::UnionAll | SetOpType::IntersectAll | SetOpType::ExceptAll
+        )
+    }
+}
+
+impl Display for SetOpType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 /// TODO: documentation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PhysicalNodeType {
     PhysicalProjection,
     PhysicalFilter,
     PhysicalScan,
+    /// A scan of the rows of a table that match a condition on the attributes of an index.
+    PhysicalIndexScan,
     PhysicalSort,
+    /// An `ORDER BY ... LIMIT` that keeps the first rows in a heap instead of sorting its whole
+    /// input. Its predicates are the sort orders, the number of rows to skip and the number of
+    /// rows to fetch, as in a limit.
+    PhysicalTopN,
+    /// A hash aggregation.
     PhysicalAgg,
+    /// An aggregation over an input that is sorted on the GROUP BY keys, which does not need a
+    /// hash table.
+    PhysicalSortAgg,
+    /// Computes window functions over the partitions of its input. Its predicates are the
+    /// window functions, the PARTITION BY keys and the ORDER BY sort orders, which all the
+    /// window functions share.
+    PhysicalWindow,
     PhysicalHashJoin(JoinType),
     PhysicalNestedLoopJoin(JoinType),
+    /// A join that merges its two children sorted on the join keys. Its predicates are the keys
+    /// of the left child and the keys of the right child, as in a hash join.
+    PhysicalSortMergeJoin(JoinType),
     PhysicalEmptyRelation,
     PhysicalLimit,
+    /// A set operation on two children with the same attributes, which matches their rows with
+    /// a hash table.
+    PhysicalHashSetOp(SetOpType),
+    /// A set operation on two children with the same attributes, which sorts them on all their
+    /// attributes and merges them.
+    PhysicalSortSetOp(SetOpType),
 }
 
 impl std::fmt::Display for PhysicalNodeType {
@@ -113,7 +174,16 @@ impl PredicateNode {
 pub trait ReprPredicateNode: 'static + Clone {
     fn into_pred_node(self) -> ArcPredicateNode;
 
+    /// Returns `None` if the node is not of this predicate type, or if it does not have the
+    /// children and data that this predicate type requires.
     fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self>;
+
+    /// Like [`ReprPredicateNode::from_pred_node`], but returns an error for malformed nodes.
+    fn try_from_pred_node(pred_node: ArcPredicateNode) -> Result<Self, SemanticError> {
+        Self::from_pred_node(pred_node.clone()).ok_or_else(|| {
+            SemanticError::InvalidPredicate(format!("malformed predicate {pred_node}"))
+        })
+    }
 }
 
 impl ReprPredicateNode for ArcPredicateNode {
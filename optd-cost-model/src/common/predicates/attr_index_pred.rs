@@ -24,7 +24,10 @@ impl AttrIndexPred {
 
     /// Gets the attribute index.
     pub fn attr_index(&self) -> u64 {
-        self.0.data.as_ref().unwrap().as_u64()
+        match self.0.data {
+            Some(Value::UInt64(attr_idx)) => attr_idx,
+            _ => unreachable!("checked in from_pred_node"),
+        }
     }
 }
 
@@ -34,7 +37,9 @@ impl ReprPredicateNode for AttrIndexPred {
     }
 
     fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self> {
-        if pred_node.typ != PredicateType::AttrIndex {
+        if pred_node.typ != PredicateType::AttrIndex
+            || !matches!(pred_node.data, Some(Value::UInt64(_)))
+        {
             return None;
         }
         Some(Self(pred_node))
@@ -0,0 +1,59 @@
+use crate::common::{
+    nodes::{ArcPredicateNode, PredicateNode, PredicateType, ReprPredicateNode},
+    values::Value,
+};
+
+/// `child BETWEEN lower AND upper`, where both bounds are inclusive.
+#[derive(Clone, Debug)]
+pub struct BetweenPred(pub ArcPredicateNode);
+
+impl BetweenPred {
+    pub fn new(
+        child: ArcPredicateNode,
+        lower: ArcPredicateNode,
+        upper: ArcPredicateNode,
+        negated: bool,
+    ) -> Self {
+        BetweenPred(
+            PredicateNode {
+                typ: PredicateType::Between,
+                children: vec![child, lower, upper],
+                data: Some(Value::Bool(negated)),
+            }
+            .into(),
+        )
+    }
+
+    pub fn child(&self) -> ArcPredicateNode {
+        self.0.child(0)
+    }
+
+    pub fn lower(&self) -> ArcPredicateNode {
+        self.0.child(1)
+    }
+
+    pub fn upper(&self) -> ArcPredicateNode {
+        self.0.child(2)
+    }
+
+    /// `true` for `NOT BETWEEN`.
+    pub fn negated(&self) -> bool {
+        matches!(self.0.data, Some(Value::Bool(true)))
+    }
+}
+
+impl ReprPredicateNode for BetweenPred {
+    fn into_pred_node(self) -> ArcPredicateNode {
+        self.0
+    }
+
+    fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self> {
+        if !matches!(pred_node.typ, PredicateType::Between)
+            || pred_node.children.len() != 3
+            || !matches!(pred_node.data, Some(Value::Bool(_)))
+        {
+            return None;
+        }
+        Some(Self(pred_node))
+    }
+}
@@ -79,7 +79,7 @@ impl ReprPredicateNode for BinOpPred {
     }
 
     fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self> {
-        if !matches!(pred_node.typ, PredicateType::BinOp(_)) {
+        if !matches!(pred_node.typ, PredicateType::BinOp(_)) || pred_node.children.len() != 2 {
             return None;
         }
         Some(Self(pred_node))
@@ -41,7 +41,10 @@ impl ReprPredicateNode for CastPred {
     }
 
     fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self> {
-        if !matches!(pred_node.typ, PredicateType::Cast) {
+        if !matches!(pred_node.typ, PredicateType::Cast)
+            || pred_node.children.len() != 2
+            || !matches!(pred_node.children[1].typ, PredicateType::DataType(_))
+        {
             return None;
         }
         Some(Self(pred_node))
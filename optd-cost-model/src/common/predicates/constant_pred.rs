@@ -4,9 +4,12 @@ use arrow_schema::{DataType, IntervalUnit};
 use optd_persistent::cost_model::interface::AttrType;
 use serde::{Deserialize, Serialize};
 
-use crate::common::{
-    nodes::{ArcPredicateNode, PredicateNode, PredicateType, ReprPredicateNode},
-    values::{SerializableOrderedF64, Value},
+use crate::{
+    common::{
+        nodes::{ArcPredicateNode, PredicateNode, PredicateType, ReprPredicateNode},
+        values::{SerializableOrderedF64, Value},
+    },
+    stats::DEFAULT_VARLEN_WIDTH,
 };
 
 /// TODO: documentation
@@ -92,6 +95,19 @@ impl ConstantType {
         }
     }
 
+    /// The width in bytes of a value of this type. Variable-width types are assumed to be
+    /// `DEFAULT_VARLEN_WIDTH` wide.
+    pub fn default_width(&self) -> f64 {
+        match self {
+            ConstantType::Bool | ConstantType::UInt8 | ConstantType::Int8 => 1.0,
+            ConstantType::UInt16 | ConstantType::Int16 => 2.0,
+            ConstantType::UInt32 | ConstantType::Int32 | ConstantType::Date => 4.0,
+            ConstantType::UInt64 | ConstantType::Int64 | ConstantType::Float64 => 8.0,
+            ConstantType::IntervalMonthDateNano | ConstantType::Decimal => 16.0,
+            ConstantType::Utf8String | ConstantType::Binary => DEFAULT_VARLEN_WIDTH,
+        }
+    }
+
     pub fn from_persistent_attr_type(attr_type: AttrType) -> Self {
         match attr_type {
             AttrType::Integer => ConstantType::Int32,
@@ -211,7 +227,7 @@ impl ReprPredicateNode for ConstantPred {
     }
 
     fn from_pred_node(rel_node: ArcPredicateNode) -> Option<Self> {
-        if let PredicateType::Constant(_) = rel_node.typ {
+        if let (PredicateType::Constant(_), Some(_)) = (&rel_node.typ, &rel_node.data) {
             Some(Self(rel_node))
         } else {
             None
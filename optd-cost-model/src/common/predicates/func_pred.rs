@@ -2,7 +2,12 @@
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum FuncType {
     Scalar(datafusion_expr::BuiltinScalarFunction),
+    /// An aggregate function. The data of the node is `Value::Bool(true)` for a DISTINCT
+    /// aggregate, e.g. `COUNT(DISTINCT a)`.
     Agg(datafusion_expr::AggregateFunction),
+    /// A window function, e.g. `ROW_NUMBER()` or an aggregate function over a window, e.g.
+    /// `SUM(a) OVER (...)`.
+    Window(datafusion_expr::WindowFunction),
     Case,
 }
 
@@ -20,4 +25,8 @@ impl FuncType {
     pub fn new_agg(func_id: datafusion_expr::AggregateFunction) -> Self {
         FuncType::Agg(func_id)
     }
+
+    pub fn new_window(func_id: datafusion_expr::WindowFunction) -> Self {
+        FuncType::Window(func_id)
+    }
 }
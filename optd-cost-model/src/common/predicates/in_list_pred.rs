@@ -30,7 +30,7 @@ impl InListPred {
 
     /// `true` for `NOT IN`.
     pub fn negated(&self) -> bool {
-        self.0.data.as_ref().unwrap().as_bool()
+        matches!(self.0.data, Some(Value::Bool(true)))
     }
 }
 
@@ -40,7 +40,11 @@ impl ReprPredicateNode for InListPred {
     }
 
     fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self> {
-        if !matches!(pred_node.typ, PredicateType::InList) {
+        if !matches!(pred_node.typ, PredicateType::InList)
+            || pred_node.children.len() != 2
+            || pred_node.children[1].typ != PredicateType::List
+            || !matches!(pred_node.data, Some(Value::Bool(_)))
+        {
             return None;
         }
         Some(Self(pred_node))
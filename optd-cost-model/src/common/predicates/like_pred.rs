@@ -38,16 +38,17 @@ impl LikePred {
 
     /// `true` for `NOT LIKE`.
     pub fn negated(&self) -> bool {
-        match self.0.data.as_ref().unwrap() {
-            Value::Serialized(data) => data[0] != 0,
-            _ => panic!("not a serialized value"),
-        }
+        self.flags()[0] != 0
     }
 
     pub fn case_insensitive(&self) -> bool {
-        match self.0.data.as_ref().unwrap() {
-            Value::Serialized(data) => data[1] != 0,
-            _ => panic!("not a serialized value"),
+        self.flags()[1] != 0
+    }
+
+    fn flags(&self) -> &[u8] {
+        match &self.0.data {
+            Some(Value::Serialized(data)) => data,
+            _ => unreachable!("checked in from_pred_node"),
         }
     }
 }
@@ -58,7 +59,10 @@ impl ReprPredicateNode for LikePred {
     }
 
     fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self> {
-        if !matches!(pred_node.typ, PredicateType::Like) {
+        if !matches!(pred_node.typ, PredicateType::Like)
+            || pred_node.children.len() != 2
+            || !matches!(&pred_node.data, Some(Value::Serialized(data)) if data.len() == 2)
+        {
             return None;
         }
         Some(Self(pred_node))
@@ -1,4 +1,5 @@
 pub mod attr_index_pred;
+pub mod between_pred;
 pub mod bin_op_pred;
 pub mod cast_pred;
 pub mod constant_pred;
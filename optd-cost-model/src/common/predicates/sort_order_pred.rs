@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
 /// TODO: documentation
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum SortOrderType {
     Asc,
     Desc,
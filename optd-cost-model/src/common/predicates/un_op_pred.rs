@@ -49,7 +49,7 @@ impl ReprPredicateNode for UnOpPred {
     }
 
     fn from_pred_node(pred_node: ArcPredicateNode) -> Option<Self> {
-        if !matches!(pred_node.typ, PredicateType::UnOp(_)) {
+        if !matches!(pred_node.typ, PredicateType::UnOp(_)) || pred_node.children.len() != 1 {
             return None;
         }
         Some(Self(pred_node))
@@ -143,11 +143,7 @@ impl SemanticCorrelation {
     /// Union two `EqBaseTableattributesets` to produce a new disjoint sets.
     pub fn union(x: Self, y: Self) -> Self {
         let mut eq_attr_sets = Self::new();
-        for predicate in x
-            .eq_predicates
-            .into_iter()
-            .chain(y.eq_predicates.into_iter())
-        {
+        for predicate in x.eq_predicates.into_iter().chain(y.eq_predicates) {
             eq_attr_sets.add_predicate(predicate);
         }
         eq_attr_sets
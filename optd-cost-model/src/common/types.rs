@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
-/// TODO: Implement from and to methods for the following types to enable conversion
-/// to and from their persistent counterparts.
+// TODO: Implement from and to methods for the following types to enable conversion
+// to and from their persistent counterparts.
 
 /// TODO: documentation
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
@@ -23,6 +23,10 @@ pub struct AttrId(pub u64);
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
 pub struct EpochId(pub u64);
 
+/// TODO: documentation
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
+pub struct IndexId(pub u64);
+
 impl Display for GroupId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "!{}", self.0)
@@ -53,6 +57,12 @@ impl Display for EpochId {
     }
 }
 
+impl Display for IndexId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Index#{}", self.0)
+    }
+}
+
 impl From<GroupId> for i32 {
     fn from(id: GroupId) -> i32 {
         id.0 as i32
@@ -82,3 +92,9 @@ impl From<EpochId> for i32 {
         id.0 as i32
     }
 }
+
+impl From<IndexId> for i32 {
+    fn from(id: IndexId) -> i32 {
+        id.0 as i32
+    }
+}
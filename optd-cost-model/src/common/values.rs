@@ -4,6 +4,8 @@ use ordered_float::OrderedFloat;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::sync::Arc;
 
+use crate::SemanticError;
+
 /// TODO: documentation
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct SerializableOrderedF64(pub OrderedFloat<f64>);
@@ -79,127 +81,149 @@ impl std::fmt::Display for Value {
 ///
 /// If you want to perform conversions, use the `to_*()` functions.
 impl Value {
-    pub fn as_u8(&self) -> u8 {
+    pub fn as_u8(&self) -> Result<u8, SemanticError> {
+        match self {
+            Value::UInt8(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an u8"))),
+        }
+    }
+
+    pub fn as_u16(&self) -> Result<u16, SemanticError> {
         match self {
-            Value::UInt8(i) => *i,
-            _ => panic!("Value is not an u8"),
+            Value::UInt16(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an u16"))),
         }
     }
 
-    pub fn as_u16(&self) -> u16 {
+    pub fn as_u32(&self) -> Result<u32, SemanticError> {
         match self {
-            Value::UInt16(i) => *i,
-            _ => panic!("Value is not an u16"),
+            Value::UInt32(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an u32"))),
         }
     }
 
-    pub fn as_u32(&self) -> u32 {
+    pub fn as_u64(&self) -> Result<u64, SemanticError> {
         match self {
-            Value::UInt32(i) => *i,
-            _ => panic!("Value is not an u32"),
+            Value::UInt64(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an u64"))),
         }
     }
 
-    pub fn as_u64(&self) -> u64 {
+    pub fn as_i8(&self) -> Result<i8, SemanticError> {
         match self {
-            Value::UInt64(i) => *i,
-            _ => panic!("Value is not an u64"),
+            Value::Int8(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an i8"))),
         }
     }
 
-    pub fn as_i8(&self) -> i8 {
+    pub fn as_i16(&self) -> Result<i16, SemanticError> {
         match self {
-            Value::Int8(i) => *i,
-            _ => panic!("Value is not an i8"),
+            Value::Int16(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an i16"))),
         }
     }
 
-    pub fn as_i16(&self) -> i16 {
+    pub fn as_i32(&self) -> Result<i32, SemanticError> {
         match self {
-            Value::Int16(i) => *i,
-            _ => panic!("Value is not an i16"),
+            Value::Int32(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an i32"))),
         }
     }
 
-    pub fn as_i32(&self) -> i32 {
+    pub fn as_i64(&self) -> Result<i64, SemanticError> {
         match self {
-            Value::Int32(i) => *i,
-            _ => panic!("Value is not an i32"),
+            Value::Int64(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an i64"))),
         }
     }
 
-    pub fn as_i64(&self) -> i64 {
+    pub fn as_i128(&self) -> Result<i128, SemanticError> {
         match self {
-            Value::Int64(i) => *i,
-            _ => panic!("Value is not an i64"),
+            Value::Int128(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!(
+                "{self} is not an i128"
+            ))),
         }
     }
 
-    pub fn as_i128(&self) -> i128 {
+    pub fn as_f64(&self) -> Result<f64, SemanticError> {
         match self {
-            Value::Int128(i) => *i,
-            _ => panic!("Value is not an i128"),
+            Value::Float(i) => Ok(*i.0),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not an f64"))),
         }
     }
 
-    pub fn as_f64(&self) -> f64 {
+    pub fn as_bool(&self) -> Result<bool, SemanticError> {
         match self {
-            Value::Float(i) => *i.0,
-            _ => panic!("Value is not an f64"),
+            Value::Bool(i) => Ok(*i),
+            _ => Err(SemanticError::TypeMismatch(format!("{self} is not a bool"))),
         }
     }
 
-    pub fn as_bool(&self) -> bool {
+    pub fn as_str(&self) -> Result<Arc<str>, SemanticError> {
         match self {
-            Value::Bool(i) => *i,
-            _ => panic!("Value is not a bool"),
+            Value::String(i) => Ok(i.clone()),
+            _ => Err(SemanticError::TypeMismatch(format!(
+                "{self} is not a string"
+            ))),
         }
     }
 
-    pub fn as_str(&self) -> Arc<str> {
+    pub fn as_slice(&self) -> Result<Arc<[u8]>, SemanticError> {
         match self {
-            Value::String(i) => i.clone(),
-            _ => panic!("Value is not a string"),
+            Value::Serialized(i) => Ok(i.clone()),
+            _ => Err(SemanticError::TypeMismatch(format!(
+                "{self} is not a serialized"
+            ))),
         }
     }
 
-    pub fn as_slice(&self) -> Arc<[u8]> {
+    /// The width of the value in bytes, e.g. the length of a string.
+    pub fn width(&self) -> usize {
         match self {
-            Value::Serialized(i) => i.clone(),
-            _ => panic!("Value is not a serialized"),
+            Value::UInt8(_) | Value::Int8(_) | Value::Bool(_) => 1,
+            Value::UInt16(_) | Value::Int16(_) => 2,
+            Value::UInt32(_) | Value::Int32(_) | Value::Date32(_) => 4,
+            Value::UInt64(_) | Value::Int64(_) | Value::Float(_) => 8,
+            Value::Int128(_) | Value::Decimal128(_) => 16,
+            Value::String(s) => s.len(),
+            Value::Serialized(bytes) => bytes.len(),
         }
     }
 
-    pub fn convert_to_type(&self, typ: DataType) -> Value {
-        match typ {
+    pub fn convert_to_type(&self, typ: DataType) -> Result<Value, SemanticError> {
+        let mismatch =
+            || SemanticError::TypeMismatch(format!("{self} cannot be converted into {typ}"));
+        Ok(match typ {
             DataType::Int32 => Value::Int32(match self {
                 Value::Int32(i32) => *i32,
-                Value::Int64(i64) => (*i64).try_into().unwrap(),
-                _ => panic!("{self} could not be converted into an Int32"),
+                Value::Int64(i64) => (*i64).try_into().map_err(|_| mismatch())?,
+                _ => return Err(mismatch()),
             }),
             DataType::Int64 => Value::Int64(match self {
                 Value::Int64(i64) => *i64,
                 Value::Int32(i32) => (*i32).into(),
-                _ => panic!("{self} could not be converted into an Int64"),
+                _ => return Err(mismatch()),
             }),
             DataType::UInt64 => Value::UInt64(match self {
-                Value::Int64(i64) => (*i64).try_into().unwrap(),
+                Value::Int64(i64) => (*i64).try_into().map_err(|_| mismatch())?,
                 Value::UInt64(i64) => *i64,
                 Value::UInt32(i32) => (*i32).into(),
-                _ => panic!("{self} could not be converted into an UInt64"),
+                _ => return Err(mismatch()),
             }),
             DataType::Date32 => Value::Date32(match self {
                 Value::Date32(date32) => *date32,
                 Value::String(str) => {
-                    let date = NaiveDate::parse_from_str(str, "%Y-%m-%d").unwrap();
+                    let date =
+                        NaiveDate::parse_from_str(str, "%Y-%m-%d").map_err(|_| mismatch())?;
                     let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
                     let duration_since_epoch = date.signed_duration_since(epoch);
                     let days_since_epoch: i32 = duration_since_epoch.num_days() as i32;
                     days_since_epoch
                 }
-                _ => panic!("{self} could not be converted into an Date32"),
+                _ => return Err(mismatch()),
             }),
-            _ => unimplemented!("Have not implemented convert_to_type for DataType {typ}"),
-        }
+            _ => return Err(mismatch()),
+        })
     }
 }
@@ -1,85 +1,258 @@
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::Itertools;
+
 use crate::{
     common::{
         nodes::{ArcPredicateNode, PredicateType, ReprPredicateNode},
-        predicates::{attr_index_pred::AttrIndexPred, list_pred::ListPred},
+        predicates::{
+            attr_index_pred::AttrIndexPred, constant_pred::ConstantPred, func_pred::FuncType,
+            list_pred::ListPred,
+        },
         properties::attr_ref::{AttrRef, BaseTableAttrRef},
-        types::GroupId,
+        types::{GroupId, TableId},
+        values::Value,
     },
     cost_model::CostModelImpl,
-    stats::DEFAULT_NUM_DISTINCT,
+    stats::{DEFAULT_NUM_DISTINCT, MAX_ATTR_COMB_LEN, MAX_ATTR_COMB_LOOKUPS},
     storage::CostModelStorageManager,
     CostModelResult, EstimatedStatistic, SemanticError,
 };
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Estimates the number of groups of an aggregation, which is at most `child_row_cnt`.
+    ///
+    /// The GROUP BY expressions are reduced to the attributes they reference, as in Postgres'
+    /// `estimate_num_groups`. The attributes of each base table are estimated together, using the
+    /// n-distinct of the largest attribute combinations that have statistics, and the tables are
+    /// assumed to be independent.
     pub async fn get_agg_row_cnt(
         &self,
         group_id: GroupId,
         group_by: ArcPredicateNode,
+        child_row_cnt: EstimatedStatistic,
     ) -> CostModelResult<EstimatedStatistic> {
-        let group_by = ListPred::from_pred_node(group_by).unwrap();
+        let group_by = ListPred::try_from_pred_node(group_by)?;
         if group_by.is_empty() {
-            Ok(EstimatedStatistic(1.0))
-        } else {
-            // Multiply the n-distinct of all the group by columns.
-            // TODO: improve with multi-dimensional n-distinct
-            let mut row_cnt = 1;
-
-            for node in &group_by.0.children {
-                match node.typ {
-                    PredicateType::AttrIndex => {
-                        let attr_ref =
-                            AttrIndexPred::from_pred_node(node.clone()).ok_or_else(|| {
-                                SemanticError::InvalidPredicate(
-                                    "Expected AttributeRef predicate".to_string(),
-                                )
-                            })?;
-                        if let AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) =
-                            self.memo.get_attribute_ref(group_id, attr_ref.attr_index())
-                        {
-                            // TODO: Only query ndistinct instead of all kinds of stats.
-                            let stats_option =
-                                self.get_attribute_comb_stats(table_id, &[attr_idx]).await?;
-
-                            let ndistinct = match stats_option {
-                                Some(stats) => stats.ndistinct,
-                                None => {
-                                    // The column type is not supported or stats are missing.
-                                    DEFAULT_NUM_DISTINCT
-                                }
-                            };
-                            row_cnt *= ndistinct;
-                        } else {
-                            // TOOD: Handle derived attributes.
-                            row_cnt *= DEFAULT_NUM_DISTINCT;
-                        }
+            return Ok(EstimatedStatistic(1.0));
+        }
+
+        let attr_cnt = self.memo.get_attribute_refs(group_id).attr_refs().len() as u64;
+        let mut attr_ref_idxs = BTreeSet::new();
+        for node in &group_by.0.children {
+            collect_group_by_attrs(node, attr_cnt, true, &mut attr_ref_idxs)?;
+        }
+        self.get_attrs_group_cnt(group_id, attr_ref_idxs, child_row_cnt)
+            .await
+    }
+
+    /// Estimates the number of distinct combinations of the attributes `attr_ref_idxs` of a
+    /// group, which is at most `child_row_cnt`.
+    ///
+    /// Each table takes at most one lookup per attribute and `MAX_ATTR_COMB_LOOKUPS` lookups of
+    /// attribute combinations, see `get_attribute_comb_ndistinct`.
+    pub(crate) async fn get_attrs_group_cnt(
+        &self,
+        group_id: GroupId,
+        attr_ref_idxs: BTreeSet<u64>,
+        child_row_cnt: EstimatedStatistic,
+    ) -> CostModelResult<EstimatedStatistic> {
+        let mut row_cnt = 1.0;
+        let mut base_attrs: HashMap<TableId, Vec<u64>> = HashMap::new();
+        for attr_ref_idx in attr_ref_idxs {
+            if let AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) =
+                self.memo.get_attribute_ref(group_id, attr_ref_idx)
+            {
+                base_attrs.entry(table_id).or_default().push(attr_idx);
+            } else {
+                // TODO: Handle derived attributes.
+                row_cnt *= DEFAULT_NUM_DISTINCT as f64;
+            }
+        }
+        for (table_id, attrs) in base_attrs {
+            row_cnt *= self.get_attribute_comb_ndistinct(table_id, attrs).await?;
+        }
+        Ok(EstimatedStatistic(row_cnt.min(child_row_cnt.0).max(1.0)))
+    }
+
+    /// Estimates the number of distinct combinations of `attrs` in a table.
+    ///
+    /// The attributes are covered greedily by the largest combinations that have an n-distinct,
+    /// which are assumed to be independent of each other. Only combinations of up to
+    /// `MAX_ATTR_COMB_LEN` attributes are tried, with at most `MAX_ATTR_COMB_LOOKUPS` lookups.
+    /// The attributes left are counted one by one, as `DEFAULT_NUM_DISTINCT` if they have no
+    /// statistics.
+    async fn get_attribute_comb_ndistinct(
+        &self,
+        table_id: TableId,
+        mut attrs: Vec<u64>,
+    ) -> CostModelResult<f64> {
+        attrs.sort();
+        attrs.dedup();
+        let mut ndistinct = 1.0;
+        let mut lookup_cnt = 0;
+        'cover: while attrs.len() >= 2 {
+            for comb_len in (2..=attrs.len().min(MAX_ATTR_COMB_LEN)).rev() {
+                for attr_comb in attrs.iter().copied().combinations(comb_len) {
+                    if lookup_cnt == MAX_ATTR_COMB_LOOKUPS {
+                        break 'cover;
                     }
-                    _ => {
-                        // TODO: Consider the case where `GROUP BY 1`.
-                        panic!("GROUP BY must have attribute ref predicate");
+                    lookup_cnt += 1;
+                    let comb_ndistinct = self
+                        .get_attribute_comb_stored_ndistinct(table_id, &attr_comb)
+                        .await?;
+                    if comb_ndistinct == 0 {
+                        continue;
                     }
+                    ndistinct *= comb_ndistinct as f64;
+                    attrs.retain(|attr_idx| !attr_comb.contains(attr_idx));
+                    continue 'cover;
                 }
             }
-            Ok(EstimatedStatistic(row_cnt as f64))
+            // No combination of the remaining attributes has statistics.
+            break;
+        }
+        for attr_idx in attrs {
+            let attr_ndistinct = match self
+                .get_attribute_comb_stored_ndistinct(table_id, &[attr_idx])
+                .await?
+            {
+                0 => DEFAULT_NUM_DISTINCT,
+                attr_ndistinct => attr_ndistinct,
+            };
+            ndistinct *= attr_ndistinct as f64;
         }
+        Ok(ndistinct)
+    }
+
+    /// Get the stored n-distinct of an attribute combination, or 0 if there is none. The storage
+    /// may return empty statistics for combinations that were never analyzed.
+    async fn get_attribute_comb_stored_ndistinct(
+        &self,
+        table_id: TableId,
+        attr_comb: &[u64],
+    ) -> CostModelResult<u64> {
+        Ok(self
+            .get_attribute_comb_stats(table_id, attr_comb)
+            .await?
+            .map_or(0, |stats| stats.ndistinct))
+    }
+
+    /// Estimates the compute cost of an aggregation from the aggregate functions in `aggs` and
+    /// the number of groups.
+    ///
+    /// A hash aggregation hashes the GROUP BY keys of every input row and inserts every group
+    /// into a hash table, while a sort-based aggregation only compares the keys of consecutive
+    /// rows. `expr_cost` is the cost of evaluating the aggregate and GROUP BY expressions on one
+    /// input row.
+    pub(crate) fn get_agg_compute_cost(
+        &self,
+        is_sort_agg: bool,
+        aggs: ArcPredicateNode,
+        child_row_cnt: f64,
+        group_cnt: f64,
+        expr_cost: f64,
+    ) -> CostModelResult<f64> {
+        let profile = &self.cost_profile;
+        let (mut per_row, mut per_group) = if is_sort_agg {
+            (profile.sort_agg_per_row, 0.0)
+        } else {
+            (profile.hash_agg_per_row, profile.hash_agg_per_group)
+        };
+        per_row += expr_cost;
+        for agg in &ListPred::try_from_pred_node(aggs)?.0.children {
+            let PredicateType::Func(FuncType::Agg(func)) = &agg.typ else {
+                return Err(SemanticError::InvalidPredicate(format!(
+                    "{agg} is not an aggregate function"
+                ))
+                .into());
+            };
+            let func_cost = profile.agg_func_cost(func);
+            per_row += func_cost.per_row;
+            per_group += func_cost.per_group;
+            if matches!(agg.data, Some(Value::Bool(true))) {
+                per_row += profile.distinct_agg_per_row;
+            }
+        }
+        Ok(child_row_cnt * per_row + group_cnt * per_group)
     }
 }
 
+/// Collects the attributes a GROUP BY expression depends on into `attr_ref_idxs`.
+///
+/// An integer constant at the top level is the ordinal position of an attribute, starting from 1
+/// as in `GROUP BY 1`. Other constants do not split the groups, and other expressions depend on
+/// the attributes they reference.
+pub(crate) fn collect_group_by_attrs(
+    expr: &ArcPredicateNode,
+    attr_cnt: u64,
+    is_top_level: bool,
+    attr_ref_idxs: &mut BTreeSet<u64>,
+) -> Result<(), SemanticError> {
+    let attr_ref_idx = match expr.typ {
+        PredicateType::AttrIndex => AttrIndexPred::try_from_pred_node(expr.clone())?.attr_index(),
+        PredicateType::Constant(_) if is_top_level => {
+            let value = ConstantPred::try_from_pred_node(expr.clone())?.value();
+            let ordinal = match value {
+                Value::UInt8(v) => v as i128,
+                Value::UInt16(v) => v as i128,
+                Value::UInt32(v) => v as i128,
+                Value::UInt64(v) => v as i128,
+                Value::Int8(v) => v as i128,
+                Value::Int16(v) => v as i128,
+                Value::Int32(v) => v as i128,
+                Value::Int64(v) => v as i128,
+                _ => return Ok(()),
+            };
+            if ordinal < 1 {
+                return Err(SemanticError::InvalidPredicate(format!(
+                    "GROUP BY position {ordinal} is not in the select list"
+                )));
+            }
+            (ordinal - 1) as u64
+        }
+        PredicateType::Constant(_) => return Ok(()),
+        _ => {
+            for child in &expr.children {
+                collect_group_by_attrs(child, attr_cnt, false, attr_ref_idxs)?;
+            }
+            return Ok(());
+        }
+    };
+    if attr_ref_idx >= attr_cnt {
+        return Err(SemanticError::InvalidPredicate(format!(
+            "GROUP BY refers to attribute {attr_ref_idx} of {attr_cnt} attributes"
+        )));
+    }
+    attr_ref_idxs.insert(attr_ref_idx);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
+    use datafusion_expr::AggregateFunction;
+
     use crate::{
-        common::predicates::constant_pred::ConstantType,
+        common::{
+            predicates::{bin_op_pred::BinOpType, constant_pred::ConstantType},
+            values::Value,
+        },
+        cost_profile::{AggFuncCost, CostProfile},
         stats::{utilities::simple_map::SimpleMap, MostCommonValues, DEFAULT_NUM_DISTINCT},
+        storage::mock::TableStats,
         test_utils::tests::{
-            attr_index, create_mock_cost_model_with_attr_types, empty_list, list,
+            agg_func, attr_index, bin_op, cnst, create_mock_cost_model_with_attr_types,
+            create_mock_cost_model_with_table_stats, empty_list, list, TestOptCostModelMock,
             TestPerAttributeStats, TEST_ATTR1_BASE_INDEX, TEST_ATTR2_BASE_INDEX,
             TEST_ATTR3_BASE_INDEX, TEST_GROUP1_ID, TEST_TABLE1_ID,
         },
-        EstimatedStatistic,
+        CostModelError, EstimatedStatistic, SemanticError,
     };
 
+    const UNCAPPED: EstimatedStatistic = EstimatedStatistic(f64::MAX);
+
     #[tokio::test]
     async fn test_agg_no_stats() {
         let cost_model = create_mock_cost_model_with_attr_types(
@@ -96,7 +269,7 @@ mod tests {
         let group_bys = empty_list();
         assert_eq!(
             cost_model
-                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys)
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys, UNCAPPED)
                 .await
                 .unwrap(),
             EstimatedStatistic(1.0)
@@ -106,7 +279,7 @@ mod tests {
         let group_bys = list(vec![attr_index(0)]);
         assert_eq!(
             cost_model
-                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys)
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys, UNCAPPED)
                 .await
                 .unwrap(),
             EstimatedStatistic(DEFAULT_NUM_DISTINCT as f64)
@@ -116,13 +289,51 @@ mod tests {
         let group_bys = list(vec![attr_index(0), attr_index(1)]);
         assert_eq!(
             cost_model
-                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys)
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys, UNCAPPED)
                 .await
                 .unwrap(),
             EstimatedStatistic((DEFAULT_NUM_DISTINCT * DEFAULT_NUM_DISTINCT) as f64)
         );
     }
 
+    #[tokio::test]
+    async fn test_agg_malformed_group_by() {
+        let cost_model = create_mock_cost_model_with_attr_types(
+            vec![TEST_TABLE1_ID],
+            vec![],
+            vec![HashMap::from([(
+                TEST_ATTR1_BASE_INDEX,
+                ConstantType::Int32,
+            )])],
+            vec![None],
+        );
+
+        // The GROUP BY expressions are not in a list.
+        assert!(matches!(
+            cost_model
+                .get_agg_row_cnt(TEST_GROUP1_ID, attr_index(0), UNCAPPED)
+                .await,
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidPredicate(_)
+            ))
+        ));
+        // Positions and attributes must be in the select list.
+        for group_by in [
+            list(vec![cnst(Value::Int32(0))]),
+            list(vec![cnst(Value::Int64(2))]),
+            list(vec![attr_index(1)]),
+        ] {
+            assert!(matches!(
+                cost_model
+                    .get_agg_row_cnt(TEST_GROUP1_ID, group_by, UNCAPPED)
+                    .await,
+                Err(CostModelError::SemanticError(
+                    SemanticError::InvalidPredicate(_)
+                ))
+            ));
+        }
+    }
+
     #[tokio::test]
     async fn test_agg_with_stats() {
         let attr1_ndistinct = 12;
@@ -158,7 +369,7 @@ mod tests {
         let group_bys = empty_list();
         assert_eq!(
             cost_model
-                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys)
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys, UNCAPPED)
                 .await
                 .unwrap(),
             EstimatedStatistic(1.0)
@@ -168,7 +379,7 @@ mod tests {
         let group_bys = list(vec![attr_index(0)]);
         assert_eq!(
             cost_model
-                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys)
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys, UNCAPPED)
                 .await
                 .unwrap(),
             EstimatedStatistic(attr1_ndistinct as f64)
@@ -178,7 +389,7 @@ mod tests {
         let group_bys = list(vec![attr_index(0), attr_index(1)]);
         assert_eq!(
             cost_model
-                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys)
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys, UNCAPPED)
                 .await
                 .unwrap(),
             EstimatedStatistic((attr1_ndistinct * attr2_ndistinct) as f64)
@@ -189,10 +400,204 @@ mod tests {
         let group_bys = list(vec![attr_index(0), attr_index(1), attr_index(2)]);
         assert_eq!(
             cost_model
-                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys)
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_bys, UNCAPPED)
                 .await
                 .unwrap(),
             EstimatedStatistic((attr1_ndistinct * attr2_ndistinct * DEFAULT_NUM_DISTINCT) as f64)
         );
     }
+
+    /// Table 1 has 3 attributes with 10, 20 and 30 distinct values. The first two attributes are
+    /// correlated and only have 25 distinct combinations.
+    fn create_cost_model_with_joint_stats() -> TestOptCostModelMock {
+        let stats =
+            |ndistinct| TestPerAttributeStats::new(MostCommonValues::empty(), None, ndistinct, 0.0);
+        create_mock_cost_model_with_table_stats(
+            HashMap::from([(
+                TEST_TABLE1_ID,
+                TableStats::new(
+                    1000,
+                    HashMap::from([
+                        (vec![0], stats(10)),
+                        (vec![1], stats(20)),
+                        (vec![2], stats(30)),
+                        (vec![0, 1], stats(25)),
+                    ]),
+                ),
+            )]),
+            vec![
+                (TEST_TABLE1_ID, 0, None),
+                (TEST_TABLE1_ID, 1, None),
+                (TEST_TABLE1_ID, 2, None),
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_agg_with_joint_stats() {
+        let cost_model = create_cost_model_with_joint_stats();
+
+        for (group_by, expected_row_cnt) in [
+            (vec![attr_index(0), attr_index(1)], 25.0),
+            (vec![attr_index(1), attr_index(0)], 25.0),
+            // The combination covers the first two attributes, and the third one is independent.
+            (
+                vec![attr_index(2), attr_index(0), attr_index(1)],
+                25.0 * 30.0,
+            ),
+            (vec![attr_index(0), attr_index(2)], 10.0 * 30.0),
+            // Duplicated GROUP BY attributes do not add groups.
+            (vec![attr_index(0), attr_index(0)], 10.0),
+        ] {
+            assert_eq!(
+                cost_model
+                    .get_agg_row_cnt(TEST_GROUP1_ID, list(group_by), UNCAPPED)
+                    .await
+                    .unwrap(),
+                EstimatedStatistic(expected_row_cnt)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agg_many_attrs_is_bounded() {
+        // Without a bound, there would be more than 2^40 combinations to look up.
+        let attr_cnt = 40;
+        let stats = TestPerAttributeStats::new(MostCommonValues::empty(), None, 2, 0.0);
+        let cost_model = create_mock_cost_model_with_table_stats(
+            HashMap::from([(
+                TEST_TABLE1_ID,
+                TableStats::new(
+                    1000,
+                    (0..attr_cnt)
+                        .map(|attr_idx| (vec![attr_idx], stats.clone()))
+                        .collect(),
+                ),
+            )]),
+            (0..attr_cnt)
+                .map(|attr_idx| (TEST_TABLE1_ID, attr_idx, None))
+                .collect(),
+        );
+
+        let group_by = list((0..attr_cnt).map(attr_index).collect());
+        assert_eq!(
+            cost_model
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_by, UNCAPPED)
+                .await
+                .unwrap(),
+            EstimatedStatistic(2f64.powi(attr_cnt as i32))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_agg_capped_by_child_row_cnt() {
+        let cost_model = create_cost_model_with_joint_stats();
+
+        let group_by = list(vec![attr_index(0), attr_index(1), attr_index(2)]);
+        assert_eq!(
+            cost_model
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_by.clone(), EstimatedStatistic(100.0))
+                .await
+                .unwrap(),
+            EstimatedStatistic(100.0)
+        );
+        assert_eq!(
+            cost_model
+                .get_agg_row_cnt(TEST_GROUP1_ID, group_by, EstimatedStatistic(1000.0))
+                .await
+                .unwrap(),
+            EstimatedStatistic(750.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_agg_ordinals_and_expressions() {
+        let cost_model = create_cost_model_with_joint_stats();
+
+        for (group_by, expected_row_cnt) in [
+            // `GROUP BY 1, 3`.
+            (
+                vec![cnst(Value::Int64(1)), cnst(Value::Int64(3))],
+                10.0 * 30.0,
+            ),
+            // `GROUP BY #0 + #1` has at most as many groups as `GROUP BY #0, #1`.
+            (
+                vec![bin_op(BinOpType::Add, attr_index(0), attr_index(1))],
+                25.0,
+            ),
+            // `GROUP BY #2, #2 * 2`.
+            (
+                vec![
+                    attr_index(2),
+                    bin_op(BinOpType::Mul, attr_index(2), cnst(Value::Int32(2))),
+                ],
+                30.0,
+            ),
+            // Constants that are not positions form a single group.
+            (vec![cnst(Value::String("a".into())), attr_index(0)], 10.0),
+        ] {
+            assert_eq!(
+                cost_model
+                    .get_agg_row_cnt(TEST_GROUP1_ID, list(group_by), UNCAPPED)
+                    .await
+                    .unwrap(),
+                EstimatedStatistic(expected_row_cnt)
+            );
+        }
+    }
+
+    #[test]
+    fn test_agg_compute_cost() {
+        let mut cost_model = create_cost_model_with_joint_stats();
+        let default_func = CostProfile::default().default_agg_func;
+        let median = CostProfile::default().agg_func_cost(&AggregateFunction::Median);
+        let sum = agg_func(AggregateFunction::Sum, vec![attr_index(0)], false);
+        let count_distinct = agg_func(AggregateFunction::Count, vec![attr_index(0)], true);
+        let median_agg = agg_func(AggregateFunction::Median, vec![attr_index(0)], false);
+        let compute_cost = |cost_model: &TestOptCostModelMock, is_sort_agg, aggs| {
+            cost_model
+                .get_agg_compute_cost(is_sort_agg, list(aggs), 1000.0, 10.0, 0.0)
+                .unwrap()
+        };
+
+        assert_eq!(
+            compute_cost(&cost_model, false, vec![sum.clone()]),
+            1000.0 * (1.0 + default_func.per_row) + 10.0 * (2.0 + default_func.per_group)
+        );
+        assert_eq!(
+            compute_cost(&cost_model, true, vec![sum.clone()]),
+            1000.0 * (0.5 + default_func.per_row) + 10.0 * default_func.per_group
+        );
+        // DISTINCT aggregates and aggregates that keep all their inputs are more expensive.
+        assert!(
+            compute_cost(&cost_model, false, vec![count_distinct.clone()])
+                > compute_cost(&cost_model, false, vec![sum.clone()])
+        );
+        assert_eq!(
+            compute_cost(&cost_model, true, vec![sum.clone(), median_agg.clone()]),
+            1000.0 * (0.5 + default_func.per_row + median.per_row)
+                + 10.0 * (default_func.per_group + median.per_group)
+        );
+
+        // The constants come from the cost profile of the cost model.
+        cost_model.cost_profile = CostProfile {
+            hash_agg_per_row: 0.0,
+            hash_agg_per_group: 0.0,
+            distinct_agg_per_row: 3.0,
+            agg_funcs: [("COUNT".to_string(), AggFuncCost::new(0.5, 1.0))].into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            compute_cost(&cost_model, false, vec![count_distinct]),
+            1000.0 * (0.5 + 3.0) + 10.0 * 1.0
+        );
+
+        // The aggregate list must only contain aggregate functions.
+        assert!(matches!(
+            cost_model.get_agg_compute_cost(false, list(vec![attr_index(0)]), 1000.0, 10.0, 0.0),
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidPredicate(_)
+            ))
+        ));
+    }
 }
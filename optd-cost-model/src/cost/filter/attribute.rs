@@ -1,11 +1,20 @@
-use std::ops::Bound;
+use std::ops::{Bound, RangeBounds};
 
 use crate::{
-    common::{types::TableId, values::Value},
+    common::{
+        predicates::constant_pred::ConstantType,
+        properties::attr_ref::{AttrRef, BaseTableAttrRef},
+        types::TableId,
+        values::Value,
+    },
+    cost::filter::core::FilterAttrs,
     cost_model::CostModelImpl,
-    stats::{AttributeCombValue, AttributeCombValueStats, DEFAULT_EQ_SEL, DEFAULT_INEQ_SEL},
+    stats::{
+        AttributeCombValue, AttributeCombValueStats, Distribution, DEFAULT_BOOL_SEL,
+        DEFAULT_EQ_SEL, DEFAULT_INEQ_SEL,
+    },
     storage::CostModelStorageManager,
-    CostModelResult,
+    CostModelResult, SemanticError,
 };
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
@@ -69,6 +78,45 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         Ok(ret_sel)
     }
 
+    /// Get the selectivity of a boolean attribute used as a predicate, i.e. "WHERE attribute".
+    /// It is the frequency of `true`, which comes from the MCVs if they were collected.
+    ///
+    /// Returns an error if the attribute is not a boolean.
+    pub(crate) async fn get_bool_attribute_selectivity(
+        &self,
+        attrs: FilterAttrs,
+        attr_ref_idx: u64,
+    ) -> CostModelResult<f64> {
+        let attribute_info = self.get_filter_attr_info(attrs, attr_ref_idx)?;
+        if attribute_info.typ != ConstantType::Bool {
+            return Err(SemanticError::TypeMismatch(format!(
+                "attribute {} of type {:?} is not a boolean predicate",
+                attribute_info.name, attribute_info.typ
+            ))
+            .into());
+        }
+        if let AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) =
+            self.get_filter_attr_ref(attrs, attr_ref_idx)?
+        {
+            if self
+                .get_attribute_comb_stats(table_id, &[attr_idx])
+                .await?
+                .is_some()
+            {
+                return self
+                    .get_attribute_equality_selectivity(
+                        table_id,
+                        attr_idx,
+                        &Value::Bool(true),
+                        true,
+                    )
+                    .await;
+            }
+        }
+        // TODO: Consider attribute is a derived attribute
+        Ok(DEFAULT_BOOL_SEL)
+    }
+
     /// Compute the frequency of values in a attribute less than or equal to the given value.
     fn get_attribute_leq_value_freq(
         per_attribute_stats: &AttributeCombValueStats,
@@ -138,6 +186,16 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             .get_attribute_comb_stats(table_id, &[attr_base_index])
             .await?
         {
+            if let Some(Distribution::EquiDepthHistogram(histogram)) = &attribute_stats.distr {
+                // The histogram interpolates inside its buckets, so the frequency of the range
+                // is read from it directly, and only the MCVs need to look at the bounds.
+                let (mcv_start, mcv_end) = (start.cloned(), end.cloned());
+                let pred = Box::new(move |val: &AttributeCombValue| {
+                    (mcv_start.as_ref(), mcv_end.as_ref()).contains(val[0].as_ref().unwrap())
+                });
+                let mcvs_freq = attribute_stats.mcvs.freq_over_pred(pred);
+                return Ok(histogram.range_freq(start, end) + mcvs_freq);
+            }
             let left_quantile = match start {
                 Bound::Unbounded => 0.0,
                 Bound::Included(value) => {
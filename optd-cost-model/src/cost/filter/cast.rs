@@ -0,0 +1,121 @@
+use arrow_schema::DataType;
+
+use crate::{
+    common::{
+        nodes::{PredicateType, ReprPredicateNode},
+        predicates::{
+            attr_index_pred::AttrIndexPred,
+            cast_pred::CastPred,
+            constant_pred::{ConstantPred, ConstantType},
+        },
+        properties::attr_ref::{AttrRef, BaseTableAttrRef},
+        values::{SerializableOrderedF64, Value},
+    },
+    cost::filter::core::FilterAttrs,
+    cost_model::CostModelImpl,
+    stats::DEFAULT_BOOL_SEL,
+    storage::CostModelStorageManager,
+    CostModelResult, SemanticError,
+};
+
+impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Get the selectivity of a cast used as a predicate, i.e. "WHERE CAST(expr AS BOOLEAN)".
+    ///
+    /// Casting a boolean to a boolean is a no-op, and casting a number to a boolean is the same
+    /// as "expr != 0", so in both cases the cast is unwrapped. Returns an error if the cast
+    /// does not produce a boolean, or if the child cannot be cast to a boolean.
+    pub(crate) async fn get_cast_selectivity(
+        &self,
+        attrs: FilterAttrs,
+        expr: &CastPred,
+    ) -> CostModelResult<f64> {
+        let cast_to = expr.cast_to();
+        if cast_to != DataType::Boolean {
+            return Err(SemanticError::TypeMismatch(format!(
+                "a cast to {cast_to} is not a boolean predicate"
+            ))
+            .into());
+        }
+
+        let child = expr.child();
+        match child.typ {
+            PredicateType::Constant(_) => {
+                let value = ConstantPred::try_from_pred_node(child)?.value();
+                let is_true = match value {
+                    Value::Bool(b) => b,
+                    value => match zero_of_value(&value) {
+                        Some(zero) => value != zero,
+                        None => {
+                            return Err(SemanticError::TypeMismatch(format!(
+                                "{value} cannot be cast to a boolean"
+                            ))
+                            .into())
+                        }
+                    },
+                };
+                Ok(if is_true { 1.0 } else { 0.0 })
+            }
+            PredicateType::AttrIndex => {
+                let attr_ref_idx = AttrIndexPred::try_from_pred_node(child)?.attr_index();
+                let attribute_info = self.get_filter_attr_info(attrs, attr_ref_idx)?;
+                if attribute_info.typ == ConstantType::Bool {
+                    return self
+                        .get_bool_attribute_selectivity(attrs, attr_ref_idx)
+                        .await;
+                }
+                let Some(zero) = zero_of(attribute_info.typ) else {
+                    return Err(SemanticError::TypeMismatch(format!(
+                        "attribute {} of type {:?} cannot be cast to a boolean",
+                        attribute_info.name, attribute_info.typ
+                    ))
+                    .into());
+                };
+                if let AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) =
+                    self.get_filter_attr_ref(attrs, attr_ref_idx)?
+                {
+                    self.get_attribute_equality_selectivity(table_id, attr_idx, &zero, false)
+                        .await
+                } else {
+                    // TODO: Consider attribute is a derived attribute
+                    Ok(DEFAULT_BOOL_SEL)
+                }
+            }
+            // The child is a predicate itself, e.g. "CAST(A = 1 AS BOOLEAN)".
+            _ => self.get_filter_selectivity(attrs, child).await,
+        }
+    }
+}
+
+/// The zero of a numeric type, which is the only value of the type cast to `false`.
+fn zero_of(typ: ConstantType) -> Option<Value> {
+    match typ {
+        ConstantType::UInt8 => Some(Value::UInt8(0)),
+        ConstantType::UInt16 => Some(Value::UInt16(0)),
+        ConstantType::UInt32 => Some(Value::UInt32(0)),
+        ConstantType::UInt64 => Some(Value::UInt64(0)),
+        ConstantType::Int8 => Some(Value::Int8(0)),
+        ConstantType::Int16 => Some(Value::Int16(0)),
+        ConstantType::Int32 => Some(Value::Int32(0)),
+        ConstantType::Int64 => Some(Value::Int64(0)),
+        ConstantType::Float64 => Some(Value::Float(SerializableOrderedF64(0.0.into()))),
+        _ => None,
+    }
+}
+
+/// The zero of a numeric value's type, or None if the value is not a number.
+fn zero_of_value(value: &Value) -> Option<Value> {
+    match value {
+        Value::UInt8(_) => Some(Value::UInt8(0)),
+        Value::UInt16(_) => Some(Value::UInt16(0)),
+        Value::UInt32(_) => Some(Value::UInt32(0)),
+        Value::UInt64(_) => Some(Value::UInt64(0)),
+        Value::Int8(_) => Some(Value::Int8(0)),
+        Value::Int16(_) => Some(Value::Int16(0)),
+        Value::Int32(_) => Some(Value::Int32(0)),
+        Value::Int64(_) => Some(Value::Int64(0)),
+        Value::Int128(_) => Some(Value::Int128(0)),
+        Value::Float(_) => Some(Value::Float(SerializableOrderedF64(0.0.into()))),
+        Value::Decimal128(_) => Some(Value::Decimal128(0)),
+        Value::String(_) | Value::Bool(_) | Value::Date32(_) | Value::Serialized(_) => None,
+    }
+}
@@ -8,33 +8,44 @@ use crate::{
             constant_pred::ConstantPred,
         },
         properties::attr_ref::{AttrRef, BaseTableAttrRef},
-        types::GroupId,
         values::Value,
     },
+    cost::filter::core::FilterAttrs,
     cost_model::CostModelImpl,
     stats::{DEFAULT_EQ_SEL, DEFAULT_INEQ_SEL, UNIMPLEMENTED_SEL},
     storage::CostModelStorageManager,
-    CostModelResult,
+    CostModelError, CostModelResult, SemanticError,
 };
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
     /// Comparison operators are the base case for recursion in get_filter_selectivity()
     pub(crate) async fn get_comp_op_selectivity(
         &self,
-        group_id: GroupId,
+        attrs: FilterAttrs,
         comp_bin_op_typ: BinOpType,
         left: ArcPredicateNode,
         right: ArcPredicateNode,
     ) -> CostModelResult<f64> {
-        assert!(comp_bin_op_typ.is_comparison());
+        if !comp_bin_op_typ.is_comparison() {
+            return Err(SemanticError::InvalidPredicate(format!(
+                "{} is not a comparison operator",
+                comp_bin_op_typ
+            ))
+            .into());
+        }
 
         // I intentionally performed moves on left and right. This way, we don't accidentally use
         // them after this block
-        let semantic_res = self.get_semantic_nodes(group_id, left, right).await;
-        if semantic_res.is_err() {
-            return Ok(Self::get_default_comparison_op_selectivity(comp_bin_op_typ));
-        }
-        let (attr_ref_exprs, values, non_attr_ref_exprs, is_left_attr_ref) = semantic_res.unwrap();
+        let (attr_ref_exprs, values, non_attr_ref_exprs, is_left_attr_ref) =
+            match self.get_semantic_nodes(attrs, left, right).await {
+                Ok(semantic_nodes) => semantic_nodes,
+                // A malformed predicate is an error, while a constant that cannot be cast only
+                // makes the comparison impossible to estimate.
+                Err(err @ CostModelError::SemanticError(SemanticError::InvalidPredicate(_))) => {
+                    return Err(err)
+                }
+                Err(_) => return Ok(Self::get_default_comparison_op_selectivity(comp_bin_op_typ)),
+            };
 
         // Handle the different cases of semantic nodes.
         if attr_ref_exprs.is_empty() {
@@ -46,7 +57,7 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             let attr_ref_idx = attr_ref_expr.attr_index();
 
             if let AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) =
-                self.memo.get_attribute_ref(group_id, attr_ref_idx)
+                self.get_filter_attr_ref(attrs, attr_ref_idx)?
             {
                 if values.len() == 1 {
                     let value = values
@@ -103,10 +114,11 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
                                 "we should have handled this in the values.len() == 1 branch"
                             )
                         }
-                        _ => unimplemented!(
-                            "unhandled case of comparing a attribute ref node to {}",
+                        _ => Err(SemanticError::UnsupportedPredicate(format!(
+                            "comparing an attribute ref to {}",
                             non_attr_ref_expr.as_ref().typ
-                        ),
+                        ))
+                        .into()),
                     }
                 }
             } else {
@@ -124,9 +136,9 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
     /// This is convenient to avoid repeating the same logic just with "left" and "right" swapped.
     /// The last return value is true when the input node (left) is a AttributeRefPred.
     #[allow(clippy::type_complexity)]
-    async fn get_semantic_nodes(
+    pub(crate) async fn get_semantic_nodes(
         &self,
-        group_id: GroupId,
+        attrs: FilterAttrs,
         left: ArcPredicateNode,
         right: ArcPredicateNode,
     ) -> CostModelResult<(Vec<AttrIndexPred>, Vec<Value>, Vec<ArcPredicateNode>, bool)> {
@@ -144,13 +156,37 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             if uncasted_left.as_ref().typ == PredicateType::Cast
                 && uncasted_right.as_ref().typ == PredicateType::Cast
             {
-                let left_cast_expr = CastPred::from_pred_node(uncasted_left)
-                    .expect("we already checked that the type is Cast");
-                let right_cast_expr = CastPred::from_pred_node(uncasted_right)
-                    .expect("we already checked that the type is Cast");
-                assert!(left_cast_expr.cast_to() == right_cast_expr.cast_to());
-                uncasted_left = left_cast_expr.child().into_pred_node();
-                uncasted_right = right_cast_expr.child().into_pred_node();
+                let left_cast_expr = CastPred::try_from_pred_node(uncasted_left)?;
+                let right_cast_expr = CastPred::try_from_pred_node(uncasted_right)?;
+                if left_cast_expr.cast_to() != right_cast_expr.cast_to() {
+                    return Err(SemanticError::InvalidPredicate(format!(
+                        "comparing a cast to {} with a cast to {}",
+                        left_cast_expr.cast_to(),
+                        right_cast_expr.cast_to()
+                    ))
+                    .into());
+                }
+                let is_left_cast_constant =
+                    matches!(left_cast_expr.child().typ, PredicateType::Constant(_));
+                let is_right_cast_constant =
+                    matches!(right_cast_expr.child().typ, PredicateType::Constant(_));
+                if is_left_cast_constant || is_right_cast_constant {
+                    // Fold the cast constants first. Removing both casts would compare the
+                    // constant in its original type, e.g. a string instead of a date.
+                    uncasted_left = if is_left_cast_constant {
+                        fold_cast_constant(&left_cast_expr)?
+                    } else {
+                        left_cast_expr.into_pred_node()
+                    };
+                    uncasted_right = if is_right_cast_constant {
+                        fold_cast_constant(&right_cast_expr)?
+                    } else {
+                        right_cast_expr.into_pred_node()
+                    };
+                } else {
+                    uncasted_left = left_cast_expr.child().into_pred_node();
+                    uncasted_right = right_cast_expr.child().into_pred_node();
+                }
             } else if uncasted_left.as_ref().typ == PredicateType::Cast
                 || uncasted_right.as_ref().typ == PredicateType::Cast
             {
@@ -161,30 +197,21 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
                     (uncasted_right, uncasted_left)
                 };
 
-                let cast_expr = CastPred::from_pred_node(cast_node)
-                    .expect("we already checked that the type is Cast");
+                let cast_expr = CastPred::try_from_pred_node(cast_node)?;
                 let cast_expr_child = cast_expr.child().into_pred_node();
-                let cast_expr_cast_to = cast_expr.cast_to();
 
                 let should_break = match cast_expr_child.typ {
                     PredicateType::Constant(_) => {
-                        cast_node = ConstantPred::new(
-                            ConstantPred::from_pred_node(cast_expr_child)
-                                .expect("we already checked that the type is Constant")
-                                .value()
-                                .convert_to_type(cast_expr_cast_to),
-                        )
-                        .into_pred_node();
+                        cast_node = fold_cast_constant(&cast_expr)?;
                         false
                     }
                     PredicateType::AttrIndex => {
-                        let attr_ref_expr = AttrIndexPred::from_pred_node(cast_expr_child)
-                            .expect("we already checked that the type is AttributeRef");
+                        let attr_ref_expr = AttrIndexPred::try_from_pred_node(cast_expr_child)?;
                         let attr_ref_idx = attr_ref_expr.attr_index();
                         cast_node = attr_ref_expr.into_pred_node();
                         // The "invert" cast is to invert the cast so that we're casting the
                         // non_cast_node to the attribute's original type.
-                        let attribute_info = self.memo.get_attribute_info(group_id, attr_ref_idx);
+                        let attribute_info = self.get_filter_attr_info(attrs, attr_ref_idx)?;
                         let invert_cast_data_type = &attribute_info.typ.into_data_type();
 
                         match non_cast_node.typ {
@@ -203,7 +230,11 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
                             }
                         }
                     }
-                    _ => todo!(),
+                    // The cast cannot be removed from other expressions, so leave it where it is.
+                    _ => {
+                        cast_node = cast_expr.into_pred_node();
+                        true
+                    }
                 };
 
                 (uncasted_left, uncasted_right) = if is_left_cast {
@@ -224,18 +255,11 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         match uncasted_left.as_ref().typ {
             PredicateType::AttrIndex => {
                 is_left_attr_ref = true;
-                attr_ref_exprs.push(
-                    AttrIndexPred::from_pred_node(uncasted_left)
-                        .expect("we already checked that the type is AttributeRef"),
-                );
+                attr_ref_exprs.push(AttrIndexPred::try_from_pred_node(uncasted_left)?);
             }
             PredicateType::Constant(_) => {
                 is_left_attr_ref = false;
-                values.push(
-                    ConstantPred::from_pred_node(uncasted_left)
-                        .expect("we already checked that the type is Constant")
-                        .value(),
-                )
+                values.push(ConstantPred::try_from_pred_node(uncasted_left)?.value())
             }
             _ => {
                 is_left_attr_ref = false;
@@ -244,16 +268,11 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         }
         match uncasted_right.as_ref().typ {
             PredicateType::AttrIndex => {
-                attr_ref_exprs.push(
-                    AttrIndexPred::from_pred_node(uncasted_right)
-                        .expect("we already checked that the type is AttributeRef"),
-                );
+                attr_ref_exprs.push(AttrIndexPred::try_from_pred_node(uncasted_right)?);
+            }
+            PredicateType::Constant(_) => {
+                values.push(ConstantPred::try_from_pred_node(uncasted_right)?.value())
             }
-            PredicateType::Constant(_) => values.push(
-                ConstantPred::from_pred_node(uncasted_right)
-                    .expect("we already checked that the type is Constant")
-                    .value(),
-            ),
             _ => {
                 non_attr_ref_exprs.push(uncasted_right);
             }
@@ -278,3 +297,11 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         }
     }
 }
+
+/// Converts a constant behind a cast into the type of the cast.
+fn fold_cast_constant(cast_expr: &CastPred) -> CostModelResult<ArcPredicateNode> {
+    let value = ConstantPred::try_from_pred_node(cast_expr.child())?
+        .value()
+        .convert_to_type(cast_expr.cast_to())?;
+    Ok(ConstantPred::new(value).into_pred_node())
+}
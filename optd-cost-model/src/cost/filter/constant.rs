@@ -1,38 +1,22 @@
 use crate::{
-    common::{
-        nodes::{ArcPredicateNode, PredicateType},
-        predicates::constant_pred::ConstantType,
-        values::Value,
-    },
+    common::{predicates::constant_pred::ConstantPred, values::Value},
     cost_model::CostModelImpl,
     storage::CostModelStorageManager,
+    SemanticError,
 };
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
-    pub(crate) fn get_constant_selectivity(const_node: ArcPredicateNode) -> f64 {
-        if let PredicateType::Constant(const_typ) = const_node.typ {
-            if matches!(const_typ, ConstantType::Bool) {
-                let value = const_node
-                    .as_ref()
-                    .data
-                    .as_ref()
-                    .expect("constants should have data");
-                if let Value::Bool(bool_value) = value {
-                    if *bool_value {
-                        1.0
-                    } else {
-                        0.0
-                    }
-                } else {
-                    unreachable!(
-                        "if the typ is ConstantType::Bool, the value should be a Value::Bool"
-                    )
-                }
-            } else {
-                panic!("selectivity is not defined on constants which are not bools")
-            }
-        } else {
-            panic!("get_constant_selectivity must be called on a constant")
+    /// A constant filter keeps every row or none of them. Selectivity is not defined on constants
+    /// which are not bools.
+    pub(crate) fn get_constant_selectivity(
+        const_expr: &ConstantPred,
+    ) -> Result<f64, SemanticError> {
+        match const_expr.value() {
+            Value::Bool(true) => Ok(1.0),
+            Value::Bool(false) => Ok(0.0),
+            value => Err(SemanticError::TypeMismatch(format!(
+                "{value} is not a boolean predicate"
+            ))),
         }
     }
 }
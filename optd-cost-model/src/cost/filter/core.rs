@@ -1,88 +1,177 @@
 use crate::{
     common::{
         nodes::{ArcPredicateNode, PredicateType, ReprPredicateNode},
-        predicates::{in_list_pred::InListPred, like_pred::LikePred, un_op_pred::UnOpType},
+        predicates::{
+            attr_index_pred::AttrIndexPred,
+            between_pred::BetweenPred,
+            bin_op_pred::BinOpPred,
+            cast_pred::CastPred,
+            constant_pred::ConstantPred,
+            in_list_pred::InListPred,
+            like_pred::LikePred,
+            un_op_pred::{UnOpPred, UnOpType},
+        },
+        properties::{attr_ref::AttrRef, Attribute},
         types::GroupId,
     },
     cost_model::CostModelImpl,
-    stats::UNIMPLEMENTED_SEL,
     storage::CostModelStorageManager,
-    CostModelResult, EstimatedStatistic,
+    CostModelResult, EstimatedStatistic, SemanticError,
 };
 
+/// The attributes that the attribute indices of a filter refer to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterAttrs {
+    /// The output attributes of a group.
+    Group(GroupId),
+    /// The attributes of the left child of a join followed by those of its right child. Semi
+    /// and anti joins only output the attributes of one child, but their filters can reference
+    /// both.
+    JoinChildren {
+        left: GroupId,
+        right: GroupId,
+        left_attr_cnt: usize,
+        right_attr_cnt: usize,
+    },
+}
+
+impl From<GroupId> for FilterAttrs {
+    fn from(group_id: GroupId) -> Self {
+        FilterAttrs::Group(group_id)
+    }
+}
+
+impl FilterAttrs {
+    /// Get the group and the index within that group of the `attr_ref_idx`-th attribute.
+    fn resolve(self, attr_ref_idx: u64) -> CostModelResult<(GroupId, u64)> {
+        match self {
+            FilterAttrs::Group(group_id) => Ok((group_id, attr_ref_idx)),
+            FilterAttrs::JoinChildren {
+                left,
+                right,
+                left_attr_cnt,
+                right_attr_cnt,
+            } => {
+                let idx = attr_ref_idx as usize;
+                if idx < left_attr_cnt {
+                    Ok((left, attr_ref_idx))
+                } else if idx < left_attr_cnt + right_attr_cnt {
+                    Ok((right, (idx - left_attr_cnt) as u64))
+                } else {
+                    Err(SemanticError::InvalidPredicate(format!(
+                        "attribute index {} is out of range for a join of {} and {} attributes",
+                        attr_ref_idx, left_attr_cnt, right_attr_cnt
+                    ))
+                    .into())
+                }
+            }
+        }
+    }
+}
+
 impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Get the attribute ref of the `attr_ref_idx`-th attribute of `attrs`.
+    pub(crate) fn get_filter_attr_ref(
+        &self,
+        attrs: FilterAttrs,
+        attr_ref_idx: u64,
+    ) -> CostModelResult<AttrRef> {
+        let (group_id, attr_ref_idx) = attrs.resolve(attr_ref_idx)?;
+        Ok(self.memo.get_attribute_ref(group_id, attr_ref_idx))
+    }
+
+    /// Get the type information of the `attr_ref_idx`-th attribute of `attrs`.
+    pub(crate) fn get_filter_attr_info(
+        &self,
+        attrs: FilterAttrs,
+        attr_ref_idx: u64,
+    ) -> CostModelResult<Attribute> {
+        let (group_id, attr_ref_idx) = attrs.resolve(attr_ref_idx)?;
+        Ok(self.memo.get_attribute_info(group_id, attr_ref_idx))
+    }
+
     // TODO: is it a good design to pass table_id here? I think it needs to be refactored.
     // Consider to remove table_id.
     pub async fn get_filter_row_cnt(
         &self,
         child_row_cnt: EstimatedStatistic,
-        group_id: GroupId,
+        attrs: impl Into<FilterAttrs>,
         cond: ArcPredicateNode,
     ) -> CostModelResult<EstimatedStatistic> {
-        let selectivity = { self.get_filter_selectivity(group_id, cond).await? };
+        let selectivity = { self.get_filter_selectivity(attrs, cond).await? };
         Ok(EstimatedStatistic((child_row_cnt.0 * selectivity).max(1.0)))
     }
 
     pub async fn get_filter_selectivity(
         &self,
-        group_id: GroupId,
+        attrs: impl Into<FilterAttrs>,
         expr_tree: ArcPredicateNode,
     ) -> CostModelResult<f64> {
+        let attrs = attrs.into();
         Box::pin(async move {
             match &expr_tree.typ {
-                PredicateType::Constant(_) => Ok(Self::get_constant_selectivity(expr_tree)),
-                PredicateType::AttrIndex => unimplemented!("check bool type or else panic"),
+                PredicateType::Constant(_) => {
+                    let constant_expr = ConstantPred::try_from_pred_node(expr_tree)?;
+                    Ok(Self::get_constant_selectivity(&constant_expr)?)
+                }
+                PredicateType::AttrIndex => {
+                    let attr_ref_idx = AttrIndexPred::try_from_pred_node(expr_tree)?.attr_index();
+                    self.get_bool_attribute_selectivity(attrs, attr_ref_idx).await
+                }
                 PredicateType::UnOp(un_op_typ) => {
-                    assert!(expr_tree.children.len() == 1);
-                    let child = expr_tree.child(0);
+                    let child = UnOpPred::try_from_pred_node(expr_tree.clone())?.child();
                     match un_op_typ {
                         // not doesn't care about nulls so there's no complex logic. it just reverses
                         // the selectivity for instance, != _will not_ include nulls
                         // but "NOT ==" _will_ include nulls
-                        UnOpType::Not => Ok(1.0 - self.get_filter_selectivity(group_id, child).await?),
-                        UnOpType::Neg => panic!(
-                            "the selectivity of operations that return numerical values is undefined"
-                        ),
+                        UnOpType::Not => Ok(1.0 - self.get_filter_selectivity(attrs, child).await?),
+                        UnOpType::Neg => Err(SemanticError::UnsupportedPredicate(
+                            "the selectivity of operations that return numerical values is undefined".to_string(),
+                        ).into()),
                     }
                 }
                 PredicateType::BinOp(bin_op_typ) => {
-                    assert!(expr_tree.children.len() == 2);
-                    let left_child = expr_tree.child(0);
-                    let right_child = expr_tree.child(1);
+                    let bin_op_expr = BinOpPred::try_from_pred_node(expr_tree.clone())?;
+                    let left_child = bin_op_expr.left_child();
+                    let right_child = bin_op_expr.right_child();
 
                     if bin_op_typ.is_comparison() {
-                        self.get_comp_op_selectivity(group_id, *bin_op_typ, left_child, right_child).await
-                    } else if bin_op_typ.is_numerical() {
-                        panic!(
-                            "the selectivity of operations that return numerical values is undefined"
-                        )
+                        self.get_comp_op_selectivity(attrs, *bin_op_typ, left_child, right_child).await
                     } else {
-                        unreachable!("all BinOpTypes should be true for at least one is_*() function")
+                        Err(SemanticError::UnsupportedPredicate(
+                            "the selectivity of operations that return numerical values is undefined".to_string(),
+                        ).into())
                     }
                 }
                 PredicateType::LogOp(log_op_typ) => {
-                    self.get_log_op_selectivity(group_id, *log_op_typ, &expr_tree.children).await
+                    self.get_log_op_selectivity(attrs, *log_op_typ, &expr_tree.children).await
                 }
-                PredicateType::Func(_) => unimplemented!("check bool type or else panic"),
-                PredicateType::SortOrder(_) => {
-                    panic!("the selectivity of sort order expressions is undefined")
+                PredicateType::Func(func_type) => Self::get_func_selectivity(func_type),
+                PredicateType::SortOrder(_) => Err(SemanticError::UnsupportedPredicate(
+                    "the selectivity of sort order expressions is undefined".to_string(),
+                ).into()),
+                PredicateType::Between => {
+                    let between_expr = BetweenPred::try_from_pred_node(expr_tree)?;
+                    self.get_between_selectivity(attrs, &between_expr).await
                 }
-                PredicateType::Between => Ok(UNIMPLEMENTED_SEL),
-                PredicateType::Cast => unimplemented!("check bool type or else panic"),
-                PredicateType::Like => {
-                    let like_expr = LikePred::from_pred_node(expr_tree).unwrap();
-                    self.get_like_selectivity(group_id, &like_expr).await
+                PredicateType::Cast => {
+                    let cast_expr = CastPred::try_from_pred_node(expr_tree)?;
+                    self.get_cast_selectivity(attrs, &cast_expr).await
                 }
-                PredicateType::DataType(_) => {
-                    panic!("the selectivity of a data type is not defined")
+                PredicateType::Like => {
+                    let like_expr = LikePred::try_from_pred_node(expr_tree)?;
+                    self.get_like_selectivity(attrs, &like_expr).await
                 }
+                PredicateType::DataType(_) => Err(SemanticError::UnsupportedPredicate(
+                    "the selectivity of a data type is not defined".to_string(),
+                ).into()),
                 PredicateType::InList => {
-                    let in_list_expr = InListPred::from_pred_node(expr_tree).unwrap();
-                    self.get_in_list_selectivity(group_id, &in_list_expr).await
+                    let in_list_expr = InListPred::try_from_pred_node(expr_tree)?;
+                    self.get_in_list_selectivity(attrs, &in_list_expr).await
                 }
-                _ => unreachable!(
-                    "all expression DfPredType were enumerated. this should be unreachable"
-                ),
+                PredicateType::List => Err(SemanticError::UnsupportedPredicate(
+                    "the selectivity of a list is not defined".to_string(),
+                ).into()),
             }
         }).await
     }
@@ -94,17 +183,30 @@ mod tests {
 
     use crate::{
         common::{
+            nodes::ReprPredicateNode,
+            nodes::{ArcPredicateNode, PredicateNode, PredicateType},
             predicates::{
-                bin_op_pred::BinOpType, constant_pred::ConstantType, log_op_pred::LogOpType,
-                un_op_pred::UnOpType,
+                bin_op_pred::BinOpType,
+                constant_pred::{ConstantPred, ConstantType},
+                func_pred::FuncType,
+                in_list_pred::InListPred,
+                like_pred::LikePred,
+                list_pred::ListPred,
+                log_op_pred::LogOpType,
+                un_op_pred::{UnOpPred, UnOpType},
             },
             types::TableId,
             values::Value,
         },
-        stats::{utilities::simple_map::SimpleMap, Distribution, MostCommonValues, DEFAULT_EQ_SEL},
+        stats::{
+            utilities::{histogram::EquiDepthHistogram, simple_map::SimpleMap},
+            Distribution, MostCommonValues, DEFAULT_BOOL_SEL, DEFAULT_EQ_SEL, DEFAULT_FUNC_SEL,
+        },
         test_utils::tests::*,
+        CostModelError, SemanticError,
     };
     use arrow_schema::DataType;
+    use datafusion_expr::{AggregateFunction, BuiltinScalarFunction};
 
     #[tokio::test]
     async fn test_const() {
@@ -579,6 +681,51 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_attr_ref_range_with_equi_depth_histogram() {
+        // 80% of the rows are spread over [0, 100] in 10 buckets, 10% are the MCV 50, and 10%
+        // are null.
+        let values = (0..=100).map(Value::Int32).collect::<Vec<_>>();
+        let per_attribute_stats = TestPerAttributeStats::new(
+            MostCommonValues::SimpleFrequency(SimpleMap::new(vec![(
+                vec![Some(Value::Int32(50))],
+                0.1,
+            )])),
+            Some(Distribution::EquiDepthHistogram(
+                EquiDepthHistogram::from_values(&values, 10, 0.8).unwrap(),
+            )),
+            101,
+            0.1,
+        );
+        let cost_model = create_mock_cost_model(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::from([(
+                TEST_ATTR1_BASE_INDEX,
+                per_attribute_stats,
+            )])],
+            vec![None],
+        );
+
+        let expr_tree = bin_op(BinOpType::Lt, attr_index(0), cnst(Value::Int32(60)));
+        let expr_tree_rev = bin_op(BinOpType::Leq, cnst(Value::Int32(60)), attr_index(0));
+
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                .await
+                .unwrap(),
+            0.8 * 0.6 + 0.1
+        );
+        // Nulls are neither below nor above the value.
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree_rev)
+                .await
+                .unwrap(),
+            0.8 * 0.4
+        );
+    }
+
     #[tokio::test]
     async fn test_and() {
         let per_attribute_stats = TestPerAttributeStats::new(
@@ -869,4 +1016,363 @@ mod tests {
             DEFAULT_EQ_SEL
         );
     }
+
+    fn bool_attr_stats() -> TestPerAttributeStats {
+        TestPerAttributeStats::new(
+            MostCommonValues::SimpleFrequency(SimpleMap::new(vec![
+                (vec![Some(Value::Bool(true))], 0.7),
+                (vec![Some(Value::Bool(false))], 0.2),
+            ])),
+            None,
+            2,
+            0.1,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_bool_attr_ref() {
+        let cost_model = create_mock_cost_model_with_attr_types(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::from([(TEST_ATTR1_BASE_INDEX, bool_attr_stats())])],
+            vec![HashMap::from([(TEST_ATTR1_BASE_INDEX, ConstantType::Bool)])],
+            vec![None],
+        );
+
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, attr_index(0))
+                .await
+                .unwrap(),
+            0.7
+        );
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, un_op(UnOpType::Not, attr_index(0)))
+                .await
+                .unwrap(),
+            0.3
+        );
+        // Casting a boolean to a boolean does not change anything.
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, cast(attr_index(0), DataType::Boolean))
+                .await
+                .unwrap(),
+            0.7
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bool_attr_ref_without_stats() {
+        let cost_model = create_mock_cost_model_with_attr_types(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::new()],
+            vec![HashMap::from([(TEST_ATTR1_BASE_INDEX, ConstantType::Bool)])],
+            vec![None],
+        );
+
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, attr_index(0))
+                .await
+                .unwrap(),
+            DEFAULT_BOOL_SEL
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cast_to_bool() {
+        let per_attribute_stats = TestPerAttributeStats::new(
+            MostCommonValues::SimpleFrequency(SimpleMap::new(vec![(
+                vec![Some(Value::Int32(0))],
+                0.4,
+            )])),
+            None,
+            10,
+            0.1,
+        );
+        let cost_model = create_mock_cost_model_with_attr_types(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::from([(
+                TEST_ATTR1_BASE_INDEX,
+                per_attribute_stats,
+            )])],
+            vec![HashMap::from([(
+                TEST_ATTR1_BASE_INDEX,
+                ConstantType::Int32,
+            )])],
+            vec![None],
+        );
+
+        // A number is true if it is neither zero nor null.
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, cast(attr_index(0), DataType::Boolean))
+                .await
+                .unwrap(),
+            1.0 - 0.4 - 0.1
+        );
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(
+                    TEST_GROUP1_ID,
+                    cast(cnst(Value::Int32(0)), DataType::Boolean)
+                )
+                .await
+                .unwrap(),
+            0.0
+        );
+        for (value, sel) in [(Value::Decimal128(0), 0.0), (Value::Decimal128(150), 1.0)] {
+            let decimal = ConstantPred::new_with_type(value, ConstantType::Decimal);
+            assert_approx_eq::assert_approx_eq!(
+                cost_model
+                    .get_filter_selectivity(
+                        TEST_GROUP1_ID,
+                        cast(decimal.into_pred_node(), DataType::Boolean)
+                    )
+                    .await
+                    .unwrap(),
+                sel
+            );
+        }
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(
+                    TEST_GROUP1_ID,
+                    cast(
+                        bin_op(BinOpType::Eq, attr_index(0), cnst(Value::Int32(0))),
+                        DataType::Boolean
+                    )
+                )
+                .await
+                .unwrap(),
+            0.4
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_bool_predicates_are_errors() {
+        let cost_model = create_mock_cost_model_with_attr_types(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::new()],
+            vec![HashMap::from([(
+                TEST_ATTR1_BASE_INDEX,
+                ConstantType::Utf8String,
+            )])],
+            vec![None],
+        );
+
+        for expr_tree in [
+            attr_index(0),
+            cast(attr_index(0), DataType::Boolean),
+            cast(attr_index(0), DataType::Int64),
+            cast(cnst(Value::String("true".into())), DataType::Boolean),
+        ] {
+            assert!(matches!(
+                cost_model
+                    .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                    .await,
+                Err(CostModelError::SemanticError(SemanticError::TypeMismatch(
+                    _
+                )))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_predicates_are_errors() {
+        let cost_model = create_mock_cost_model(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::from([(0, empty_per_attr_stats())])],
+            vec![None],
+        );
+        let node = |typ: PredicateType, children: Vec<ArcPredicateNode>, data: Option<Value>| {
+            ArcPredicateNode::from(PredicateNode {
+                typ,
+                children,
+                data,
+            })
+        };
+        let malformed_attr_index = || node(PredicateType::AttrIndex, vec![], None);
+        let malformed_cnst = || node(PredicateType::Constant(ConstantType::Int32), vec![], None);
+
+        for expr_tree in [
+            // An attribute ref without an index.
+            node(PredicateType::AttrIndex, vec![], None),
+            // A constant without a value.
+            node(PredicateType::Constant(ConstantType::Bool), vec![], None),
+            // Operators with missing children.
+            node(PredicateType::UnOp(UnOpType::Not), vec![], None),
+            node(
+                PredicateType::BinOp(BinOpType::Eq),
+                vec![attr_index(0)],
+                None,
+            ),
+            node(
+                PredicateType::Like,
+                vec![attr_index(0)],
+                Some(Value::Serialized(vec![0, 0].into())),
+            ),
+            node(
+                PredicateType::Between,
+                vec![attr_index(0), cnst(Value::Int32(1))],
+                Some(Value::Bool(false)),
+            ),
+            // An IN list whose list is not a list.
+            node(
+                PredicateType::InList,
+                vec![attr_index(0), cnst(Value::Int32(1))],
+                Some(Value::Bool(false)),
+            ),
+            // A cast without a target type.
+            node(PredicateType::Cast, vec![attr_index(0)], None),
+            cast(malformed_attr_index(), DataType::Boolean),
+            cast(malformed_cnst(), DataType::Boolean),
+            // Malformed children of well-formed nodes.
+            InListPred::new(
+                malformed_attr_index(),
+                ListPred::new(vec![cnst(Value::Int32(1))]),
+                false,
+            )
+            .into_pred_node(),
+            InListPred::new(
+                attr_index(0),
+                ListPred::new(vec![cnst(Value::Int32(1)), malformed_cnst()]),
+                false,
+            )
+            .into_pred_node(),
+            LikePred::new(
+                false,
+                false,
+                malformed_attr_index(),
+                cnst(Value::String("a%".into())),
+            )
+            .into_pred_node(),
+            LikePred::new(false, false, attr_index(0), malformed_cnst()).into_pred_node(),
+            bin_op(BinOpType::Eq, malformed_attr_index(), cnst(Value::Int32(1))),
+            bin_op(BinOpType::Lt, attr_index(0), malformed_cnst()),
+            bin_op(
+                BinOpType::Eq,
+                cast(malformed_cnst(), DataType::Int64),
+                attr_index(0),
+            ),
+            // Both sides of a comparison cast to different types.
+            bin_op(
+                BinOpType::Eq,
+                cast(attr_index(0), DataType::Int64),
+                cast(cnst(Value::Int32(1)), DataType::Utf8),
+            ),
+            // A malformed comparison inside a conjunction and a negation.
+            log_op(
+                LogOpType::And,
+                vec![
+                    bin_op(BinOpType::Eq, attr_index(0), cnst(Value::Int32(1))),
+                    bin_op(BinOpType::Eq, attr_index(0), malformed_cnst()),
+                ],
+            ),
+            UnOpPred::new(
+                bin_op(BinOpType::Eq, malformed_attr_index(), cnst(Value::Int32(1))),
+                UnOpType::Not,
+            )
+            .into_pred_node(),
+        ] {
+            let res = cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree.clone())
+                .await;
+            assert!(
+                matches!(
+                    res,
+                    Err(CostModelError::SemanticError(
+                        SemanticError::InvalidPredicate(_)
+                    ))
+                ),
+                "{expr_tree} should be invalid, got {res:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_predicates_are_errors() {
+        let cost_model = create_mock_cost_model(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::from([(0, empty_per_attr_stats())])],
+            vec![None],
+        );
+
+        for expr_tree in [
+            un_op(UnOpType::Neg, attr_index(0)),
+            bin_op(BinOpType::Add, attr_index(0), cnst(Value::Int32(1))),
+            list(vec![attr_index(0)]),
+            ArcPredicateNode::from(PredicateNode {
+                typ: PredicateType::DataType(DataType::Int32),
+                children: vec![],
+                data: None,
+            }),
+        ] {
+            let res = cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree.clone())
+                .await;
+            assert!(
+                matches!(
+                    res,
+                    Err(CostModelError::SemanticError(
+                        SemanticError::UnsupportedPredicate(_)
+                    ))
+                ),
+                "{expr_tree} should be unsupported, got {res:?}"
+            );
+        }
+
+        // Constants other than booleans do not filter anything.
+        assert!(matches!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, cnst(Value::Int32(1)))
+                .await,
+            Err(CostModelError::SemanticError(SemanticError::TypeMismatch(
+                _
+            )))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_func() {
+        let cost_model = create_mock_cost_model(
+            vec![TEST_TABLE1_ID],
+            vec![HashMap::from([(0, empty_per_attr_stats())])],
+            vec![None],
+        );
+        let func = |func_type: FuncType| -> ArcPredicateNode {
+            PredicateNode {
+                typ: PredicateType::Func(func_type),
+                children: vec![attr_index(0)],
+                data: None,
+            }
+            .into()
+        };
+
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(
+                    TEST_GROUP1_ID,
+                    func(FuncType::new_scalar(BuiltinScalarFunction::Isnan))
+                )
+                .await
+                .unwrap(),
+            DEFAULT_FUNC_SEL
+        );
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, func(FuncType::Case))
+                .await
+                .unwrap(),
+            DEFAULT_BOOL_SEL
+        );
+        assert!(cost_model
+            .get_filter_selectivity(
+                TEST_GROUP1_ID,
+                func(FuncType::new_agg(AggregateFunction::Count))
+            )
+            .await
+            .is_err());
+    }
 }
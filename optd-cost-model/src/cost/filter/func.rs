@@ -0,0 +1,30 @@
+use crate::{
+    common::predicates::func_pred::FuncType,
+    cost_model::CostModelImpl,
+    stats::{DEFAULT_BOOL_SEL, DEFAULT_FUNC_SEL},
+    storage::CostModelStorageManager,
+    CostModelResult, SemanticError,
+};
+
+impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Get the selectivity of a function used as a predicate, i.e. "WHERE f(A)".
+    ///
+    /// There are no statistics on the results of functions, so scalar functions use
+    /// DEFAULT_FUNC_SEL and CASE expressions use DEFAULT_BOOL_SEL. Scalar functions are assumed
+    /// to return booleans, since the planner would have rejected the filter otherwise.
+    /// Aggregate and window functions cannot be evaluated on a single row, so they are an error.
+    pub(crate) fn get_func_selectivity(func_type: &FuncType) -> CostModelResult<f64> {
+        match func_type {
+            FuncType::Scalar(_) => Ok(DEFAULT_FUNC_SEL),
+            FuncType::Case => Ok(DEFAULT_BOOL_SEL),
+            FuncType::Agg(agg_func) => Err(SemanticError::UnsupportedPredicate(format!(
+                "aggregate function {agg_func} is not a filter predicate"
+            ))
+            .into()),
+            FuncType::Window(window_func) => Err(SemanticError::UnsupportedPredicate(format!(
+                "window function {window_func} is not a filter predicate"
+            ))
+            .into()),
+        }
+    }
+}
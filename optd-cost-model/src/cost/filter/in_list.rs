@@ -5,8 +5,8 @@ use crate::{
             attr_index_pred::AttrIndexPred, constant_pred::ConstantPred, in_list_pred::InListPred,
         },
         properties::attr_ref::{AttrRef, BaseTableAttrRef},
-        types::GroupId,
     },
+    cost::filter::core::FilterAttrs,
     cost_model::CostModelImpl,
     stats::UNIMPLEMENTED_SEL,
     storage::CostModelStorageManager,
@@ -18,7 +18,7 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
     /// val1, val2, val3 are constants.
     pub(crate) async fn get_in_list_selectivity(
         &self,
-        group_id: GroupId,
+        attrs: FilterAttrs,
         expr: &InListPred,
     ) -> CostModelResult<f64> {
         let child = expr.child();
@@ -38,20 +38,17 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         }
 
         // Convert child and const expressions to concrete types.
-        let attr_ref_pred = AttrIndexPred::from_pred_node(child).unwrap();
+        let attr_ref_pred = AttrIndexPred::try_from_pred_node(child)?;
         let attr_ref_idx = attr_ref_pred.attr_index();
 
         let list_exprs = list_exprs
             .into_iter()
-            .map(|expr| {
-                ConstantPred::from_pred_node(expr)
-                    .expect("we already checked all list elements are constants")
-            })
-            .collect::<Vec<_>>();
+            .map(ConstantPred::try_from_pred_node)
+            .collect::<Result<Vec<_>, _>>()?;
         let negated = expr.negated();
 
         if let AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) =
-            self.memo.get_attribute_ref(group_id, attr_ref_idx)
+            self.get_filter_attr_ref(attrs, attr_ref_idx)?
         {
             let mut in_sel = 0.0;
             for expr in &list_exprs {
@@ -110,7 +107,10 @@ mod tests {
 
         assert_approx_eq::assert_approx_eq!(
             cost_model
-                .get_in_list_selectivity(TEST_GROUP1_ID, &in_list(0, vec![Value::Int32(1)], false))
+                .get_in_list_selectivity(
+                    TEST_GROUP1_ID.into(),
+                    &in_list(0, vec![Value::Int32(1)], false)
+                )
                 .await
                 .unwrap(),
             0.8
@@ -118,7 +118,7 @@ mod tests {
         assert_approx_eq::assert_approx_eq!(
             cost_model
                 .get_in_list_selectivity(
-                    TEST_GROUP1_ID,
+                    TEST_GROUP1_ID.into(),
                     &in_list(0, vec![Value::Int32(1), Value::Int32(2)], false)
                 )
                 .await
@@ -127,14 +127,20 @@ mod tests {
         );
         assert_approx_eq::assert_approx_eq!(
             cost_model
-                .get_in_list_selectivity(TEST_GROUP1_ID, &in_list(0, vec![Value::Int32(3)], false))
+                .get_in_list_selectivity(
+                    TEST_GROUP1_ID.into(),
+                    &in_list(0, vec![Value::Int32(3)], false)
+                )
                 .await
                 .unwrap(),
             0.0
         );
         assert_approx_eq::assert_approx_eq!(
             cost_model
-                .get_in_list_selectivity(TEST_GROUP1_ID, &in_list(0, vec![Value::Int32(1)], true))
+                .get_in_list_selectivity(
+                    TEST_GROUP1_ID.into(),
+                    &in_list(0, vec![Value::Int32(1)], true)
+                )
                 .await
                 .unwrap(),
             0.2
@@ -142,7 +148,7 @@ mod tests {
         assert_approx_eq::assert_approx_eq!(
             cost_model
                 .get_in_list_selectivity(
-                    TEST_GROUP1_ID,
+                    TEST_GROUP1_ID.into(),
                     &in_list(0, vec![Value::Int32(1), Value::Int32(2)], true)
                 )
                 .await
@@ -151,7 +157,10 @@ mod tests {
         );
         assert_approx_eq::assert_approx_eq!(
             cost_model
-                .get_in_list_selectivity(TEST_GROUP1_ID, &in_list(0, vec![Value::Int32(3)], true)) // TODO: Fix this
+                .get_in_list_selectivity(
+                    TEST_GROUP1_ID.into(),
+                    &in_list(0, vec![Value::Int32(3)], true)
+                ) // TODO: Fix this
                 .await
                 .unwrap(),
             1.0
@@ -1,6 +1,23 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use itertools::Itertools;
+
 use crate::{
-    common::{nodes::ArcPredicateNode, predicates::log_op_pred::LogOpType, types::GroupId},
+    common::{
+        nodes::{ArcPredicateNode, PredicateType, ReprPredicateNode},
+        predicates::{
+            attr_index_pred::AttrIndexPred,
+            bin_op_pred::{BinOpPred, BinOpType},
+            constant_pred::ConstantPred,
+            log_op_pred::LogOpType,
+        },
+        properties::attr_ref::{AttrRef, BaseTableAttrRef},
+        types::TableId,
+        values::Value,
+    },
+    cost::filter::core::FilterAttrs,
     cost_model::CostModelImpl,
+    stats::{AttributeCombValue, MAX_ATTR_COMB_LEN, MAX_ATTR_COMB_LOOKUPS},
     storage::CostModelStorageManager,
     CostModelResult,
 };
@@ -8,15 +25,16 @@ use crate::{
 impl<S: CostModelStorageManager> CostModelImpl<S> {
     pub(crate) async fn get_log_op_selectivity(
         &self,
-        group_id: GroupId,
+        attrs: FilterAttrs,
         log_op_typ: LogOpType,
         children: &[ArcPredicateNode],
     ) -> CostModelResult<f64> {
         match log_op_typ {
             LogOpType::And => {
-                let mut and_sel = 1.0;
-                for child in children {
-                    let selectivity = self.get_filter_selectivity(group_id, child.clone()).await?;
+                let (mut and_sel, remaining_children) =
+                    self.get_joint_equality_selectivity(attrs, children).await?;
+                for child in remaining_children {
+                    let selectivity = self.get_filter_selectivity(attrs, child).await?;
                     and_sel *= selectivity;
                 }
                 Ok(and_sel)
@@ -24,11 +42,298 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             LogOpType::Or => {
                 let mut or_sel_neg = 1.0;
                 for child in children {
-                    let selectivity = self.get_filter_selectivity(group_id, child.clone()).await?;
+                    let selectivity = self.get_filter_selectivity(attrs, child.clone()).await?;
                     or_sel_neg *= 1.0 - selectivity;
                 }
                 Ok(1.0 - or_sel_neg)
             }
         }
     }
+
+    /// Estimates the equality predicates of a conjunction that are covered by multi-attribute
+    /// statistics, e.g. `city = 'Pittsburgh' AND zip = '15213'` with statistics on
+    /// `(city, zip)`, instead of assuming that the attributes are independent.
+    ///
+    /// For each table, the largest combinations of the attributes compared to constants are
+    /// tried first, up to `MAX_ATTR_COMB_LEN` attributes and `MAX_ATTR_COMB_LOOKUPS` lookups.
+    /// Returns the selectivity of the covered predicates together with the children that are
+    /// left to be estimated independently.
+    async fn get_joint_equality_selectivity(
+        &self,
+        attrs: FilterAttrs,
+        children: &[ArcPredicateNode],
+    ) -> CostModelResult<(f64, Vec<ArcPredicateNode>)> {
+        // The `attr = constant` children, by table and attribute.
+        let mut eq_children: HashMap<TableId, HashMap<u64, (Value, usize)>> = HashMap::new();
+        let mut duplicated_attrs = vec![];
+        for (child_idx, child) in children.iter().enumerate() {
+            let Some((attr_ref_idx, value)) = get_attr_eq_constant(child) else {
+                continue;
+            };
+            if let AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) =
+                self.get_filter_attr_ref(attrs, attr_ref_idx)?
+            {
+                match eq_children.entry(table_id).or_default().entry(attr_idx) {
+                    Entry::Occupied(_) => duplicated_attrs.push((table_id, attr_idx)),
+                    Entry::Vacant(entry) => {
+                        entry.insert((value, child_idx));
+                    }
+                }
+            }
+        }
+        // An attribute compared to several constants is left to the independent estimation.
+        for (table_id, attr_idx) in duplicated_attrs {
+            eq_children.get_mut(&table_id).unwrap().remove(&attr_idx);
+        }
+
+        let mut joint_sel = 1.0;
+        let mut covered_children = vec![false; children.len()];
+        for (table_id, mut table_eq_children) in eq_children {
+            let mut lookup_cnt = 0;
+            while table_eq_children.len() >= 2 {
+                let eq_attrs = table_eq_children.keys().copied().sorted().collect_vec();
+                let mut covered_attrs = None;
+                'search: for comb_len in (2..=eq_attrs.len().min(MAX_ATTR_COMB_LEN)).rev() {
+                    for attr_comb in eq_attrs.iter().copied().combinations(comb_len) {
+                        if lookup_cnt == MAX_ATTR_COMB_LOOKUPS {
+                            break 'search;
+                        }
+                        lookup_cnt += 1;
+                        let values = attr_comb
+                            .iter()
+                            .map(|attr_idx| Some(table_eq_children[attr_idx].0.clone()))
+                            .collect_vec();
+                        if let Some(sel) = self
+                            .get_attribute_comb_equality_selectivity(table_id, &attr_comb, &values)
+                            .await?
+                        {
+                            joint_sel *= sel;
+                            covered_attrs = Some(attr_comb);
+                            break 'search;
+                        }
+                    }
+                }
+                let Some(covered_attrs) = covered_attrs else {
+                    break;
+                };
+                for attr_idx in covered_attrs {
+                    let (_, child_idx) = table_eq_children.remove(&attr_idx).unwrap();
+                    covered_children[child_idx] = true;
+                }
+            }
+        }
+
+        let remaining_children = children
+            .iter()
+            .zip(covered_children)
+            .filter(|(_, covered)| !covered)
+            .map(|(child, _)| child.clone())
+            .collect();
+        Ok((joint_sel, remaining_children))
+    }
+
+    /// Get the selectivity of `(attr_1, ..., attr_n) = (value_1, ..., value_n)` from the
+    /// statistics of the attribute combination, or `None` if there are no such statistics.
+    ///
+    /// This is the multi-attribute counterpart of `get_attribute_equality_selectivity`.
+    async fn get_attribute_comb_equality_selectivity(
+        &self,
+        table_id: TableId,
+        attr_comb: &[u64],
+        values: &AttributeCombValue,
+    ) -> CostModelResult<Option<f64>> {
+        let Some(stats) = self.get_attribute_comb_stats(table_id, attr_comb).await? else {
+            return Ok(None);
+        };
+        // The storage may return empty statistics for combinations that were never analyzed.
+        if stats.ndistinct == 0 && stats.mcvs.cnt() == 0 {
+            return Ok(None);
+        }
+        if let Some(freq) = stats.mcvs.freq(values) {
+            return Ok(Some(freq));
+        }
+        let non_mcv_cnt = (stats.ndistinct as usize).saturating_sub(stats.mcvs.cnt());
+        if non_mcv_cnt == 0 {
+            return Ok(Some(0.0));
+        }
+        let non_mcv_freq = 1.0 - stats.mcvs.total_freq() - stats.null_frac;
+        Ok(Some((non_mcv_freq / non_mcv_cnt as f64).clamp(0.0, 1.0)))
+    }
+}
+
+/// Returns the attribute index and the value of an `attr = constant` (or `constant = attr`)
+/// predicate.
+pub(crate) fn get_attr_eq_constant(expr: &ArcPredicateNode) -> Option<(u64, Value)> {
+    if expr.typ != PredicateType::BinOp(BinOpType::Eq) {
+        return None;
+    }
+    let bin_op = BinOpPred::from_pred_node(expr.clone())?;
+    let (left, right) = (bin_op.left_child(), bin_op.right_child());
+    let (attr, constant) = match (&left.typ, &right.typ) {
+        (PredicateType::AttrIndex, PredicateType::Constant(_)) => (left, right),
+        (PredicateType::Constant(_), PredicateType::AttrIndex) => (right, left),
+        _ => return None,
+    };
+    Some((
+        AttrIndexPred::from_pred_node(attr)?.attr_index(),
+        ConstantPred::from_pred_node(constant)?.value(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        common::{
+            nodes::ArcPredicateNode,
+            predicates::{bin_op_pred::BinOpType, log_op_pred::LogOpType},
+            values::Value,
+        },
+        stats::{utilities::simple_map::SimpleMap, MostCommonValues, DEFAULT_EQ_SEL},
+        storage::mock::TableStats,
+        test_utils::tests::*,
+    };
+
+    /// Table 1 has a city attribute and a zip attribute, each with 10 distinct values, which are
+    /// perfectly correlated, and an independent attribute with 2 distinct values.
+    fn create_cost_model(with_joint_stats: bool) -> TestOptCostModelMock {
+        let single =
+            |ndistinct| TestPerAttributeStats::new(MostCommonValues::empty(), None, ndistinct, 0.0);
+        let mut column_comb_stats = HashMap::from([
+            (vec![0], single(10)),
+            (vec![1], single(10)),
+            (vec![2], single(2)),
+        ]);
+        if with_joint_stats {
+            column_comb_stats.insert(
+                vec![0, 1],
+                TestPerAttributeStats::new(
+                    MostCommonValues::SimpleFrequency(SimpleMap::new(vec![(
+                        vec![Some(Value::Int32(1)), Some(Value::Int32(15213))],
+                        0.3,
+                    )])),
+                    None,
+                    10,
+                    0.0,
+                ),
+            );
+        }
+        create_mock_cost_model_with_table_stats(
+            HashMap::from([(TEST_TABLE1_ID, TableStats::new(100, column_comb_stats))]),
+            vec![
+                (TEST_TABLE1_ID, 0, None),
+                (TEST_TABLE1_ID, 1, None),
+                (TEST_TABLE1_ID, 2, None),
+                (TEST_TABLE2_ID, 0, None),
+            ],
+        )
+    }
+
+    fn eq(attr_idx: u64, value: i32) -> ArcPredicateNode {
+        bin_op(
+            BinOpType::Eq,
+            attr_index(attr_idx),
+            cnst(Value::Int32(value)),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_and_uses_joint_stats() {
+        let cost_model = create_cost_model(true);
+
+        // An MCV of the combination.
+        let expr_tree = log_op(LogOpType::And, vec![eq(0, 1), eq(1, 15213)]);
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                .await
+                .unwrap(),
+            0.3
+        );
+
+        // A value that is not an MCV shares the rest with the other 9 distinct values, whatever
+        // the order of the predicates. The last predicate is on another attribute.
+        let expr_tree = log_op(LogOpType::And, vec![eq(2, 0), eq(1, 15217), eq(0, 2)]);
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                .await
+                .unwrap(),
+            0.7 / 9.0 * 0.5
+        );
+
+        // The other table has no statistics, so it is estimated independently.
+        let expr_tree = log_op(LogOpType::And, vec![eq(0, 1), eq(1, 15213), eq(3, 1)]);
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                .await
+                .unwrap(),
+            0.3 * DEFAULT_EQ_SEL
+        );
+    }
+
+    #[tokio::test]
+    async fn test_and_without_joint_stats() {
+        let cost_model = create_cost_model(false);
+
+        let expr_tree = log_op(LogOpType::And, vec![eq(0, 1), eq(1, 15213), eq(2, 0)]);
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                .await
+                .unwrap(),
+            0.1 * 0.1 * 0.5
+        );
+    }
+
+    #[tokio::test]
+    async fn test_and_same_attr_is_independent() {
+        let cost_model = create_cost_model(true);
+
+        // The attribute 0 is compared twice, so only the attribute 1 is left and there is no
+        // combination to use.
+        let expr_tree = log_op(LogOpType::And, vec![eq(0, 1), eq(0, 2), eq(1, 15213)]);
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                .await
+                .unwrap(),
+            0.1 * 0.1 * 0.1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_and_many_attrs_is_bounded() {
+        // Without a bound, there would be more than 2^40 combinations to look up.
+        let attr_cnt = 40;
+        let stats = TestPerAttributeStats::new(MostCommonValues::empty(), None, 2, 0.0);
+        let cost_model = create_mock_cost_model_with_table_stats(
+            HashMap::from([(
+                TEST_TABLE1_ID,
+                TableStats::new(
+                    100,
+                    (0..attr_cnt)
+                        .map(|attr_idx| (vec![attr_idx], stats.clone()))
+                        .collect(),
+                ),
+            )]),
+            (0..attr_cnt)
+                .map(|attr_idx| (TEST_TABLE1_ID, attr_idx, None))
+                .collect(),
+        );
+
+        let expr_tree = log_op(
+            LogOpType::And,
+            (0..attr_cnt).map(|attr_idx| eq(attr_idx, 1)).collect(),
+        );
+        assert_approx_eq::assert_approx_eq!(
+            cost_model
+                .get_filter_selectivity(TEST_GROUP1_ID, expr_tree)
+                .await
+                .unwrap(),
+            0.5f64.powi(attr_cnt as i32)
+        );
+    }
 }
@@ -1,7 +1,10 @@
 pub mod attribute;
+pub mod between;
+pub mod cast;
 pub mod comp_op;
 pub mod constant;
 pub mod core;
+pub mod func;
 pub mod in_list;
 pub mod like;
 pub mod log_op;
@@ -15,11 +15,14 @@ use crate::{
         },
         types::GroupId,
     },
-    cost::join::get_on_attr_ref_pair,
+    cost::{
+        filter::core::FilterAttrs,
+        join::{get_attr_ref, get_on_attr_ref_pair},
+    },
     cost_model::CostModelImpl,
     stats::DEFAULT_NUM_DISTINCT,
     storage::CostModelStorageManager,
-    CostModelResult,
+    CostModelError, CostModelResult, SemanticError,
 };
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
@@ -28,24 +31,38 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
     ///
     /// This is a "wrapper" to separate the equality conditions from the filter conditions before
     /// calling the "main" `get_join_selectivity_core` function.
+    ///
+    /// The first `left_attr_cnt` attributes of `attr_refs` belong to the left child, which is
+    /// used to put the left attribute first in each on condition. The filter conditions are
+    /// resolved against `filter_attrs`.
     #[allow(clippy::too_many_arguments)]
     pub(crate) async fn get_join_selectivity_from_expr_tree(
         &self,
         join_typ: JoinType,
-        group_id: GroupId,
+        filter_attrs: FilterAttrs,
         expr_tree: ArcPredicateNode,
         attr_refs: &AttrRefs,
         input_correlation: Option<SemanticCorrelation>,
         left_row_cnt: f64,
         right_row_cnt: f64,
+        left_attr_cnt: usize,
     ) -> CostModelResult<f64> {
+        let get_on_attr_ref_pair = |expr_tree| {
+            Ok::<_, CostModelError>(get_on_attr_ref_pair(expr_tree, attr_refs)?.map(
+                |(left, right)| {
+                    if left.attr_index() as usize >= left_attr_cnt {
+                        (right, left)
+                    } else {
+                        (left, right)
+                    }
+                },
+            ))
+        };
         if expr_tree.typ == PredicateType::LogOp(LogOpType::And) {
             let mut on_attr_ref_pairs = vec![];
             let mut filter_expr_trees = vec![];
             for child_expr_tree in &expr_tree.children {
-                if let Some(on_attr_ref_pair) =
-                    get_on_attr_ref_pair(child_expr_tree.clone(), attr_refs)
-                {
+                if let Some(on_attr_ref_pair) = get_on_attr_ref_pair(child_expr_tree.clone())? {
                     on_attr_ref_pairs.push(on_attr_ref_pair)
                 } else {
                     let child_expr = child_expr_tree.clone();
@@ -60,7 +77,7 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             };
             self.get_join_selectivity_core(
                 join_typ,
-                group_id,
+                filter_attrs,
                 on_attr_ref_pairs,
                 filter_expr_tree,
                 attr_refs,
@@ -72,10 +89,10 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             .await
         } else {
             #[allow(clippy::collapsible_else_if)]
-            if let Some(on_attr_ref_pair) = get_on_attr_ref_pair(expr_tree.clone(), attr_refs) {
+            if let Some(on_attr_ref_pair) = get_on_attr_ref_pair(expr_tree.clone())? {
                 self.get_join_selectivity_core(
                     join_typ,
-                    group_id,
+                    filter_attrs,
                     vec![on_attr_ref_pair],
                     None,
                     attr_refs,
@@ -88,7 +105,7 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             } else {
                 self.get_join_selectivity_core(
                     join_typ,
-                    group_id,
+                    filter_attrs,
                     vec![],
                     Some(expr_tree),
                     attr_refs,
@@ -116,7 +133,14 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         right_row_cnt: f64,
         left_attr_cnt: usize,
     ) -> CostModelResult<f64> {
-        assert!(left_keys.len() == right_keys.len());
+        if left_keys.len() != right_keys.len() {
+            return Err(SemanticError::InvalidPredicate(format!(
+                "{} left join keys but {} right join keys",
+                left_keys.len(),
+                right_keys.len()
+            ))
+            .into());
+        }
         // I assume that the keys are already in the right order
         // s.t. the ith key of left_keys corresponds with the ith key of right_keys
         let on_attr_ref_pairs = left_keys
@@ -124,15 +148,15 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             .into_iter()
             .zip(right_keys.to_vec())
             .map(|(left_key, right_key)| {
-                (
-                    AttrIndexPred::from_pred_node(left_key).expect("keys should be AttrRefPreds"),
-                    AttrIndexPred::from_pred_node(right_key).expect("keys should be AttrRefPreds"),
-                )
+                Ok((
+                    AttrIndexPred::try_from_pred_node(left_key)?,
+                    AttrIndexPred::try_from_pred_node(right_key)?,
+                ))
             })
-            .collect_vec();
+            .collect::<Result<Vec<_>, SemanticError>>()?;
         self.get_join_selectivity_core(
             join_typ,
-            group_id,
+            group_id.into(),
             on_attr_ref_pairs,
             None,
             attr_refs,
@@ -156,11 +180,16 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
     /// For NLJ, the right table attributes indices are with respect to the output of the join.
     /// For example, if the left table has 3 attributes, the first attribute of the right table
     /// is #3 instead of #0.
+    ///
+    /// In both cases, the first attribute of each on attribute pair belongs to the left table.
+    ///
+    /// The selectivity is always relative to the cartesian product of the two tables, including
+    /// for semi and anti joins whose output only contains rows of one table.
     #[allow(clippy::too_many_arguments)]
     async fn get_join_selectivity_core(
         &self,
         join_typ: JoinType,
-        group_id: GroupId,
+        filter_attrs: FilterAttrs,
         on_attr_ref_pairs: Vec<(AttrIndexPred, AttrIndexPred)>,
         filter_expr_tree: Option<ArcPredicateNode>,
         attr_refs: &AttrRefs,
@@ -169,14 +198,6 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         right_row_cnt: f64,
         right_attr_ref_offset: usize,
     ) -> CostModelResult<f64> {
-        let join_on_selectivity = self
-            .get_join_on_selectivity(
-                &on_attr_ref_pairs,
-                attr_refs,
-                input_correlation,
-                right_attr_ref_offset,
-            )
-            .await?;
         // Currently, there is no difference in how we handle a join filter and a select filter,
         // so we use the same function.
         //
@@ -185,26 +206,124 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         // get_filter_selectivity() function, but this may change in the future.
         let join_filter_selectivity = match filter_expr_tree {
             Some(filter_expr_tree) => {
-                self.get_filter_selectivity(group_id, filter_expr_tree)
+                self.get_filter_selectivity(filter_attrs, filter_expr_tree)
                     .await?
             }
             None => 1.0,
         };
+
+        if join_typ.is_semi_or_anti() {
+            let is_left_outer = matches!(join_typ, JoinType::LeftSemi | JoinType::LeftAnti);
+            let (outer_row_cnt, inner_row_cnt) = if is_left_outer {
+                (left_row_cnt, right_row_cnt)
+            } else {
+                (right_row_cnt, left_row_cnt)
+            };
+            let semi_join_selectivity = self
+                .get_semi_join_selectivity(
+                    &on_attr_ref_pairs,
+                    attr_refs,
+                    right_attr_ref_offset,
+                    is_left_outer,
+                    outer_row_cnt,
+                    inner_row_cnt,
+                )
+                .await?
+                * join_filter_selectivity;
+            // An anti join keeps exactly the outer rows that a semi join drops.
+            let outer_selectivity = match join_typ {
+                JoinType::LeftSemi | JoinType::RightSemi => semi_join_selectivity,
+                _ => 1.0 - semi_join_selectivity,
+            };
+            // The cartesian product is empty, so any selectivity works and 0 avoids dividing by
+            // zero.
+            if inner_row_cnt <= 0.0 {
+                return Ok(0.0);
+            }
+            return Ok(outer_selectivity / inner_row_cnt);
+        }
+
+        let join_on_selectivity = self
+            .get_join_on_selectivity(
+                &on_attr_ref_pairs,
+                attr_refs,
+                input_correlation,
+                right_attr_ref_offset,
+            )
+            .await?;
         let inner_join_selectivity = join_on_selectivity * join_filter_selectivity;
 
-        Ok(match join_typ {
-            JoinType::Inner => inner_join_selectivity,
-            JoinType::LeftOuter => f64::max(inner_join_selectivity, 1.0 / right_row_cnt),
-            JoinType::RightOuter => f64::max(inner_join_selectivity, 1.0 / left_row_cnt),
-            JoinType::Cross => {
-                assert!(
-                    on_attr_ref_pairs.is_empty(),
-                    "Cross joins should not have on attributes"
-                );
-                join_filter_selectivity
+        match join_typ {
+            JoinType::Inner => Ok(inner_join_selectivity),
+            JoinType::LeftOuter => Ok(f64::max(inner_join_selectivity, 1.0 / right_row_cnt)),
+            JoinType::RightOuter => Ok(f64::max(inner_join_selectivity, 1.0 / left_row_cnt)),
+            // A full outer join outputs every row of both tables at least once, so it has at
+            // least as many rows as the larger table.
+            JoinType::FullOuter => Ok(inner_join_selectivity
+                .max(1.0 / left_row_cnt)
+                .max(1.0 / right_row_cnt)),
+            JoinType::Cross if on_attr_ref_pairs.is_empty() => Ok(join_filter_selectivity),
+            JoinType::Cross => Err(SemanticError::InvalidPredicate(
+                "cross joins should not have on attributes".to_string(),
+            )
+            .into()),
+            _ => Err(SemanticError::UnsupportedJoinType(join_typ).into()),
+        }
+    }
+
+    /// Get the fraction of the rows of the outer table of a semi join that have at least one
+    /// match in the inner table, i.e. the selectivity of `EXISTS` or `IN` with a subquery.
+    ///
+    /// Each on condition is estimated as in Postgres' `eqjoinsel_semi` when there are no MCVs: if
+    /// the inner key has at least as many distinct values as the outer key, every non-null outer
+    /// key is assumed to have a match, otherwise only `ndistinct(inner) / ndistinct(outer)` of
+    /// them do. Null keys never match. The on conditions are assumed to be independent.
+    async fn get_semi_join_selectivity(
+        &self,
+        on_attr_ref_pairs: &[(AttrIndexPred, AttrIndexPred)],
+        attr_refs: &AttrRefs,
+        right_attr_ref_offset: usize,
+        is_left_outer: bool,
+        outer_row_cnt: f64,
+        inner_row_cnt: f64,
+    ) -> CostModelResult<f64> {
+        let mut selectivity = 1.0;
+        for (left, right) in on_attr_ref_pairs {
+            let left_attr_ref = get_attr_ref(attr_refs, left.attr_index() as usize)?;
+            let right_attr_ref = get_attr_ref(
+                attr_refs,
+                right.attr_index() as usize + right_attr_ref_offset,
+            )?;
+            let (outer_attr_ref, inner_attr_ref) = if is_left_outer {
+                (left_attr_ref, right_attr_ref)
+            } else {
+                (right_attr_ref, left_attr_ref)
+            };
+
+            let (outer_ndistinct, outer_null_frac) =
+                self.get_join_key_stats(outer_attr_ref).await?;
+            let (inner_ndistinct, _) = self.get_join_key_stats(inner_attr_ref).await?;
+            // A table cannot have more distinct values than rows.
+            let outer_ndistinct = (outer_ndistinct as f64).min(outer_row_cnt).max(1.0);
+            let inner_ndistinct = (inner_ndistinct as f64).min(inner_row_cnt).max(1.0);
+
+            selectivity *= (1.0 - outer_null_frac) * (inner_ndistinct / outer_ndistinct).min(1.0);
+        }
+        Ok(selectivity)
+    }
+
+    /// Get the ndistinct and null fraction of a join key, or defaults if it is derived or has no
+    /// statistics.
+    async fn get_join_key_stats(&self, attr_ref: &AttrRef) -> CostModelResult<(u64, f64)> {
+        if let AttrRef::BaseTableAttrRef(base_attr_ref) = attr_ref {
+            if let Some(stats) = self
+                .get_attribute_comb_stats(base_attr_ref.table_id, &[base_attr_ref.attr_idx])
+                .await?
+            {
+                return Ok((stats.ndistinct, stats.null_frac));
             }
-            _ => unimplemented!("join_typ={} is not implemented", join_typ),
-        })
+        }
+        Ok((DEFAULT_NUM_DISTINCT, 0.0))
     }
 
     /// Get the selectivity of one attribute eq predicate, e.g. attrA = attrB.
@@ -379,9 +498,11 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
         // Multiply the selectivities of all individual conditions together
         let mut selectivity = 1.0;
         for on_attr_ref_pair in on_attr_ref_pairs {
-            let left_attr_ref = &attr_refs[on_attr_ref_pair.0.attr_index() as usize];
-            let right_attr_ref =
-                &attr_refs[on_attr_ref_pair.1.attr_index() as usize + right_attr_ref_offset];
+            let left_attr_ref = get_attr_ref(attr_refs, on_attr_ref_pair.0.attr_index() as usize)?;
+            let right_attr_ref = get_attr_ref(
+                attr_refs,
+                on_attr_ref_pair.1.attr_index() as usize + right_attr_ref_offset,
+            )?;
 
             selectivity *=
                 if let (AttrRef::BaseTableAttrRef(left), AttrRef::BaseTableAttrRef(right)) =
@@ -413,23 +534,28 @@ mod tests {
         common::{
             predicates::bin_op_pred::BinOpType,
             properties::{attr_ref, Attribute},
+            types::TableId,
             values::Value,
         },
-        stats::DEFAULT_EQ_SEL,
+        stats::{MostCommonValues, DEFAULT_EQ_SEL, DEFAULT_INEQ_SEL},
         test_utils::tests::MemoGroupInfo,
         test_utils::tests::{
             attr_index, bin_op, cnst, create_four_table_mock_cost_model, create_mock_cost_model,
             create_three_table_mock_cost_model, create_two_table_mock_cost_model,
             create_two_table_mock_cost_model_custom_row_cnts, empty_per_attr_stats, log_op,
             per_attr_stats_with_dist_and_ndistinct, per_attr_stats_with_ndistinct,
-            TestOptCostModelMock, TEST_ATTR1_NAME, TEST_ATTR2_NAME, TEST_TABLE1_ID, TEST_TABLE2_ID,
-            TEST_TABLE3_ID, TEST_TABLE4_ID,
+            TestOptCostModelMock, TestPerAttributeStats, TEST_ATTR1_NAME, TEST_ATTR2_NAME,
+            TEST_GROUP1_ID, TEST_GROUP2_ID, TEST_TABLE1_ID, TEST_TABLE2_ID, TEST_TABLE3_ID,
+            TEST_TABLE4_ID,
         },
+        CostModelError, EstimatedStatistic,
     };
 
     use super::*;
 
     const JOIN_GROUP_ID: GroupId = GroupId(10);
+    const LEFT_SEMI_JOIN_GROUP_ID: GroupId = GroupId(11);
+    const RIGHT_SEMI_JOIN_GROUP_ID: GroupId = GroupId(12);
 
     /// A wrapper around get_join_selectivity_from_expr_tree that extracts the
     /// table row counts from the cost model.
@@ -448,12 +574,13 @@ mod tests {
             cost_model
                 .get_join_selectivity_from_expr_tree(
                     join_typ,
-                    JOIN_GROUP_ID,
+                    JOIN_GROUP_ID.into(),
                     expr_tree,
                     attr_refs,
                     input_correlation,
                     table1_row_cnt,
                     table2_row_cnt,
+                    1,
                 )
                 .await
                 .unwrap()
@@ -461,12 +588,13 @@ mod tests {
             cost_model
                 .get_join_selectivity_from_expr_tree(
                     join_typ,
-                    JOIN_GROUP_ID,
+                    JOIN_GROUP_ID.into(),
                     expr_tree,
                     attr_refs,
                     input_correlation,
                     table2_row_cnt,
                     table1_row_cnt,
+                    1,
                 )
                 .await
                 .unwrap()
@@ -484,12 +612,13 @@ mod tests {
             cost_model
                 .get_join_selectivity_from_expr_tree(
                     JoinType::Inner,
-                    JOIN_GROUP_ID,
+                    JOIN_GROUP_ID.into(),
                     cnst(Value::Bool(true)),
                     &vec![],
                     None,
                     f64::NAN,
-                    f64::NAN
+                    f64::NAN,
+                    0,
                 )
                 .await
                 .unwrap(),
@@ -499,12 +628,13 @@ mod tests {
             cost_model
                 .get_join_selectivity_from_expr_tree(
                     JoinType::Inner,
-                    JOIN_GROUP_ID,
+                    JOIN_GROUP_ID.into(),
                     cnst(Value::Bool(false)),
                     &vec![],
                     None,
                     f64::NAN,
-                    f64::NAN
+                    f64::NAN,
+                    0,
                 )
                 .await
                 .unwrap(),
@@ -512,6 +642,87 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_malformed_join_keys_are_errors() {
+        let cost_model = create_two_table_mock_cost_model(
+            per_attr_stats_with_ndistinct(5),
+            per_attr_stats_with_ndistinct(4),
+            None,
+        );
+        let attr_refs = vec![
+            AttrRef::base_table_attr_ref(TEST_TABLE1_ID, 0),
+            AttrRef::base_table_attr_ref(TEST_TABLE2_ID, 0),
+        ];
+
+        for (left_keys, right_keys) in [
+            // A different number of keys on each side.
+            (
+                ListPred::new(vec![attr_index(0)]),
+                ListPred::new(vec![attr_index(1), attr_index(1)]),
+            ),
+            // A key that is not an attribute ref.
+            (
+                ListPred::new(vec![attr_index(0)]),
+                ListPred::new(vec![cnst(Value::Int32(1))]),
+            ),
+            // A key that is out of the range of the attributes.
+            (
+                ListPred::new(vec![attr_index(0)]),
+                ListPred::new(vec![attr_index(5)]),
+            ),
+        ] {
+            assert!(matches!(
+                cost_model
+                    .get_join_selectivity_from_keys(
+                        JoinType::Inner,
+                        JOIN_GROUP_ID,
+                        left_keys,
+                        right_keys,
+                        &attr_refs,
+                        None,
+                        5.0,
+                        4.0,
+                        1,
+                    )
+                    .await,
+                Err(CostModelError::SemanticError(
+                    SemanticError::InvalidPredicate(_)
+                ))
+            ));
+        }
+
+        // An on condition that is out of the range of the attributes, alone or in a conjunction.
+        let out_of_range_oncond = bin_op(BinOpType::Eq, attr_index(0), attr_index(7));
+        for expr_tree in [
+            out_of_range_oncond.clone(),
+            log_op(
+                LogOpType::And,
+                vec![
+                    bin_op(BinOpType::Eq, attr_index(0), attr_index(1)),
+                    out_of_range_oncond,
+                ],
+            ),
+        ] {
+            assert!(matches!(
+                cost_model
+                    .get_join_selectivity_from_expr_tree(
+                        JoinType::Inner,
+                        JOIN_GROUP_ID.into(),
+                        expr_tree,
+                        &attr_refs,
+                        None,
+                        5.0,
+                        4.0,
+                        1,
+                    )
+                    .await,
+                Err(CostModelError::SemanticError(
+                    SemanticError::InvalidPredicate(_)
+                ))
+            ));
+        }
+    }
+
     #[tokio::test]
     async fn test_inner_oncond() {
         let cost_model = create_two_table_mock_cost_model(
@@ -1272,4 +1483,223 @@ mod tests {
             .await;
         assert_approx_eq::assert_approx_eq!(overall_selectivity, 1.0 / (3.0 * 4.0 * 5.0));
     }
+
+    /// A cost model where table 1 has 100 rows with 50 distinct values and 20% nulls, and table 2
+    /// has 20 rows with 10 distinct values. The join group outputs both attributes, while the
+    /// left and right semi join groups only output the attribute of table 1 and table 2.
+    fn create_semi_join_mock_cost_model() -> TestOptCostModelMock {
+        let group = |attr_refs: Vec<(TableId, &str)>| {
+            MemoGroupInfo::new(
+                attr_refs
+                    .iter()
+                    .map(|(_, name)| Attribute::new_non_null_int64(name.to_string()))
+                    .collect::<Vec<_>>()
+                    .into(),
+                GroupAttrRefs::new(
+                    attr_refs
+                        .iter()
+                        .map(|(table_id, _)| AttrRef::new_base_table_attr_ref(*table_id, 0))
+                        .collect(),
+                    None,
+                ),
+            )
+        };
+        create_two_table_mock_cost_model_custom_row_cnts(
+            TestPerAttributeStats::new(MostCommonValues::empty(), None, 50, 0.2),
+            per_attr_stats_with_ndistinct(10),
+            100,
+            20,
+            Some(HashMap::from([
+                (
+                    JOIN_GROUP_ID,
+                    group(vec![
+                        (TEST_TABLE1_ID, TEST_ATTR1_NAME),
+                        (TEST_TABLE2_ID, TEST_ATTR2_NAME),
+                    ]),
+                ),
+                (
+                    LEFT_SEMI_JOIN_GROUP_ID,
+                    group(vec![(TEST_TABLE1_ID, TEST_ATTR1_NAME)]),
+                ),
+                (
+                    RIGHT_SEMI_JOIN_GROUP_ID,
+                    group(vec![(TEST_TABLE2_ID, TEST_ATTR2_NAME)]),
+                ),
+            ])),
+        )
+    }
+
+    /// The expected row counts of joining table 1 with table 2 on their attributes.
+    fn expected_semi_join_row_cnts() -> Vec<(JoinType, GroupId, f64)> {
+        vec![
+            // 1 / max(50, 10) of the cartesian product.
+            (JoinType::Inner, JOIN_GROUP_ID, 40.0),
+            // At least every row of table 1.
+            (JoinType::FullOuter, JOIN_GROUP_ID, 100.0),
+            // Only the non-null values of table 1 match, and only 10 of its 50 distinct values
+            // appear in table 2.
+            (
+                JoinType::LeftSemi,
+                LEFT_SEMI_JOIN_GROUP_ID,
+                100.0 * 0.8 * 0.2,
+            ),
+            (
+                JoinType::LeftAnti,
+                LEFT_SEMI_JOIN_GROUP_ID,
+                100.0 * (1.0 - 0.8 * 0.2),
+            ),
+            // Every value of table 2 is assumed to appear in table 1.
+            (JoinType::RightSemi, RIGHT_SEMI_JOIN_GROUP_ID, 20.0),
+            (JoinType::RightAnti, RIGHT_SEMI_JOIN_GROUP_ID, 1.0),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_semi_anti_full_hash_join() {
+        let cost_model = create_semi_join_mock_cost_model();
+
+        for (join_typ, group_id, expected_row_cnt) in expected_semi_join_row_cnts() {
+            let row_cnt = cost_model
+                .get_hash_join_row_cnt(
+                    join_typ,
+                    group_id,
+                    EstimatedStatistic(100.0),
+                    EstimatedStatistic(20.0),
+                    TEST_GROUP1_ID,
+                    TEST_GROUP2_ID,
+                    ListPred::new(vec![attr_index(0)]),
+                    ListPred::new(vec![attr_index(0)]),
+                )
+                .await
+                .unwrap();
+            assert_approx_eq::assert_approx_eq!(row_cnt.0, expected_row_cnt);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_semi_anti_full_nested_loop_join() {
+        let cost_model = create_semi_join_mock_cost_model();
+
+        for (join_typ, group_id, expected_row_cnt) in expected_semi_join_row_cnts() {
+            // The order of the attributes in the condition does not matter.
+            for join_cond in [
+                bin_op(BinOpType::Eq, attr_index(0), attr_index(1)),
+                bin_op(BinOpType::Eq, attr_index(1), attr_index(0)),
+            ] {
+                let row_cnt = cost_model
+                    .get_nlj_row_cnt(
+                        join_typ,
+                        group_id,
+                        EstimatedStatistic(100.0),
+                        EstimatedStatistic(20.0),
+                        TEST_GROUP1_ID,
+                        TEST_GROUP2_ID,
+                        join_cond,
+                    )
+                    .await
+                    .unwrap();
+                assert_approx_eq::assert_approx_eq!(row_cnt.0, expected_row_cnt);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_semi_join_with_filter() {
+        let cost_model = create_semi_join_mock_cost_model();
+
+        // The filter only keeps the matches of a third of the rows of table 1.
+        let join_cond = log_op(
+            LogOpType::And,
+            vec![
+                bin_op(BinOpType::Eq, attr_index(0), attr_index(1)),
+                bin_op(BinOpType::Lt, attr_index(0), attr_index(1)),
+            ],
+        );
+        for (join_typ, expected_row_cnt) in [
+            (JoinType::LeftSemi, 100.0 * 0.16 * DEFAULT_INEQ_SEL),
+            (JoinType::LeftAnti, 100.0 * (1.0 - 0.16 * DEFAULT_INEQ_SEL)),
+        ] {
+            let row_cnt = cost_model
+                .get_nlj_row_cnt(
+                    join_typ,
+                    LEFT_SEMI_JOIN_GROUP_ID,
+                    EstimatedStatistic(100.0),
+                    EstimatedStatistic(20.0),
+                    TEST_GROUP1_ID,
+                    TEST_GROUP2_ID,
+                    join_cond.clone(),
+                )
+                .await
+                .unwrap();
+            assert_approx_eq::assert_approx_eq!(row_cnt.0, expected_row_cnt);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_semi_join_filter_on_inner_attr() {
+        let cost_model = create_semi_join_mock_cost_model();
+
+        // The filter references the attribute of table 2, which is not in the output of the join.
+        let inner_filter = bin_op(BinOpType::Eq, attr_index(1), cnst(Value::Int64(5)));
+        let inner_filter_sel = cost_model
+            .get_filter_selectivity(
+                TEST_GROUP2_ID,
+                bin_op(BinOpType::Eq, attr_index(0), cnst(Value::Int64(5))),
+            )
+            .await
+            .unwrap();
+        let join_cond = log_op(
+            LogOpType::And,
+            vec![
+                bin_op(BinOpType::Eq, attr_index(0), attr_index(1)),
+                inner_filter,
+            ],
+        );
+        let row_cnt = cost_model
+            .get_nlj_row_cnt(
+                JoinType::LeftSemi,
+                LEFT_SEMI_JOIN_GROUP_ID,
+                EstimatedStatistic(100.0),
+                EstimatedStatistic(20.0),
+                TEST_GROUP1_ID,
+                TEST_GROUP2_ID,
+                join_cond.clone(),
+            )
+            .await
+            .unwrap();
+        assert_approx_eq::assert_approx_eq!(row_cnt.0, 100.0 * 0.16 * inner_filter_sel);
+
+        // An empty inner table does not divide by zero.
+        let row_cnt = cost_model
+            .get_nlj_row_cnt(
+                JoinType::LeftSemi,
+                LEFT_SEMI_JOIN_GROUP_ID,
+                EstimatedStatistic(100.0),
+                EstimatedStatistic(0.0),
+                TEST_GROUP1_ID,
+                TEST_GROUP2_ID,
+                join_cond,
+            )
+            .await
+            .unwrap();
+        assert_approx_eq::assert_approx_eq!(row_cnt.0, 1.0);
+
+        // A filter attribute past both children is an error.
+        assert!(matches!(
+            cost_model
+                .get_nlj_row_cnt(
+                    JoinType::LeftSemi,
+                    LEFT_SEMI_JOIN_GROUP_ID,
+                    EstimatedStatistic(100.0),
+                    EstimatedStatistic(20.0),
+                    TEST_GROUP1_ID,
+                    TEST_GROUP2_ID,
+                    bin_op(BinOpType::Eq, attr_index(2), cnst(Value::Int64(5))),
+                )
+                .await,
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidPredicate(_)
+            ))
+        ));
+    }
 }
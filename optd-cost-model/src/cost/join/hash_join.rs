@@ -5,7 +5,7 @@ use crate::{
     CostModelResult, EstimatedStatistic,
 };
 
-use super::get_input_correlation;
+use super::{get_input_correlation, get_join_attr_refs};
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
     #[allow(clippy::too_many_arguments)]
@@ -25,6 +25,12 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             let left_attr_refs = self.memo.get_attribute_refs(left_group_id);
             let right_attr_refs = self.memo.get_attribute_refs(right_group_id);
             let left_attr_cnt = left_attr_refs.attr_refs().len();
+            let attr_refs = get_join_attr_refs(
+                join_typ,
+                output_attr_refs,
+                &left_attr_refs,
+                &right_attr_refs,
+            );
             // there may be more than one expression tree in a group.
             // see comment in PredicateType::PhysicalFilter(_) for more information
             let input_correlation = get_input_correlation(left_attr_refs, right_attr_refs);
@@ -33,7 +39,7 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
                 group_id,
                 left_keys,
                 right_keys,
-                output_attr_refs.attr_refs(),
+                &attr_refs,
                 input_correlation,
                 left_row_cnt.0,
                 right_row_cnt.0,
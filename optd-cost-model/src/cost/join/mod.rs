@@ -1,14 +1,20 @@
-use crate::common::{
-    nodes::{ArcPredicateNode, PredicateType, ReprPredicateNode},
-    predicates::{attr_index_pred::AttrIndexPred, bin_op_pred::BinOpType},
-    properties::attr_ref::{
-        AttrRef, AttrRefs, BaseTableAttrRef, GroupAttrRefs, SemanticCorrelation,
+use crate::{
+    common::{
+        nodes::{ArcPredicateNode, JoinType, PredicateType, ReprPredicateNode},
+        predicates::{attr_index_pred::AttrIndexPred, bin_op_pred::BinOpType},
+        properties::attr_ref::{
+            AttrRef, AttrRefs, BaseTableAttrRef, GroupAttrRefs, SemanticCorrelation,
+        },
+        types::GroupId,
     },
+    cost::filter::core::FilterAttrs,
+    CostModelResult, SemanticError,
 };
 
 pub mod core;
 pub mod hash_join;
 pub mod nested_loop_join;
+pub mod sort_merge_join;
 
 pub(crate) fn get_input_correlation(
     left_prop: GroupAttrRefs,
@@ -20,6 +26,62 @@ pub(crate) fn get_input_correlation(
     )
 }
 
+/// The attributes the join conditions refer to: the attributes of the left child followed by
+/// those of the right child. This is the output of the join, except for semi and anti joins,
+/// which only output the attributes of one child.
+pub(crate) fn get_join_attr_refs(
+    join_typ: JoinType,
+    output_attr_refs: GroupAttrRefs,
+    left_attr_refs: &GroupAttrRefs,
+    right_attr_refs: &GroupAttrRefs,
+) -> AttrRefs {
+    if join_typ.is_semi_or_anti() {
+        left_attr_refs
+            .attr_refs()
+            .iter()
+            .chain(right_attr_refs.attr_refs())
+            .cloned()
+            .collect()
+    } else {
+        output_attr_refs.attr_refs().clone()
+    }
+}
+
+/// The attributes the join filter refers to, which are resolved against the children for semi
+/// and anti joins, in the same way as `get_join_attr_refs`.
+pub(crate) fn get_join_filter_attrs(
+    join_typ: JoinType,
+    group_id: GroupId,
+    left_group_id: GroupId,
+    right_group_id: GroupId,
+    left_attr_refs: &GroupAttrRefs,
+    right_attr_refs: &GroupAttrRefs,
+) -> FilterAttrs {
+    if join_typ.is_semi_or_anti() {
+        FilterAttrs::JoinChildren {
+            left: left_group_id,
+            right: right_group_id,
+            left_attr_cnt: left_attr_refs.attr_refs().len(),
+            right_attr_cnt: right_attr_refs.attr_refs().len(),
+        }
+    } else {
+        FilterAttrs::Group(group_id)
+    }
+}
+
+/// Get the attribute that a join condition refers to by its index, or an error if the index is out
+/// of range.
+pub(crate) fn get_attr_ref(attr_refs: &AttrRefs, attr_index: usize) -> CostModelResult<&AttrRef> {
+    attr_refs.get(attr_index).ok_or_else(|| {
+        SemanticError::InvalidPredicate(format!(
+            "attribute index {} is out of range for {} attributes",
+            attr_index,
+            attr_refs.len()
+        ))
+        .into()
+    })
+}
+
 /// Check if an expr_tree is a join condition, returning the join on attr ref pair if it is.
 /// The reason the check and the info are in the same function is because their code is almost
 /// identical. It only picks out equality conditions between two attribute refs on different
@@ -27,22 +89,21 @@ pub(crate) fn get_input_correlation(
 pub(crate) fn get_on_attr_ref_pair(
     expr_tree: ArcPredicateNode,
     attr_refs: &AttrRefs,
-) -> Option<(AttrIndexPred, AttrIndexPred)> {
+) -> CostModelResult<Option<(AttrIndexPred, AttrIndexPred)>> {
     // 1. Check that it's equality
-    if expr_tree.typ == PredicateType::BinOp(BinOpType::Eq) {
+    if expr_tree.typ == PredicateType::BinOp(BinOpType::Eq) && expr_tree.children.len() == 2 {
         let left_child = expr_tree.child(0);
         let right_child = expr_tree.child(1);
         // 2. Check that both sides are attribute refs
-        if left_child.typ == PredicateType::AttrIndex && right_child.typ == PredicateType::AttrIndex
-        {
+        if let (Some(left_attr_ref_expr), Some(right_attr_ref_expr)) = (
+            AttrIndexPred::from_pred_node(left_child),
+            AttrIndexPred::from_pred_node(right_child),
+        ) {
             // 3. Check that both sides don't belong to the same table (if we don't know, that
             //    means they don't belong)
-            let left_attr_ref_expr = AttrIndexPred::from_pred_node(left_child)
-                .expect("we already checked that the type is AttrRef");
-            let right_attr_ref_expr = AttrIndexPred::from_pred_node(right_child)
-                .expect("we already checked that the type is AttrRef");
-            let left_attr_ref = &attr_refs[left_attr_ref_expr.attr_index() as usize];
-            let right_attr_ref = &attr_refs[right_attr_ref_expr.attr_index() as usize];
+            let left_attr_ref = get_attr_ref(attr_refs, left_attr_ref_expr.attr_index() as usize)?;
+            let right_attr_ref =
+                get_attr_ref(attr_refs, right_attr_ref_expr.attr_index() as usize)?;
             let is_same_table = if let (
                 AttrRef::BaseTableAttrRef(BaseTableAttrRef {
                     table_id: left_table_id,
@@ -59,14 +120,14 @@ pub(crate) fn get_on_attr_ref_pair(
                 false
             };
             if !is_same_table {
-                Some((left_attr_ref_expr, right_attr_ref_expr))
+                Ok(Some((left_attr_ref_expr, right_attr_ref_expr)))
             } else {
-                None
+                Ok(None)
             }
         } else {
-            None
+            Ok(None)
         }
     } else {
-        None
+        Ok(None)
     }
 }
@@ -8,7 +8,7 @@ use crate::{
     CostModelResult, EstimatedStatistic,
 };
 
-use super::get_input_correlation;
+use super::{get_input_correlation, get_join_attr_refs, get_join_filter_attrs};
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
     #[allow(clippy::too_many_arguments)]
@@ -26,16 +26,32 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
             let output_attr_refs = self.memo.get_attribute_refs(group_id);
             let left_attr_refs = self.memo.get_attribute_refs(left_group_id);
             let right_attr_refs = self.memo.get_attribute_refs(right_group_id);
+            let left_attr_cnt = left_attr_refs.attr_refs().len();
+            let attr_refs = get_join_attr_refs(
+                join_typ,
+                output_attr_refs,
+                &left_attr_refs,
+                &right_attr_refs,
+            );
+            let filter_attrs = get_join_filter_attrs(
+                join_typ,
+                group_id,
+                left_group_id,
+                right_group_id,
+                &left_attr_refs,
+                &right_attr_refs,
+            );
             let input_correlation = get_input_correlation(left_attr_refs, right_attr_refs);
 
             self.get_join_selectivity_from_expr_tree(
                 join_typ,
-                group_id,
+                filter_attrs,
                 join_cond,
-                output_attr_refs.attr_refs(),
+                &attr_refs,
                 input_correlation,
                 left_row_cnt.0,
                 right_row_cnt.0,
+                left_attr_cnt,
             )
             .await?
         };
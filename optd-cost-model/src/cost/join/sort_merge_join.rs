@@ -0,0 +1,121 @@
+use crate::{
+    common::{
+        nodes::ReprPredicateNode,
+        predicates::{
+            attr_index_pred::AttrIndexPred, list_pred::ListPred, sort_order_pred::SortOrderType,
+        },
+    },
+    cost_model::CostModelImpl,
+    storage::CostModelStorageManager,
+    CostModelResult, EstimatedStatistic,
+};
+
+impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Computes the cost of a sort-merge join: sorting each child on its join keys, unless it is
+    /// already sorted on them, and then merging the two sorted inputs.
+    ///
+    /// The merge needs both children sorted in the same direction on each pair of keys. If both
+    /// are already sorted on their keys but in different directions, the cheaper one is sorted
+    /// again.
+    ///
+    /// Like in a hash join, the keys of each child are the indices of its own attributes.
+    pub(crate) fn get_sort_merge_join_compute_cost(
+        &self,
+        left_keys: ListPred,
+        right_keys: ListPred,
+        children_stats: &[EstimatedStatistic],
+        children_sort_orders: &[Vec<(u64, SortOrderType)>],
+    ) -> CostModelResult<f64> {
+        let mut compute_cost =
+            (children_stats[0].0 + children_stats[1].0) * self.cost_profile.merge_join_per_row;
+        let mut key_directions = vec![];
+        for (child_idx, keys) in [left_keys, right_keys].into_iter().enumerate() {
+            let sort_order = children_sort_orders
+                .get(child_idx)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            key_directions.push(get_key_directions(sort_order, keys)?);
+        }
+        let sort_costs = [
+            self.get_sort_compute_cost(children_stats[0].0),
+            self.get_sort_compute_cost(children_stats[1].0),
+        ];
+        compute_cost += match (&key_directions[0], &key_directions[1]) {
+            (Some(left), Some(right)) if left == right => 0.0,
+            (Some(_), Some(_)) => sort_costs[0].min(sort_costs[1]),
+            (Some(_), None) => sort_costs[1],
+            (None, Some(_)) => sort_costs[0],
+            (None, None) => sort_costs[0] + sort_costs[1],
+        };
+        Ok(compute_cost)
+    }
+}
+
+/// The directions in which rows sorted in `sort_order` are sorted on `keys`, or `None` if they are
+/// not sorted on the keys, i.e. if the keys are not a prefix of the order.
+fn get_key_directions(
+    sort_order: &[(u64, SortOrderType)],
+    keys: ListPred,
+) -> CostModelResult<Option<Vec<SortOrderType>>> {
+    let keys = keys
+        .to_vec()
+        .into_iter()
+        .map(|key| Ok(AttrIndexPred::try_from_pred_node(key)?.attr_index()))
+        .collect::<CostModelResult<Vec<_>>>()?;
+    if keys.len() > sort_order.len() {
+        return Ok(None);
+    }
+    Ok(keys
+        .iter()
+        .zip(sort_order)
+        .map(|(key, (attr_idx, direction))| (key == attr_idx).then_some(*direction))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::{
+            predicates::{list_pred::ListPred, sort_order_pred::SortOrderType},
+            values::Value,
+        },
+        test_utils::tests::{attr_index, cnst},
+    };
+
+    use super::get_key_directions;
+
+    use SortOrderType::{Asc, Desc};
+
+    fn keys(attr_indices: &[u64]) -> ListPred {
+        ListPred::new(attr_indices.iter().map(|&idx| attr_index(idx)).collect())
+    }
+
+    #[test]
+    fn test_get_key_directions() {
+        assert_eq!(
+            get_key_directions(&[(0, Asc)], keys(&[0])).unwrap(),
+            Some(vec![Asc])
+        );
+        assert_eq!(
+            get_key_directions(&[(1, Desc), (0, Asc)], keys(&[1])).unwrap(),
+            Some(vec![Desc])
+        );
+        assert_eq!(
+            get_key_directions(&[(1, Desc), (0, Asc)], keys(&[1, 0])).unwrap(),
+            Some(vec![Desc, Asc])
+        );
+        assert_eq!(
+            get_key_directions(&[(1, Asc), (0, Asc)], keys(&[0, 1])).unwrap(),
+            None
+        );
+        assert_eq!(
+            get_key_directions(&[(0, Asc)], keys(&[0, 1])).unwrap(),
+            None
+        );
+        assert_eq!(get_key_directions(&[], keys(&[0])).unwrap(), None);
+        // The keys must be attributes.
+        assert!(
+            get_key_directions(&[(0, Asc)], ListPred::new(vec![cnst(Value::Int32(1))])).is_err()
+        );
+    }
+}
@@ -12,17 +12,34 @@ impl<S: CostModelStorageManager> CostModelImpl<S> {
     pub(crate) fn get_limit_row_cnt(
         &self,
         child_row_cnt: EstimatedStatistic,
+        skip_expr: ArcPredicateNode,
         fetch_expr: ArcPredicateNode,
     ) -> CostModelResult<EstimatedStatistic> {
-        let fetch = ConstantPred::from_pred_node(fetch_expr)
-            .unwrap()
-            .value()
-            .as_u64();
-        // u64::MAX represents None
-        if fetch == u64::MAX {
-            Ok(child_row_cnt)
-        } else {
-            Ok(EstimatedStatistic(child_row_cnt.0.min(fetch as f64)))
+        let (skip, fetch) = get_limit_bounds(skip_expr, fetch_expr)?;
+        let row_cnt = (child_row_cnt.0 - skip as f64).max(0.0);
+        match fetch {
+            Some(fetch) => Ok(EstimatedStatistic(row_cnt.min(fetch as f64))),
+            None => Ok(EstimatedStatistic(row_cnt)),
         }
     }
 }
+
+/// Returns the number of rows a LIMIT skips and the number of rows it fetches after them, or
+/// `None` if it fetches all of them.
+pub(crate) fn get_limit_bounds(
+    skip_expr: ArcPredicateNode,
+    fetch_expr: ArcPredicateNode,
+) -> CostModelResult<(u64, Option<u64>)> {
+    let skip = ConstantPred::try_from_pred_node(skip_expr)?
+        .value()
+        .as_u64()?;
+    let fetch = ConstantPred::try_from_pred_node(fetch_expr)?
+        .value()
+        .as_u64()?;
+    // u64::MAX represents None
+    if fetch == u64::MAX {
+        Ok((skip, None))
+    } else {
+        Ok((skip, Some(fetch)))
+    }
+}
@@ -2,3 +2,7 @@ pub mod agg;
 pub mod filter;
 pub mod join;
 pub mod limit;
+pub mod scan;
+pub mod set_op;
+pub mod sort;
+pub mod window;
@@ -0,0 +1,536 @@
+use std::collections::HashSet;
+
+use optd_persistent::cost_model::interface::IndexType;
+
+use crate::{
+    common::{
+        nodes::{ArcPredicateNode, PredicateType, ReprPredicateNode},
+        predicates::{constant_pred::ConstantPred, log_op_pred::LogOpType},
+        properties::attr_ref::{AttrRef, BaseTableAttrRef},
+        types::{GroupId, IndexId, TableId},
+    },
+    cost::filter::log_op::get_attr_eq_constant,
+    cost_model::CostModelImpl,
+    storage::{CostModelStorageManager, IndexInfo},
+    Cost, CostModelResult, EstimatedStatistic, SemanticError,
+};
+
+impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Estimates the IO cost of scanning `row_cnt` rows of a group, as the number of pages the
+    /// rows take times the cost of reading a page sequentially.
+    ///
+    /// Only the attributes that the scan outputs are counted, so that a scan whose columns are
+    /// pruned reads fewer pages.
+    pub(crate) async fn get_scan_io_cost(
+        &self,
+        group_id: GroupId,
+        row_cnt: f64,
+    ) -> CostModelResult<f64> {
+        let tuple_width = self.get_tuple_width(group_id).await?;
+        let page_cnt = (row_cnt * tuple_width / self.cost_profile.page_size).ceil();
+        Ok(page_cnt * self.cost_profile.seq_page_io_cost)
+    }
+
+    /// Estimates the average width in bytes of the rows of a group, including the tuple header.
+    ///
+    /// The width of a base table attribute comes from its average width statistic. Attributes
+    /// without one, such as derived attributes, are assumed to have the width of their type.
+    pub(crate) async fn get_tuple_width(&self, group_id: GroupId) -> CostModelResult<f64> {
+        let mut tuple_width = self.cost_profile.tuple_header_width;
+        let attr_refs = self.memo.get_attribute_refs(group_id);
+        for (attr_ref_idx, attr_ref) in attr_refs.attr_refs().iter().enumerate() {
+            let avg_width = match attr_ref {
+                AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx }) => {
+                    self.storage_manager
+                        .get_attribute_avg_width(*table_id, *attr_idx)
+                        .await?
+                }
+                AttrRef::Derived => None,
+            };
+            tuple_width += match avg_width {
+                Some(avg_width) => avg_width,
+                None => self
+                    .memo
+                    .get_attribute_info(group_id, attr_ref_idx as u64)
+                    .typ
+                    .default_width(),
+            };
+        }
+        Ok(tuple_width)
+    }
+
+    /// Estimates the number of rows of a table that match the condition of an index scan.
+    ///
+    /// A lookup of every attribute of a unique index matches at most one row.
+    pub(crate) async fn get_index_scan_row_cnt(
+        &self,
+        group_id: GroupId,
+        index: &IndexInfo,
+        cond: ArcPredicateNode,
+    ) -> CostModelResult<EstimatedStatistic> {
+        let table_row_cnt = self.get_table_row_cnt(index.table_id).await?;
+        let row_cnt = self
+            .get_filter_row_cnt(EstimatedStatistic(table_row_cnt), group_id, cond.clone())
+            .await?;
+        if self.is_point_lookup(group_id, index, &cond) {
+            return Ok(EstimatedStatistic(row_cnt.0.min(1.0)));
+        }
+        Ok(row_cnt)
+    }
+
+    /// Estimates the cost of an index scan that matches `row_cnt` rows.
+    ///
+    /// Finding the first match takes one random page read per level of the index. The matching
+    /// rows of a clustered index are then read sequentially, while each matching row of a
+    /// non-clustered index may be on a different page and takes a random page read. The rows are
+    /// assumed to be as wide as the attributes of the group.
+    pub(crate) async fn get_index_scan_cost(
+        &self,
+        group_id: GroupId,
+        index: &IndexInfo,
+        cond: ArcPredicateNode,
+        row_cnt: f64,
+    ) -> CostModelResult<Cost> {
+        let profile = &self.cost_profile;
+        let table_row_cnt = self.get_table_row_cnt(index.table_id).await?.max(1.0);
+        let tuple_width = self.get_tuple_width(group_id).await?;
+        let table_page_cnt = (table_row_cnt * tuple_width / profile.page_size)
+            .ceil()
+            .max(1.0);
+
+        let index_level_cnt = match index.index_type {
+            IndexType::BTree => table_row_cnt.log(profile.btree_fanout).ceil().max(1.0),
+            IndexType::Hash => 1.0,
+        };
+        let fetch_io_cost = if self.is_point_lookup(group_id, index, &cond) {
+            profile.random_page_io_cost
+        } else if index.is_clustered {
+            let page_cnt = (row_cnt / table_row_cnt * table_page_cnt).ceil().max(1.0);
+            profile.random_page_io_cost + (page_cnt - 1.0) * profile.seq_page_io_cost
+        } else {
+            row_cnt.min(table_page_cnt) * profile.random_page_io_cost
+        };
+        Ok(Cost {
+            compute_cost: row_cnt * profile.cpu_tuple_cost,
+            io_cost: index_level_cnt * profile.random_page_io_cost + fetch_io_cost,
+        })
+    }
+
+    /// Gets an index of a table, or an error if it does not exist or belongs to another table.
+    pub(crate) async fn get_table_index(
+        &self,
+        table_id: TableId,
+        index_id: IndexId,
+    ) -> CostModelResult<IndexInfo> {
+        match self.storage_manager.get_index(index_id).await? {
+            Some(index) if index.table_id == table_id => Ok(index),
+            Some(_) => Err(SemanticError::InvalidPredicate(format!(
+                "{index_id} is not an index of {table_id}"
+            ))
+            .into()),
+            None => Err(SemanticError::IndexNotFound(index_id).into()),
+        }
+    }
+
+    async fn get_table_row_cnt(&self, table_id: TableId) -> CostModelResult<f64> {
+        Ok(self
+            .storage_manager
+            .get_table_row_count(table_id)
+            .await?
+            .unwrap_or(1) as f64)
+    }
+
+    /// Returns whether `cond` compares every attribute of a unique index to a constant. An index
+    /// without attributes never makes a point lookup.
+    fn is_point_lookup(
+        &self,
+        group_id: GroupId,
+        index: &IndexInfo,
+        cond: &ArcPredicateNode,
+    ) -> bool {
+        if !index.is_unique || index.attr_base_indices.is_empty() {
+            return false;
+        }
+        let conjuncts = if cond.typ == PredicateType::LogOp(LogOpType::And) {
+            cond.children.clone()
+        } else {
+            vec![cond.clone()]
+        };
+        let eq_attrs: HashSet<u64> = conjuncts
+            .iter()
+            .filter_map(get_attr_eq_constant)
+            .filter_map(|(attr_ref_idx, _)| {
+                match self.memo.get_attribute_ref(group_id, attr_ref_idx) {
+                    AttrRef::BaseTableAttrRef(BaseTableAttrRef { table_id, attr_idx })
+                        if table_id == index.table_id =>
+                    {
+                        Some(attr_idx)
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        index
+            .attr_base_indices
+            .iter()
+            .all(|attr_idx| eq_attrs.contains(attr_idx))
+    }
+}
+
+/// Returns the table and the index of an index scan, whose predicates are the table id, the index
+/// id and the index condition.
+pub(crate) fn get_index_scan_ids(
+    predicates: &[ArcPredicateNode],
+) -> CostModelResult<(TableId, IndexId)> {
+    if predicates.len() != 3 {
+        return Err(SemanticError::InvalidPredicate(format!(
+            "an index scan has 3 predicates, but got {}",
+            predicates.len()
+        ))
+        .into());
+    }
+    let table_id = ConstantPred::try_from_pred_node(predicates[0].clone())?
+        .value()
+        .as_u64()?;
+    let index_id = ConstantPred::try_from_pred_node(predicates[1].clone())?
+        .value()
+        .as_u64()?;
+    Ok((TableId(table_id), IndexId(index_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use optd_persistent::cost_model::interface::IndexType;
+
+    use crate::{
+        common::{
+            nodes::{ArcPredicateNode, PhysicalNodeType},
+            predicates::{
+                bin_op_pred::BinOpType, constant_pred::ConstantType, log_op_pred::LogOpType,
+            },
+            types::{ExprId, IndexId},
+            values::Value,
+        },
+        stats::{MostCommonValues, DEFAULT_VARLEN_WIDTH},
+        storage::{mock::TableStats, IndexInfo},
+        test_utils::tests::{
+            attr_index, bin_op, cnst, create_mock_cost_model_with_table_stats, log_op,
+            TestOptCostModelMock, TestPerAttributeStats, TEST_GROUP1_ID, TEST_TABLE1_ID,
+            TEST_TABLE2_ID,
+        },
+        ComputeCostContext, Cost, CostModel, CostModelError, EstimatedStatistic, SemanticError,
+    };
+
+    /// Table 1 has an int64 attribute, a string attribute that is 200 bytes wide on average, and
+    /// a string attribute without statistics. The group scans the attributes `attr_idxs`.
+    fn create_cost_model(attr_idxs: Vec<u64>) -> TestOptCostModelMock {
+        let mut table_stats = TableStats::new(1000, HashMap::new());
+        table_stats.attr_avg_widths.insert(1, 200.0);
+        let types = [
+            ConstantType::Int64,
+            ConstantType::Utf8String,
+            ConstantType::Utf8String,
+        ];
+        create_mock_cost_model_with_table_stats(
+            HashMap::from([(TEST_TABLE1_ID, table_stats)]),
+            attr_idxs
+                .into_iter()
+                .map(|attr_idx| (TEST_TABLE1_ID, attr_idx, Some(types[attr_idx as usize])))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tuple_width() {
+        let cost_model = create_cost_model(vec![0, 1, 2]);
+        assert_eq!(
+            cost_model.get_tuple_width(TEST_GROUP1_ID).await.unwrap(),
+            24.0 + 8.0 + 200.0 + DEFAULT_VARLEN_WIDTH
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_io_cost() {
+        // (24 + 8 + 200 + 32) * 1000 bytes take 33 pages of 8KB.
+        let cost_model = create_cost_model(vec![0, 1, 2]);
+        assert_eq!(
+            cost_model
+                .get_scan_io_cost(TEST_GROUP1_ID, 1000.0)
+                .await
+                .unwrap(),
+            33.0
+        );
+
+        // Scanning fewer attributes reads fewer pages.
+        let cost_model = create_cost_model(vec![0]);
+        assert_eq!(
+            cost_model
+                .get_scan_io_cost(TEST_GROUP1_ID, 1000.0)
+                .await
+                .unwrap(),
+            4.0
+        );
+
+        // The page size and the cost per page come from the cost profile.
+        let mut cost_model = create_cost_model(vec![0]);
+        cost_model.cost_profile.page_size = 4096.0;
+        cost_model.cost_profile.seq_page_io_cost = 0.5;
+        assert_eq!(
+            cost_model
+                .get_scan_io_cost(TEST_GROUP1_ID, 1000.0)
+                .await
+                .unwrap(),
+            8.0 * 0.5
+        );
+    }
+
+    const UNIQUE_INDEX_ID: IndexId = IndexId(1);
+    const INDEX_ID: IndexId = IndexId(2);
+    const CLUSTERED_INDEX_ID: IndexId = IndexId(3);
+    const HASH_INDEX_ID: IndexId = IndexId(4);
+    const OTHER_TABLE_INDEX_ID: IndexId = IndexId(5);
+    const EMPTY_INDEX_ID: IndexId = IndexId(6);
+
+    /// Table 1 has 100000 rows, a unique int64 attribute and an int64 attribute with 100
+    /// distinct values. Its rows are 24 + 8 + 8 bytes wide, so they take 489 pages.
+    fn create_cost_model_with_indexes() -> TestOptCostModelMock {
+        let index = |index_id, table_id, index_type, is_unique, is_clustered, attr_idx| IndexInfo {
+            index_id,
+            table_id,
+            index_type,
+            is_unique,
+            is_clustered,
+            attr_base_indices: vec![attr_idx],
+        };
+        let stats =
+            |ndistinct| TestPerAttributeStats::new(MostCommonValues::empty(), None, ndistinct, 0.0);
+        let mut table_stats = TableStats::new(
+            100000,
+            HashMap::from([(vec![0], stats(100000)), (vec![1], stats(100))]),
+        );
+        table_stats.indexes = vec![
+            index(
+                UNIQUE_INDEX_ID,
+                TEST_TABLE1_ID,
+                IndexType::BTree,
+                true,
+                false,
+                0,
+            ),
+            index(INDEX_ID, TEST_TABLE1_ID, IndexType::BTree, false, false, 1),
+            index(
+                CLUSTERED_INDEX_ID,
+                TEST_TABLE1_ID,
+                IndexType::BTree,
+                false,
+                true,
+                1,
+            ),
+            index(
+                HASH_INDEX_ID,
+                TEST_TABLE1_ID,
+                IndexType::Hash,
+                true,
+                false,
+                0,
+            ),
+            IndexInfo {
+                attr_base_indices: vec![],
+                ..index(
+                    EMPTY_INDEX_ID,
+                    TEST_TABLE1_ID,
+                    IndexType::BTree,
+                    true,
+                    false,
+                    0,
+                )
+            },
+        ];
+        let mut other_table_stats = TableStats::new(10, HashMap::new());
+        other_table_stats.indexes = vec![index(
+            OTHER_TABLE_INDEX_ID,
+            TEST_TABLE2_ID,
+            IndexType::BTree,
+            false,
+            false,
+            0,
+        )];
+        create_mock_cost_model_with_table_stats(
+            HashMap::from([
+                (TEST_TABLE1_ID, table_stats),
+                (TEST_TABLE2_ID, other_table_stats),
+            ]),
+            vec![(TEST_TABLE1_ID, 0, None), (TEST_TABLE1_ID, 1, None)],
+        )
+    }
+
+    fn eq(attr_idx: u64, value: i64) -> ArcPredicateNode {
+        bin_op(
+            BinOpType::Eq,
+            attr_index(attr_idx),
+            cnst(Value::Int64(value)),
+        )
+    }
+
+    async fn index_scan(
+        cost_model: &TestOptCostModelMock,
+        index_id: IndexId,
+        cond: ArcPredicateNode,
+    ) -> (EstimatedStatistic, Cost) {
+        let predicates = [
+            cnst(Value::UInt64(TEST_TABLE1_ID.0)),
+            cnst(Value::UInt64(index_id.0)),
+            cond,
+        ];
+        let context = ComputeCostContext {
+            group_id: TEST_GROUP1_ID,
+            expr_id: ExprId(index_id.0),
+            ..Default::default()
+        };
+        let row_cnt = cost_model
+            .derive_statistics(
+                PhysicalNodeType::PhysicalIndexScan,
+                &predicates,
+                &[],
+                context.clone(),
+                false,
+            )
+            .await
+            .unwrap();
+        let cost = cost_model
+            .compute_operation_cost(
+                PhysicalNodeType::PhysicalIndexScan,
+                &predicates,
+                &[],
+                &[],
+                context,
+            )
+            .await
+            .unwrap();
+        (row_cnt, cost)
+    }
+
+    #[tokio::test]
+    async fn test_index_scan_point_lookup() {
+        let cost_model = create_cost_model_with_indexes();
+
+        // A B-tree over 100000 rows has 3 levels, and the matching row takes one more read.
+        let (row_cnt, cost) = index_scan(&cost_model, UNIQUE_INDEX_ID, eq(0, 5)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(1.0));
+        assert_eq!(
+            cost,
+            Cost {
+                compute_cost: 1.0,
+                io_cost: 3.0 * 4.0 + 4.0,
+            }
+        );
+
+        // A hash index is read once, whatever the other conjuncts are.
+        let cond = log_op(LogOpType::And, vec![eq(1, 1), eq(0, 5)]);
+        let (row_cnt, cost) = index_scan(&cost_model, HASH_INDEX_ID, cond).await;
+        assert_eq!(row_cnt, EstimatedStatistic(1.0));
+        assert_eq!(cost.io_cost, 4.0 + 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_index_scan_empty_unique_index() {
+        let cost_model = create_cost_model_with_indexes();
+
+        // A unique index without attributes does not make every scan a point lookup.
+        let (row_cnt, cost) = index_scan(&cost_model, EMPTY_INDEX_ID, eq(1, 5)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+        assert_eq!(cost.compute_cost, 1000.0);
+    }
+
+    #[tokio::test]
+    async fn test_index_scan_clustered() {
+        let cost_model = create_cost_model_with_indexes();
+
+        // Each of the 1000 matching rows may be on a different page, but there are only 489.
+        let (row_cnt, cost) = index_scan(&cost_model, INDEX_ID, eq(1, 5)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+        assert_eq!(
+            cost,
+            Cost {
+                compute_cost: 1000.0,
+                io_cost: 3.0 * 4.0 + 489.0 * 4.0,
+            }
+        );
+
+        // The matching rows of a clustered index are on 5 consecutive pages.
+        let (row_cnt, cost) = index_scan(&cost_model, CLUSTERED_INDEX_ID, eq(1, 5)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+        assert_eq!(cost.io_cost, 3.0 * 4.0 + 4.0 + 4.0 * 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_index_scan_unknown_index() {
+        let cost_model = create_cost_model_with_indexes();
+        let res = cost_model
+            .get_table_index(TEST_TABLE1_ID, IndexId(42))
+            .await;
+        assert!(matches!(
+            res,
+            Err(CostModelError::SemanticError(SemanticError::IndexNotFound(
+                IndexId(42)
+            )))
+        ));
+
+        // The index exists, but on another table.
+        let res = cost_model
+            .get_table_index(TEST_TABLE1_ID, OTHER_TABLE_INDEX_ID)
+            .await;
+        assert!(matches!(
+            res,
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidPredicate(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_index_scan_missing_predicates_is_error() {
+        let cost_model = create_cost_model_with_indexes();
+        // The index condition is missing.
+        let predicates = [
+            cnst(Value::UInt64(TEST_TABLE1_ID.0)),
+            cnst(Value::UInt64(UNIQUE_INDEX_ID.0)),
+        ];
+        let context = ComputeCostContext {
+            group_id: TEST_GROUP1_ID,
+            ..Default::default()
+        };
+        let res = cost_model
+            .derive_statistics(
+                PhysicalNodeType::PhysicalIndexScan,
+                &predicates,
+                &[],
+                context.clone(),
+                false,
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidPredicate(_)
+            ))
+        ));
+        let res = cost_model
+            .compute_operation_cost(
+                PhysicalNodeType::PhysicalIndexScan,
+                &predicates,
+                &[],
+                &[],
+                context,
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidPredicate(_)
+            ))
+        ));
+    }
+}
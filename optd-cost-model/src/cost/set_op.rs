@@ -0,0 +1,96 @@
+use crate::{
+    common::{nodes::SetOpType, types::GroupId},
+    cost_model::CostModelImpl,
+    storage::CostModelStorageManager,
+    CostModelResult, EstimatedStatistic,
+};
+
+impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Estimates the number of rows of a set operation from the rows of its children, and from
+    /// their distinct rows if it removes duplicates.
+    ///
+    /// As in Postgres, the distinct rows of the children are assumed to be disjoint for a
+    /// UNION, and to be contained in one another for an INTERSECT or an EXCEPT, which then
+    /// outputs the smaller side or the left side.
+    pub async fn get_set_op_row_cnt(
+        &self,
+        set_op: SetOpType,
+        children_group_ids: &[GroupId],
+        children_row_cnts: &[EstimatedStatistic],
+    ) -> CostModelResult<EstimatedStatistic> {
+        let (left_row_cnt, right_row_cnt) = (children_row_cnts[0].0, children_row_cnts[1].0);
+        let row_cnt = match set_op {
+            SetOpType::UnionAll => left_row_cnt + right_row_cnt,
+            SetOpType::IntersectAll => left_row_cnt.min(right_row_cnt),
+            SetOpType::ExceptAll => left_row_cnt,
+            SetOpType::Union | SetOpType::Intersect | SetOpType::Except => {
+                let (left_distinct_cnt, right_distinct_cnt) = self
+                    .get_children_distinct_row_cnts(children_group_ids, children_row_cnts)
+                    .await?;
+                match set_op {
+                    SetOpType::Union => left_distinct_cnt + right_distinct_cnt,
+                    SetOpType::Intersect => left_distinct_cnt.min(right_distinct_cnt),
+                    _ => left_distinct_cnt,
+                }
+            }
+        };
+        Ok(EstimatedStatistic(row_cnt))
+    }
+
+    /// Estimates the compute cost of a set operation.
+    ///
+    /// A UNION ALL only appends its children. The other set operations match equal rows, either
+    /// by hashing every row into a hash table with an entry per distinct row, or by sorting both
+    /// children and comparing consecutive rows. For an INTERSECT or an EXCEPT, only the rows of
+    /// the left child create entries.
+    pub(crate) async fn get_set_op_compute_cost(
+        &self,
+        set_op: SetOpType,
+        is_sort_set_op: bool,
+        children_group_ids: &[GroupId],
+        children_row_cnts: &[EstimatedStatistic],
+    ) -> CostModelResult<f64> {
+        let profile = &self.cost_profile;
+        let (left_row_cnt, right_row_cnt) = (children_row_cnts[0].0, children_row_cnts[1].0);
+        let input_row_cnt = left_row_cnt + right_row_cnt;
+        if set_op == SetOpType::UnionAll {
+            return Ok(input_row_cnt * profile.cpu_tuple_cost);
+        }
+        if is_sort_set_op {
+            return Ok(self.get_sort_compute_cost(left_row_cnt)
+                + self.get_sort_compute_cost(right_row_cnt)
+                + input_row_cnt * profile.sort_agg_per_row);
+        }
+        let (left_distinct_cnt, right_distinct_cnt) = self
+            .get_children_distinct_row_cnts(children_group_ids, children_row_cnts)
+            .await?;
+        let entry_cnt = match set_op {
+            SetOpType::Union => left_distinct_cnt + right_distinct_cnt,
+            _ => left_distinct_cnt,
+        };
+        Ok(input_row_cnt * profile.hash_agg_per_row + entry_cnt * profile.hash_agg_per_group)
+    }
+
+    /// Estimates the number of distinct rows of each child, i.e. the n-distinct of all their
+    /// attributes. This is a bounded number of statistics lookups, see `get_attrs_group_cnt`.
+    async fn get_children_distinct_row_cnts(
+        &self,
+        children_group_ids: &[GroupId],
+        children_row_cnts: &[EstimatedStatistic],
+    ) -> CostModelResult<(f64, f64)> {
+        let mut distinct_row_cnts = [0.0; 2];
+        for (child_idx, distinct_row_cnt) in distinct_row_cnts.iter_mut().enumerate() {
+            let group_id = children_group_ids[child_idx];
+            let attr_cnt = self.memo.get_attribute_refs(group_id).attr_refs().len() as u64;
+            *distinct_row_cnt = self
+                .get_attrs_group_cnt(
+                    group_id,
+                    (0..attr_cnt).collect(),
+                    children_row_cnts[child_idx].clone(),
+                )
+                .await?
+                .0;
+        }
+        Ok((distinct_row_cnts[0], distinct_row_cnts[1]))
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{
+    common::nodes::ArcPredicateNode, cost_model::CostModelImpl, storage::CostModelStorageManager,
+    CostModelResult,
+};
+
+use super::limit::get_limit_bounds;
+
+impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// The cost of sorting `row_cnt` rows, which is at least linear in the input size.
+    pub(crate) fn get_sort_compute_cost(&self, row_cnt: f64) -> f64 {
+        row_cnt * row_cnt.ln_1p().max(1.0) * self.cost_profile.sort_comparison_cost
+    }
+
+    /// The cost of a top-N, which pushes every input row through a heap of the `skip + fetch`
+    /// first rows instead of sorting the whole input. Without a fetch, this is a full sort.
+    pub(crate) fn get_top_n_compute_cost(
+        &self,
+        child_row_cnt: f64,
+        skip_expr: ArcPredicateNode,
+        fetch_expr: ArcPredicateNode,
+    ) -> CostModelResult<f64> {
+        let heap_size = match get_limit_bounds(skip_expr, fetch_expr)? {
+            (skip, Some(fetch)) => (skip.saturating_add(fetch) as f64).min(child_row_cnt),
+            (_, None) => child_row_cnt,
+        };
+        Ok(child_row_cnt * heap_size.ln_1p().max(1.0) * self.cost_profile.sort_comparison_cost)
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeSet;
+
+use datafusion_expr::WindowFunction;
+
+use crate::{
+    common::{
+        nodes::{ArcPredicateNode, PredicateType, ReprPredicateNode},
+        predicates::{func_pred::FuncType, list_pred::ListPred},
+        types::GroupId,
+    },
+    cost::agg::collect_group_by_attrs,
+    cost_model::CostModelImpl,
+    storage::CostModelStorageManager,
+    CostModelResult, EstimatedStatistic, SemanticError,
+};
+
+impl<S: CostModelStorageManager> CostModelImpl<S> {
+    /// Estimates the compute cost of a window operator.
+    ///
+    /// The rows are hashed into their partitions, whose number is the n-distinct of the
+    /// PARTITION BY keys, and each partition is sorted on the ORDER BY keys. Then every row
+    /// computes each window function over its frame. Aggregate functions maintain their frame
+    /// incrementally, so a row adds itself to the frame and produces the result of the frame.
+    /// `expr_cost` is the cost of evaluating the window function arguments and the keys on one
+    /// row.
+    pub(crate) async fn get_window_compute_cost(
+        &self,
+        child_group_id: GroupId,
+        window_funcs: ArcPredicateNode,
+        partition_by: ArcPredicateNode,
+        order_by: ArcPredicateNode,
+        child_row_cnt: f64,
+        expr_cost: f64,
+    ) -> CostModelResult<f64> {
+        let profile = &self.cost_profile;
+        let mut compute_cost = 0.0;
+
+        let partition_by = ListPred::try_from_pred_node(partition_by)?;
+        if !partition_by.is_empty() {
+            compute_cost += child_row_cnt * profile.window_partition_per_row;
+        }
+        if !ListPred::try_from_pred_node(order_by)?.is_empty() {
+            let partition_cnt = self
+                .get_window_partition_cnt(
+                    child_group_id,
+                    &partition_by,
+                    EstimatedStatistic(child_row_cnt),
+                )
+                .await?
+                .0;
+            compute_cost +=
+                partition_cnt * self.get_sort_compute_cost(child_row_cnt / partition_cnt);
+        }
+
+        let mut per_row = expr_cost;
+        for window_func in &ListPred::try_from_pred_node(window_funcs)?.0.children {
+            per_row += match &window_func.typ {
+                PredicateType::Func(FuncType::Window(WindowFunction::AggregateFunction(func))) => {
+                    let func_cost = profile.agg_func_cost(func);
+                    func_cost.per_row + func_cost.per_group
+                }
+                PredicateType::Func(FuncType::Window(_)) => profile.window_func_per_row,
+                _ => {
+                    return Err(SemanticError::InvalidPredicate(format!(
+                        "{window_func} is not a window function"
+                    ))
+                    .into())
+                }
+            };
+        }
+        Ok(compute_cost + child_row_cnt * per_row)
+    }
+
+    /// Estimates the number of partitions of a window operator like the groups of a GROUP BY,
+    /// except that PARTITION BY has no ordinal positions, so constants put every row in the same
+    /// partition.
+    async fn get_window_partition_cnt(
+        &self,
+        child_group_id: GroupId,
+        partition_by: &ListPred,
+        child_row_cnt: EstimatedStatistic,
+    ) -> CostModelResult<EstimatedStatistic> {
+        let attr_cnt = self
+            .memo
+            .get_attribute_refs(child_group_id)
+            .attr_refs()
+            .len() as u64;
+        let mut attr_ref_idxs = BTreeSet::new();
+        for node in &partition_by.0.children {
+            collect_group_by_attrs(node, attr_cnt, false, &mut attr_ref_idxs)?;
+        }
+        self.get_attrs_group_cnt(child_group_id, attr_ref_idxs, child_row_cnt)
+            .await
+    }
+}
@@ -10,9 +10,11 @@ use optd_persistent::{
 use crate::{
     common::{
         nodes::{ArcPredicateNode, PhysicalNodeType, ReprPredicateNode},
-        predicates::list_pred::ListPred,
+        predicates::{constant_pred::ConstantPred, list_pred::ListPred},
         types::{AttrId, EpochId, ExprId, TableId},
     },
+    cost::scan::get_index_scan_ids,
+    cost_profile::CostProfile,
     memo_ext::MemoExt,
     stats::AttributeCombValueStats,
     storage::{self, CostModelStorageManager},
@@ -24,19 +26,23 @@ pub struct CostModelImpl<S: CostModelStorageManager> {
     pub storage_manager: S,
     pub default_catalog_source: CatalogSource,
     pub memo: Arc<dyn MemoExt>,
+    pub cost_profile: CostProfile,
 }
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
-    /// TODO: documentation
+    /// Creates a cost model whose cost formulas are calibrated with `cost_profile`, e.g.
+    /// `CostProfile::default()` or a profile loaded with `CostProfile::from_file`.
     pub fn new(
         storage_manager: S,
         default_catalog_source: CatalogSource,
         memo: Arc<dyn MemoExt>,
+        cost_profile: CostProfile,
     ) -> Self {
         Self {
             storage_manager,
             default_catalog_source,
             memo,
+            cost_profile,
         }
     }
 }
@@ -52,14 +58,18 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
         children_stats: &[EstimatedStatistic],
         context: ComputeCostContext,
     ) -> CostModelResult<Cost> {
+        // The cost of a sort-merge join depends on the order of its children, which the cached
+        // cost of the expression does not know about, so it is always recomputed.
+        let is_cost_cached = !matches!(node, PhysicalNodeType::PhysicalSortMergeJoin(_));
         let res = self.storage_manager.get_cost(context.expr_id).await;
-        if let Ok((Some(cost), _)) = res {
-            return Ok(cost);
+        if let (true, Ok((Some(cost), _))) = (is_cost_cached, &res) {
+            return Ok(cost.clone());
         };
         let mut output_statistic = None;
         if let Ok((_, Some(statistic))) = res {
             output_statistic = Some(statistic);
         };
+        let profile = &self.cost_profile;
         let output_cost = match node {
             PhysicalNodeType::PhysicalScan => {
                 let output_statistic_data = output_statistic.unwrap_or(
@@ -75,11 +85,28 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
                 output_statistic = Some(output_statistic_data.clone());
                 Cost {
                     compute_cost: 0.0,
-                    io_cost: output_statistic_data.0,
+                    io_cost: self
+                        .get_scan_io_cost(context.group_id, output_statistic_data.0)
+                        .await?,
                 }
             }
+            PhysicalNodeType::PhysicalIndexScan => {
+                // Get the index once for both the row count and the cost.
+                let (table_id, index_id) = get_index_scan_ids(predicates)?;
+                let index = self.get_table_index(table_id, index_id).await?;
+                let row_cnt = match output_statistic {
+                    Some(statistic) => statistic,
+                    None => {
+                        self.get_index_scan_row_cnt(context.group_id, &index, predicates[2].clone())
+                            .await?
+                    }
+                };
+                output_statistic = Some(row_cnt.clone());
+                self.get_index_scan_cost(context.group_id, &index, predicates[2].clone(), row_cnt.0)
+                    .await?
+            }
             PhysicalNodeType::PhysicalEmptyRelation => Cost {
-                compute_cost: 0.1,
+                compute_cost: profile.empty_relation_cost,
                 io_cost: 0.0,
             },
             PhysicalNodeType::PhysicalLimit => Cost {
@@ -87,49 +114,125 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
                 io_cost: 0.0,
             },
             PhysicalNodeType::PhysicalFilter => Cost {
-                // TODO: now this equation is specific to optd, and try to make this equation more general
-                compute_cost: children_costs[1].compute_cost * children_stats[0].0,
+                compute_cost: children_costs[1].compute_cost
+                    * profile.cpu_predicate_cost
+                    * children_stats[0].0,
                 io_cost: 0.0,
             },
             PhysicalNodeType::PhysicalNestedLoopJoin(join_typ) => {
                 let child_compute_cost = children_costs[2].compute_cost;
                 Cost {
-                    compute_cost: children_stats[0].0 * children_stats[1].0 * child_compute_cost
-                        + children_stats[0].0,
+                    compute_cost: children_stats[0].0
+                        * children_stats[1].0
+                        * child_compute_cost
+                        * profile.cpu_predicate_cost
+                        + children_stats[0].0 * profile.cpu_tuple_cost,
                     io_cost: 0.0,
                 }
             }
             // TODO: we should document that the first child is the left table, which is used to build
             // the hash table.
             PhysicalNodeType::PhysicalHashJoin(join_typ) => Cost {
-                compute_cost: children_stats[0].0 * 2.0 + children_stats[1].0,
+                compute_cost: children_stats[0].0 * profile.hash_build_cost
+                    + children_stats[1].0 * profile.hash_probe_cost,
                 io_cost: 0.0,
             },
-            PhysicalNodeType::PhysicalAgg => Cost {
-                compute_cost: children_stats[0].0
-                    * (children_costs[1].compute_cost + children_costs[2].compute_cost),
+            PhysicalNodeType::PhysicalSortMergeJoin(_) => Cost {
+                compute_cost: self.get_sort_merge_join_compute_cost(
+                    ListPred::try_from_pred_node(predicates[0].clone())?,
+                    ListPred::try_from_pred_node(predicates[1].clone())?,
+                    children_stats,
+                    &context.children_sort_orders,
+                )?,
+                io_cost: 0.0,
+            },
+            PhysicalNodeType::PhysicalAgg | PhysicalNodeType::PhysicalSortAgg => {
+                let group_cnt = match output_statistic {
+                    Some(statistic) => statistic,
+                    None => {
+                        self.derive_statistics(
+                            node,
+                            predicates,
+                            children_stats,
+                            context.clone(),
+                            false,
+                        )
+                        .await?
+                    }
+                };
+                output_statistic = Some(group_cnt.clone());
+                Cost {
+                    compute_cost: self.get_agg_compute_cost(
+                        node == PhysicalNodeType::PhysicalSortAgg,
+                        predicates[0].clone(),
+                        children_stats[0].0,
+                        group_cnt.0,
+                        (children_costs[1].compute_cost + children_costs[2].compute_cost)
+                            * profile.cpu_predicate_cost,
+                    )?,
+                    io_cost: 0.0,
+                }
+            }
+            PhysicalNodeType::PhysicalWindow => Cost {
+                compute_cost: self
+                    .get_window_compute_cost(
+                        context.children_group_ids[0],
+                        predicates[0].clone(),
+                        predicates[1].clone(),
+                        predicates[2].clone(),
+                        children_stats[0].0,
+                        (children_costs[1].compute_cost
+                            + children_costs[2].compute_cost
+                            + children_costs[3].compute_cost)
+                            * profile.cpu_predicate_cost,
+                    )
+                    .await?,
                 io_cost: 0.0,
             },
             PhysicalNodeType::PhysicalProjection => Cost {
-                compute_cost: children_stats[0].0 * children_costs[1].compute_cost,
+                compute_cost: children_stats[0].0
+                    * children_costs[1].compute_cost
+                    * profile.cpu_predicate_cost,
                 io_cost: 0.0,
             },
             PhysicalNodeType::PhysicalSort => Cost {
-                compute_cost: children_stats[0].0 * children_stats[0].0.ln_1p().max(1.0),
+                compute_cost: self.get_sort_compute_cost(children_stats[0].0),
+                io_cost: 0.0,
+            },
+            PhysicalNodeType::PhysicalHashSetOp(set_op)
+            | PhysicalNodeType::PhysicalSortSetOp(set_op) => Cost {
+                compute_cost: self
+                    .get_set_op_compute_cost(
+                        set_op,
+                        matches!(node, PhysicalNodeType::PhysicalSortSetOp(_)),
+                        &context.children_group_ids,
+                        children_stats,
+                    )
+                    .await?,
+                io_cost: 0.0,
+            },
+            PhysicalNodeType::PhysicalTopN => Cost {
+                compute_cost: self.get_top_n_compute_cost(
+                    children_stats[0].0,
+                    predicates[1].clone(),
+                    predicates[2].clone(),
+                )?,
                 io_cost: 0.0,
             },
         };
-        let res = self
-            .storage_manager
-            .store_cost(
-                context.expr_id,
-                Some(output_cost.clone()),
-                output_statistic,
-                None,
-            )
-            .await;
-        if res.is_err() {
-            eprintln!("Failed to store output cost");
+        if is_cost_cached {
+            let res = self
+                .storage_manager
+                .store_cost(
+                    context.expr_id,
+                    Some(output_cost.clone()),
+                    output_statistic,
+                    None,
+                )
+                .await;
+            if res.is_err() {
+                eprintln!("Failed to store output cost");
+            }
         }
         Ok(output_cost)
     }
@@ -149,7 +252,11 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
         }
         let output_statistic = match node {
             PhysicalNodeType::PhysicalScan => {
-                let table_id = TableId(predicates[0].data.as_ref().unwrap().as_u64());
+                let table_id = TableId(
+                    ConstantPred::try_from_pred_node(predicates[0].clone())?
+                        .value()
+                        .as_u64()?,
+                );
                 let row_cnt = self
                     .storage_manager
                     .get_table_row_count(table_id)
@@ -157,10 +264,23 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
                     .unwrap_or(1) as f64;
                 Ok(EstimatedStatistic(row_cnt))
             }
-            PhysicalNodeType::PhysicalEmptyRelation => Ok(EstimatedStatistic(0.01)),
-            PhysicalNodeType::PhysicalLimit => {
-                self.get_limit_row_cnt(children_statistics[0].clone(), predicates[1].clone())
+            PhysicalNodeType::PhysicalIndexScan => {
+                let (table_id, index_id) = get_index_scan_ids(predicates)?;
+                let index = self.get_table_index(table_id, index_id).await?;
+                self.get_index_scan_row_cnt(context.group_id, &index, predicates[2].clone())
+                    .await
             }
+            PhysicalNodeType::PhysicalEmptyRelation => Ok(EstimatedStatistic(0.01)),
+            PhysicalNodeType::PhysicalLimit => self.get_limit_row_cnt(
+                children_statistics[0].clone(),
+                predicates[0].clone(),
+                predicates[1].clone(),
+            ),
+            PhysicalNodeType::PhysicalTopN => self.get_limit_row_cnt(
+                children_statistics[0].clone(),
+                predicates[1].clone(),
+                predicates[2].clone(),
+            ),
             PhysicalNodeType::PhysicalFilter => {
                 self.get_filter_row_cnt(
                     children_statistics[0].clone(),
@@ -181,7 +301,9 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
                 )
                 .await
             }
-            PhysicalNodeType::PhysicalHashJoin(join_typ) => {
+            // A sort-merge join outputs the same rows as a hash join on the same keys.
+            PhysicalNodeType::PhysicalHashJoin(join_typ)
+            | PhysicalNodeType::PhysicalSortMergeJoin(join_typ) => {
                 self.get_hash_join_row_cnt(
                     join_typ,
                     context.group_id,
@@ -189,18 +311,27 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
                     children_statistics[1].clone(),
                     context.children_group_ids[0],
                     context.children_group_ids[1],
-                    ListPred::from_pred_node(predicates[0].clone()).unwrap(),
-                    ListPred::from_pred_node(predicates[1].clone()).unwrap(),
+                    ListPred::try_from_pred_node(predicates[0].clone())?,
+                    ListPred::try_from_pred_node(predicates[1].clone())?,
                 )
                 .await
             }
-            PhysicalNodeType::PhysicalAgg => {
-                self.get_agg_row_cnt(context.group_id, predicates[1].clone())
-                    .await
+            PhysicalNodeType::PhysicalAgg | PhysicalNodeType::PhysicalSortAgg => {
+                self.get_agg_row_cnt(
+                    context.group_id,
+                    predicates[1].clone(),
+                    children_statistics[0].clone(),
+                )
+                .await
             }
-            PhysicalNodeType::PhysicalSort | PhysicalNodeType::PhysicalProjection => {
-                Ok(children_statistics[0].clone())
+            PhysicalNodeType::PhysicalHashSetOp(set_op)
+            | PhysicalNodeType::PhysicalSortSetOp(set_op) => {
+                self.get_set_op_row_cnt(set_op, &context.children_group_ids, children_statistics)
+                    .await
             }
+            PhysicalNodeType::PhysicalSort
+            | PhysicalNodeType::PhysicalProjection
+            | PhysicalNodeType::PhysicalWindow => Ok(children_statistics[0].clone()),
         }?;
         if store_output_statistic {
             let res = self
@@ -220,7 +351,10 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
         source: String,
         data: String,
     ) -> CostModelResult<()> {
-        todo!()
+        self.storage_manager
+            .update_statistics(stats, source, data)
+            .await?;
+        Ok(())
     }
 
     async fn get_table_statistic_for_analysis(
@@ -229,7 +363,11 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
         stat_type: StatType,
         epoch_id: Option<EpochId>,
     ) -> CostModelResult<Option<StatValue>> {
-        todo!()
+        Ok(self
+            .storage_manager
+            .get_table_statistic(table_id, stat_type, epoch_id)
+            .await?
+            .map(StatValue::from))
     }
 
     async fn get_attribute_statistic_for_analysis(
@@ -238,7 +376,11 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
         stat_type: StatType,
         epoch_id: Option<EpochId>,
     ) -> CostModelResult<Option<StatValue>> {
-        todo!()
+        Ok(self
+            .storage_manager
+            .get_attribute_statistic(attr_ids, stat_type, epoch_id)
+            .await?
+            .map(StatValue::from))
     }
 
     async fn get_cost_for_analysis(
@@ -246,23 +388,1064 @@ impl<S: CostModelStorageManager + Send + Sync + 'static> CostModel for CostModel
         expr_id: ExprId,
         epoch_id: Option<EpochId>,
     ) -> CostModelResult<Option<crate::Cost>> {
-        todo!()
+        let (cost, _) = self
+            .storage_manager
+            .get_cost_analysis(expr_id, epoch_id)
+            .await?;
+        Ok(cost)
     }
 }
 
 impl<S: CostModelStorageManager> CostModelImpl<S> {
     /// TODO: documentation
-    /// TODO: if we have memory cache,
-    /// we should add the reference. (&AttributeCombValueStats)
     pub(crate) async fn get_attribute_comb_stats(
         &self,
         table_id: TableId,
         attr_comb: &[u64],
-    ) -> CostModelResult<Option<AttributeCombValueStats>> {
+    ) -> CostModelResult<Option<Arc<AttributeCombValueStats>>> {
         self.storage_manager
             .get_attributes_comb_statistics(table_id, attr_comb)
             .await
     }
 }
 
-// TODO: Add tests for `derive_statistic`` and `compute_operation_cost`.
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use assert_approx_eq::assert_approx_eq;
+    use datafusion_expr::{AggregateFunction, BuiltInWindowFunction, WindowFunction};
+    use optd_persistent::cost_model::interface::{Stat, StatType};
+    use serde_json::json;
+
+    use crate::{
+        common::{
+            nodes::{ArcPredicateNode, JoinType, PhysicalNodeType, SetOpType},
+            predicates::{bin_op_pred::BinOpType, sort_order_pred::SortOrderType},
+            properties::{
+                attr_ref::{AttrRef, GroupAttrRefs},
+                Attribute,
+            },
+            types::{AttrId, EpochId, ExprId, GroupId, TableId},
+            values::Value,
+        },
+        cost_profile::CostProfile,
+        storage::{mock::TableStats, CostModelStorageManager},
+        test_utils::tests::{
+            attr_index, bin_op, cnst, create_mock_cost_model,
+            create_mock_cost_model_with_table_stats, create_persistent_cost_model,
+            create_two_table_mock_cost_model_custom_row_cnts, empty_list, list,
+            per_attr_stats_with_ndistinct, remove_db_file, window_func, MemoGroupInfo,
+            TestOptCostModelMock, TEST_ATTR1_NAME, TEST_ATTR2_NAME, TEST_GROUP1_ID, TEST_GROUP2_ID,
+            TEST_TABLE1_ID, TEST_TABLE2_ID,
+        },
+        ComputeCostContext, Cost, CostModel, EstimatedStatistic, StatValue,
+    };
+
+    const JOIN_GROUP_ID: GroupId = GroupId(10);
+    const TABLE1_ROW_CNT: u64 = 1000;
+    const TABLE2_ROW_CNT: u64 = 100;
+
+    /// Table 1 has 1000 rows and table 2 has 100 rows. The only attribute of table 1 has 10
+    /// distinct values, and the only attribute of table 2 has 4. `TEST_GROUP1_ID` and
+    /// `TEST_GROUP2_ID` scan the two tables, and `JOIN_GROUP_ID` joins them.
+    fn create_cost_model_for_operators() -> TestOptCostModelMock {
+        let join_group = MemoGroupInfo::new(
+            vec![
+                Attribute::new_non_null_int64(TEST_ATTR1_NAME.to_string()),
+                Attribute::new_non_null_int64(TEST_ATTR2_NAME.to_string()),
+            ]
+            .into(),
+            GroupAttrRefs::new(
+                vec![
+                    AttrRef::new_base_table_attr_ref(TEST_TABLE1_ID, 0),
+                    AttrRef::new_base_table_attr_ref(TEST_TABLE2_ID, 0),
+                ],
+                None,
+            ),
+        );
+        create_two_table_mock_cost_model_custom_row_cnts(
+            per_attr_stats_with_ndistinct(10),
+            per_attr_stats_with_ndistinct(4),
+            TABLE1_ROW_CNT,
+            TABLE2_ROW_CNT,
+            Some(HashMap::from([(JOIN_GROUP_ID, join_group)])),
+        )
+    }
+
+    fn context(
+        expr_id: u64,
+        group_id: GroupId,
+        children_group_ids: Vec<GroupId>,
+    ) -> ComputeCostContext {
+        ComputeCostContext {
+            group_id,
+            expr_id: ExprId(expr_id),
+            children_group_ids,
+            ..Default::default()
+        }
+    }
+
+    fn compute_cost(compute_cost: f64) -> Cost {
+        Cost {
+            compute_cost,
+            io_cost: 0.0,
+        }
+    }
+
+    /// Runs `derive_statistics` and `compute_operation_cost` on a fresh cost model, each with its
+    /// own expression id so that the results are not served from the cost store.
+    async fn derive_and_compute(
+        node: PhysicalNodeType,
+        predicates: &[ArcPredicateNode],
+        children_costs: &[Cost],
+        children_stats: &[EstimatedStatistic],
+        group_id: GroupId,
+        children_group_ids: Vec<GroupId>,
+    ) -> (EstimatedStatistic, Cost) {
+        let cost_model = create_cost_model_for_operators();
+        let row_cnt = cost_model
+            .derive_statistics(
+                node,
+                predicates,
+                children_stats,
+                context(1, group_id, children_group_ids.clone()),
+                false,
+            )
+            .await
+            .unwrap();
+        let cost = cost_model
+            .compute_operation_cost(
+                node,
+                predicates,
+                children_costs,
+                children_stats,
+                context(2, group_id, children_group_ids),
+            )
+            .await
+            .unwrap();
+        (row_cnt, cost)
+    }
+
+    fn table_stat(table_id: TableId, stat_type: StatType, value: serde_json::Value) -> Stat {
+        Stat {
+            stat_type,
+            stat_value: value,
+            attr_ids: vec![],
+            table_id: Some(table_id.0 as i32),
+            name: "table_stat".to_string(),
+        }
+    }
+
+    fn attr_stat(attr_ids: Vec<i32>, stat_type: StatType, value: serde_json::Value) -> Stat {
+        Stat {
+            stat_type,
+            stat_value: value,
+            attr_ids,
+            table_id: None,
+            name: "attr_stat".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_stat_value_from_json() {
+        assert_eq!(StatValue::from(json!(42)), StatValue::Int(42));
+        assert_eq!(StatValue::from(json!(0.25)), StatValue::Float(0.25));
+        assert_eq!(
+            StatValue::from(json!("abc")),
+            StatValue::String("abc".to_string())
+        );
+        assert_eq!(
+            StatValue::from(json!({"type": "SimpleFrequency"})),
+            StatValue::String("{\"type\":\"SimpleFrequency\"}".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_table_statistic_for_analysis_mock() {
+        let cost_model =
+            create_mock_cost_model(vec![TEST_TABLE1_ID], vec![HashMap::new()], vec![Some(42)]);
+
+        // Before any update, the row count comes from the initial table stats.
+        let res = cost_model
+            .get_table_statistic_for_analysis(TEST_TABLE1_ID, StatType::TableRowCount, None)
+            .await
+            .unwrap();
+        assert_eq!(res, Some(StatValue::Int(42)));
+
+        cost_model
+            .update_statistics(
+                vec![table_stat(
+                    TEST_TABLE1_ID,
+                    StatType::TableRowCount,
+                    json!(100),
+                )],
+                "test".to_string(),
+                "first".to_string(),
+            )
+            .await
+            .unwrap();
+        cost_model
+            .update_statistics(
+                vec![table_stat(
+                    TEST_TABLE1_ID,
+                    StatType::TableRowCount,
+                    json!(200),
+                )],
+                "test".to_string(),
+                "second".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let latest = cost_model
+            .get_table_statistic_for_analysis(TEST_TABLE1_ID, StatType::TableRowCount, None)
+            .await
+            .unwrap();
+        assert_eq!(latest, Some(StatValue::Int(200)));
+        let first = cost_model
+            .get_table_statistic_for_analysis(
+                TEST_TABLE1_ID,
+                StatType::TableRowCount,
+                Some(EpochId(1)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(first, Some(StatValue::Int(100)));
+        let missing = cost_model
+            .get_table_statistic_for_analysis(TEST_TABLE1_ID, StatType::NonNullCount, None)
+            .await
+            .unwrap();
+        assert_eq!(missing, None);
+    }
+
+    #[tokio::test]
+    async fn test_attribute_statistic_for_analysis_mock() {
+        let cost_model =
+            create_mock_cost_model(vec![TEST_TABLE1_ID], vec![HashMap::new()], vec![None]);
+        let epoch_id = cost_model
+            .storage_manager
+            .update_statistics(
+                vec![
+                    attr_stat(vec![2, 1], StatType::Cardinality, json!(7)),
+                    attr_stat(vec![1], StatType::NonNullCount, json!(0.5)),
+                ],
+                "test".to_string(),
+                "first".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(epoch_id, Some(EpochId(1)));
+
+        // Writing the same values again does not create a new epoch.
+        let epoch_id = cost_model
+            .storage_manager
+            .update_statistics(
+                vec![attr_stat(vec![1, 2], StatType::Cardinality, json!(7))],
+                "test".to_string(),
+                "second".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(epoch_id, None);
+
+        let res = cost_model
+            .get_attribute_statistic_for_analysis(
+                vec![AttrId(1), AttrId(2)],
+                StatType::Cardinality,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(res, Some(StatValue::Int(7)));
+        let res = cost_model
+            .get_attribute_statistic_for_analysis(
+                vec![AttrId(1)],
+                StatType::NonNullCount,
+                Some(EpochId(1)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res, Some(StatValue::Float(0.5)));
+        let res = cost_model
+            .get_attribute_statistic_for_analysis(
+                vec![AttrId(1)],
+                StatType::NonNullCount,
+                Some(EpochId(2)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[tokio::test]
+    async fn test_table_statistic_for_analysis_persistent() {
+        const DATABASE_FILE: &str = "test_table_statistic_for_analysis_persistent.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        let table_id = TableId(1);
+
+        // The initial row count of table 1 is 0 in epoch 1.
+        let res = cost_model
+            .get_table_statistic_for_analysis(table_id, StatType::TableRowCount, None)
+            .await
+            .unwrap();
+        assert_eq!(res, Some(StatValue::Int(0)));
+
+        cost_model
+            .update_statistics(
+                vec![table_stat(table_id, StatType::TableRowCount, json!(100))],
+                "test".to_string(),
+                "test_table_statistic_for_analysis_persistent".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let latest = cost_model
+            .get_table_statistic_for_analysis(table_id, StatType::TableRowCount, None)
+            .await
+            .unwrap();
+        assert_eq!(latest, Some(StatValue::Int(100)));
+        let first = cost_model
+            .get_table_statistic_for_analysis(table_id, StatType::TableRowCount, Some(EpochId(1)))
+            .await
+            .unwrap();
+        assert_eq!(first, Some(StatValue::Int(0)));
+
+        remove_db_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_attribute_statistic_for_analysis_persistent() {
+        const DATABASE_FILE: &str = "test_attribute_statistic_for_analysis_persistent.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+
+        let res = cost_model
+            .get_attribute_statistic_for_analysis(vec![AttrId(1)], StatType::Cardinality, None)
+            .await
+            .unwrap();
+        assert_eq!(res, Some(StatValue::Int(0)));
+
+        cost_model
+            .update_statistics(
+                vec![attr_stat(vec![2, 1], StatType::Cardinality, json!(5))],
+                "test".to_string(),
+                "test_attribute_statistic_for_analysis_persistent".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let latest = cost_model
+            .get_attribute_statistic_for_analysis(
+                vec![AttrId(1), AttrId(2)],
+                StatType::Cardinality,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(latest, Some(StatValue::Int(5)));
+        let first = cost_model
+            .get_attribute_statistic_for_analysis(
+                vec![AttrId(1), AttrId(2)],
+                StatType::Cardinality,
+                Some(EpochId(1)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(first, Some(StatValue::Int(0)));
+
+        remove_db_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_cost_for_analysis_persistent() {
+        const DATABASE_FILE: &str = "test_cost_for_analysis_persistent.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        let expected = Cost {
+            compute_cost: 10.0,
+            io_cost: 10.0,
+        };
+
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(1), Some(EpochId(1)))
+            .await
+            .unwrap();
+        assert_eq!(res, Some(expected.clone()));
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(1), None)
+            .await
+            .unwrap();
+        assert_eq!(res, Some(expected));
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(1), Some(EpochId(2)))
+            .await
+            .unwrap();
+        assert_eq!(res, None);
+
+        remove_db_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_scan() {
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalScan,
+            &[cnst(Value::UInt64(TEST_TABLE1_ID.0))],
+            &[],
+            &[],
+            TEST_GROUP1_ID,
+            vec![],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(TABLE1_ROW_CNT as f64));
+        // The rows of the int64 attribute are 24 + 8 bytes wide, so 1000 rows take 4 pages.
+        assert_eq!(
+            cost,
+            Cost {
+                compute_cost: 0.0,
+                io_cost: 4.0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_relation() {
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalEmptyRelation,
+            &[],
+            &[],
+            &[],
+            TEST_GROUP1_ID,
+            vec![],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(0.01));
+        assert_eq!(cost, compute_cost(0.1));
+    }
+
+    #[tokio::test]
+    async fn test_limit() {
+        let child_stats = [EstimatedStatistic(1000.0)];
+        let child_costs = [compute_cost(7.0)];
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalLimit,
+            &[cnst(Value::UInt64(0)), cnst(Value::UInt64(10))],
+            &child_costs,
+            &child_stats,
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(10.0));
+        assert_eq!(cost, compute_cost(7.0));
+
+        // `u64::MAX` means there is no limit.
+        let (row_cnt, _) = derive_and_compute(
+            PhysicalNodeType::PhysicalLimit,
+            &[cnst(Value::UInt64(0)), cnst(Value::UInt64(u64::MAX))],
+            &child_costs,
+            &child_stats,
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+
+        // The skipped rows are not output.
+        let (row_cnt, _) = derive_and_compute(
+            PhysicalNodeType::PhysicalLimit,
+            &[cnst(Value::UInt64(995)), cnst(Value::UInt64(10))],
+            &child_costs,
+            &child_stats,
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(5.0));
+        let (row_cnt, _) = derive_and_compute(
+            PhysicalNodeType::PhysicalLimit,
+            &[cnst(Value::UInt64(2000)), cnst(Value::UInt64(u64::MAX))],
+            &child_costs,
+            &child_stats,
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(0.0));
+    }
+
+    #[tokio::test]
+    async fn test_filter() {
+        // The selectivity of `attr1 = 1` is 1 / ndistinct = 0.1.
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalFilter,
+            &[bin_op(BinOpType::Eq, attr_index(0), cnst(Value::Int32(1)))],
+            &[compute_cost(5.0), compute_cost(2.0)],
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(100.0));
+        assert_eq!(cost, compute_cost(2000.0));
+    }
+
+    #[tokio::test]
+    async fn test_nested_loop_join() {
+        // The selectivity of `t1.attr1 = t2.attr1` is min(1 / 10, 1 / 4) = 0.1.
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalNestedLoopJoin(JoinType::Inner),
+            &[bin_op(BinOpType::Eq, attr_index(0), attr_index(1))],
+            &[compute_cost(1.0), compute_cost(1.0), compute_cost(0.5)],
+            &[EstimatedStatistic(1000.0), EstimatedStatistic(100.0)],
+            JOIN_GROUP_ID,
+            vec![TEST_GROUP1_ID, TEST_GROUP2_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(10000.0));
+        assert_eq!(cost, compute_cost(1000.0 * 100.0 * 0.5 + 1000.0));
+    }
+
+    #[tokio::test]
+    async fn test_hash_join() {
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalHashJoin(JoinType::Inner),
+            &[list(vec![attr_index(0)]), list(vec![attr_index(0)])],
+            &[compute_cost(1.0), compute_cost(1.0)],
+            &[EstimatedStatistic(1000.0), EstimatedStatistic(100.0)],
+            JOIN_GROUP_ID,
+            vec![TEST_GROUP1_ID, TEST_GROUP2_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(10000.0));
+        assert_eq!(cost, compute_cost(1000.0 * 2.0 + 100.0));
+    }
+
+    #[tokio::test]
+    async fn test_sort_merge_join() {
+        let node = PhysicalNodeType::PhysicalSortMergeJoin(JoinType::Inner);
+        let predicates = [list(vec![attr_index(0)]), list(vec![attr_index(0)])];
+        let children_stats = [EstimatedStatistic(1000.0), EstimatedStatistic(100.0)];
+        let sort_cost = |n: f64| n * n.ln_1p();
+        let cost_model = create_cost_model_for_operators();
+        let row_cnt = cost_model
+            .derive_statistics(
+                node,
+                &predicates,
+                &children_stats,
+                context(1, JOIN_GROUP_ID, vec![TEST_GROUP1_ID, TEST_GROUP2_ID]),
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(row_cnt, EstimatedStatistic(10000.0));
+
+        // The same expression is costed with different child orders, which must not be served
+        // from the cached cost of a previous order.
+        for (children_sort_orders, expected_sort_cost) in [
+            (vec![], sort_cost(1000.0) + sort_cost(100.0)),
+            // Only the right child is sorted.
+            (
+                vec![
+                    vec![(0, SortOrderType::Asc), (1, SortOrderType::Asc)],
+                    vec![],
+                ],
+                sort_cost(100.0),
+            ),
+            (
+                vec![vec![(0, SortOrderType::Asc)], vec![(0, SortOrderType::Asc)]],
+                0.0,
+            ),
+            (
+                vec![
+                    vec![(0, SortOrderType::Desc)],
+                    vec![(0, SortOrderType::Desc)],
+                ],
+                0.0,
+            ),
+            // Both children are sorted on their keys, but in different directions, so the
+            // cheaper one is sorted again.
+            (
+                vec![
+                    vec![(0, SortOrderType::Desc)],
+                    vec![(0, SortOrderType::Asc)],
+                ],
+                sort_cost(100.0),
+            ),
+        ] {
+            let cost = cost_model
+                .compute_operation_cost(
+                    node,
+                    &predicates,
+                    &[compute_cost(1.0), compute_cost(1.0)],
+                    &children_stats,
+                    ComputeCostContext {
+                        group_id: JOIN_GROUP_ID,
+                        expr_id: ExprId(1),
+                        children_group_ids: vec![TEST_GROUP1_ID, TEST_GROUP2_ID],
+                        children_sort_orders,
+                    },
+                )
+                .await
+                .unwrap();
+            assert_eq!(cost, compute_cost(expected_sort_cost + 1100.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agg() {
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalAgg,
+            &[empty_list(), list(vec![attr_index(0)])],
+            &[compute_cost(1.0), compute_cost(2.0), compute_cost(3.0)],
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(10.0));
+        // Each input row is hashed and each of the 10 groups is inserted into the hash table.
+        assert_eq!(cost, compute_cost(1000.0 * (1.0 + 2.0 + 3.0) + 10.0 * 2.0));
+
+        // A sort-based aggregation only compares the keys of consecutive rows.
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalSortAgg,
+            &[empty_list(), list(vec![attr_index(0)])],
+            &[compute_cost(1.0), compute_cost(2.0), compute_cost(3.0)],
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(10.0));
+        assert_eq!(cost, compute_cost(1000.0 * (0.5 + 2.0 + 3.0)));
+
+        // Without GROUP BY, there is exactly one output row.
+        let (row_cnt, _) = derive_and_compute(
+            PhysicalNodeType::PhysicalAgg,
+            &[empty_list(), empty_list()],
+            &[compute_cost(1.0), compute_cost(2.0), compute_cost(3.0)],
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_window() {
+        let window_funcs = list(vec![
+            window_func(
+                WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::RowNumber),
+                vec![],
+            ),
+            window_func(
+                WindowFunction::AggregateFunction(AggregateFunction::Median),
+                vec![attr_index(0)],
+            ),
+        ]);
+        let children_costs = [
+            compute_cost(1.0),
+            compute_cost(0.5),
+            compute_cost(0.25),
+            compute_cost(0.25),
+        ];
+        // ROW_NUMBER() costs 1 per row and MEDIAN costs 4 per row and 4 per frame.
+        let per_row = 0.5 + 0.25 + 0.25 + 1.0 + 4.0 + 4.0;
+
+        // The 1000 rows are hashed into 10 partitions of 100 rows, which are sorted.
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalWindow,
+            &[
+                window_funcs.clone(),
+                list(vec![attr_index(0)]),
+                list(vec![attr_index(0)]),
+            ],
+            &children_costs,
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+        assert_approx_eq!(
+            cost.compute_cost,
+            1000.0 + 10.0 * 100.0 * 100_f64.ln_1p() + 1000.0 * per_row
+        );
+
+        // Without PARTITION BY, the whole input is one partition.
+        let (_, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalWindow,
+            &[
+                window_funcs.clone(),
+                empty_list(),
+                list(vec![attr_index(0)]),
+            ],
+            &children_costs,
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_approx_eq!(
+            cost.compute_cost,
+            1000.0 * 1000_f64.ln_1p() + 1000.0 * per_row
+        );
+
+        // A constant is not an ordinal position as in GROUP BY, so it is a single partition.
+        let (_, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalWindow,
+            &[
+                window_funcs.clone(),
+                list(vec![cnst(Value::Int64(5))]),
+                list(vec![attr_index(0)]),
+            ],
+            &children_costs,
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_approx_eq!(
+            cost.compute_cost,
+            1000.0 + 1000.0 * 1000_f64.ln_1p() + 1000.0 * per_row
+        );
+
+        // Without ORDER BY, the partitions are not sorted.
+        let (_, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalWindow,
+            &[window_funcs, list(vec![attr_index(0)]), empty_list()],
+            &children_costs,
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_approx_eq!(cost.compute_cost, 1000.0 + 1000.0 * per_row);
+    }
+
+    #[tokio::test]
+    async fn test_projection() {
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalProjection,
+            &[list(vec![attr_index(0)])],
+            &[compute_cost(1.0), compute_cost(0.5)],
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+        assert_eq!(cost, compute_cost(500.0));
+    }
+
+    #[tokio::test]
+    async fn test_sort() {
+        let (row_cnt, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalSort,
+            &[list(vec![])],
+            &[compute_cost(1.0)],
+            &[EstimatedStatistic(1000.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+        assert_eq!(cost, compute_cost(1000.0 * 1000_f64.ln_1p()));
+
+        // The sort cost is at least linear in the input size.
+        let (_, cost) = derive_and_compute(
+            PhysicalNodeType::PhysicalSort,
+            &[list(vec![])],
+            &[compute_cost(1.0)],
+            &[EstimatedStatistic(1.0)],
+            TEST_GROUP1_ID,
+            vec![TEST_GROUP1_ID],
+        )
+        .await;
+        assert_eq!(cost, compute_cost(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_top_n() {
+        async fn top_n(skip: u64, fetch: u64) -> (EstimatedStatistic, Cost) {
+            derive_and_compute(
+                PhysicalNodeType::PhysicalTopN,
+                &[
+                    list(vec![]),
+                    cnst(Value::UInt64(skip)),
+                    cnst(Value::UInt64(fetch)),
+                ],
+                &[compute_cost(1.0)],
+                &[EstimatedStatistic(1000.0)],
+                TEST_GROUP1_ID,
+                vec![TEST_GROUP1_ID],
+            )
+            .await
+        }
+
+        // Every row goes through a heap of 10 rows.
+        let (row_cnt, cost) = top_n(0, 10).await;
+        assert_eq!(row_cnt, EstimatedStatistic(10.0));
+        assert_eq!(cost, compute_cost(1000.0 * 10_f64.ln_1p()));
+
+        // The heap also keeps the skipped rows.
+        let (row_cnt, cost) = top_n(20, 10).await;
+        assert_eq!(row_cnt, EstimatedStatistic(10.0));
+        assert_eq!(cost, compute_cost(1000.0 * 30_f64.ln_1p()));
+
+        // Without a fetch, this is a full sort.
+        let (row_cnt, cost) = top_n(990, u64::MAX).await;
+        assert_eq!(row_cnt, EstimatedStatistic(10.0));
+        assert_eq!(cost, compute_cost(1000.0 * 1000_f64.ln_1p()));
+    }
+
+    #[tokio::test]
+    async fn test_set_ops() {
+        async fn set_op(node: PhysicalNodeType) -> (EstimatedStatistic, Cost) {
+            derive_and_compute(
+                node,
+                &[],
+                &[compute_cost(1.0), compute_cost(1.0)],
+                &[EstimatedStatistic(1000.0), EstimatedStatistic(100.0)],
+                JOIN_GROUP_ID,
+                vec![TEST_GROUP1_ID, TEST_GROUP2_ID],
+            )
+            .await
+        }
+
+        // The children have 10 and 4 distinct rows.
+        let (row_cnt, cost) =
+            set_op(PhysicalNodeType::PhysicalHashSetOp(SetOpType::UnionAll)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(1100.0));
+        assert_eq!(cost, compute_cost(1100.0));
+        let (row_cnt, cost) = set_op(PhysicalNodeType::PhysicalHashSetOp(SetOpType::Union)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(14.0));
+        assert_eq!(cost, compute_cost(1100.0 + 14.0 * 2.0));
+        let (row_cnt, cost) =
+            set_op(PhysicalNodeType::PhysicalHashSetOp(SetOpType::Intersect)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(4.0));
+        assert_eq!(cost, compute_cost(1100.0 + 10.0 * 2.0));
+        let (row_cnt, _) =
+            set_op(PhysicalNodeType::PhysicalHashSetOp(SetOpType::IntersectAll)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(100.0));
+        let (row_cnt, _) = set_op(PhysicalNodeType::PhysicalHashSetOp(SetOpType::Except)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(10.0));
+        let (row_cnt, _) = set_op(PhysicalNodeType::PhysicalHashSetOp(SetOpType::ExceptAll)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(1000.0));
+
+        // A sort-based set operation sorts both children instead of hashing them.
+        let (row_cnt, cost) = set_op(PhysicalNodeType::PhysicalSortSetOp(SetOpType::Union)).await;
+        assert_eq!(row_cnt, EstimatedStatistic(14.0));
+        assert_eq!(
+            cost,
+            compute_cost(1000.0 * 1000_f64.ln_1p() + 100.0 * 100_f64.ln_1p() + 1100.0 * 0.5)
+        );
+        let (_, cost) = set_op(PhysicalNodeType::PhysicalSortSetOp(SetOpType::UnionAll)).await;
+        assert_eq!(cost, compute_cost(1100.0));
+    }
+
+    #[tokio::test]
+    async fn test_set_op_many_attrs_is_bounded() {
+        // Without a bound, there would be more than 2^40 attribute combinations to look up for
+        // the distinct rows of each child.
+        let attr_cnt = 40;
+        let cost_model = create_mock_cost_model_with_table_stats(
+            HashMap::from([(
+                TEST_TABLE1_ID,
+                TableStats::new(
+                    TABLE1_ROW_CNT,
+                    (0..attr_cnt)
+                        .map(|attr_idx| (vec![attr_idx], per_attr_stats_with_ndistinct(2)))
+                        .collect(),
+                ),
+            )]),
+            (0..attr_cnt)
+                .map(|attr_idx| (TEST_TABLE1_ID, attr_idx, None))
+                .collect(),
+        );
+        let children_row_cnt = 2f64.powi(attr_cnt as i32);
+        let row_cnt = cost_model
+            .get_set_op_row_cnt(
+                SetOpType::Union,
+                &[TEST_GROUP1_ID, TEST_GROUP1_ID],
+                &[
+                    EstimatedStatistic(children_row_cnt),
+                    EstimatedStatistic(children_row_cnt),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(row_cnt, EstimatedStatistic(2.0 * children_row_cnt));
+    }
+    #[tokio::test]
+    async fn test_custom_cost_profile() {
+        let mut cost_model = create_cost_model_for_operators();
+        cost_model.cost_profile = CostProfile {
+            seq_page_io_cost: 2.0,
+            hash_build_cost: 3.0,
+            hash_probe_cost: 0.5,
+            cpu_predicate_cost: 0.1,
+            ..Default::default()
+        };
+
+        let cost = cost_model
+            .compute_operation_cost(
+                PhysicalNodeType::PhysicalScan,
+                &[cnst(Value::UInt64(TEST_TABLE1_ID.0))],
+                &[],
+                &[],
+                context(1, TEST_GROUP1_ID, vec![]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            cost,
+            Cost {
+                compute_cost: 0.0,
+                io_cost: 4.0 * 2.0,
+            }
+        );
+
+        let cost = cost_model
+            .compute_operation_cost(
+                PhysicalNodeType::PhysicalHashJoin(JoinType::Inner),
+                &[list(vec![attr_index(0)]), list(vec![attr_index(0)])],
+                &[compute_cost(1.0), compute_cost(1.0)],
+                &[EstimatedStatistic(1000.0), EstimatedStatistic(100.0)],
+                context(2, JOIN_GROUP_ID, vec![TEST_GROUP1_ID, TEST_GROUP2_ID]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(cost, compute_cost(1000.0 * 3.0 + 100.0 * 0.5));
+
+        let cost = cost_model
+            .compute_operation_cost(
+                PhysicalNodeType::PhysicalFilter,
+                &[bin_op(BinOpType::Eq, attr_index(0), cnst(Value::Int32(1)))],
+                &[compute_cost(5.0), compute_cost(2.0)],
+                &[EstimatedStatistic(1000.0)],
+                context(3, TEST_GROUP1_ID, vec![TEST_GROUP1_ID]),
+            )
+            .await
+            .unwrap();
+        assert_approx_eq::assert_approx_eq!(cost.compute_cost, 1000.0 * 2.0 * 0.1);
+    }
+
+    #[tokio::test]
+    async fn test_stored_cost_and_statistic_are_reused() {
+        let cost_model = create_cost_model_for_operators();
+        let scan = [cnst(Value::UInt64(TEST_TABLE1_ID.0))];
+        let ctx = context(1, TEST_GROUP1_ID, vec![]);
+
+        let row_cnt = cost_model
+            .derive_statistics(
+                PhysicalNodeType::PhysicalScan,
+                &scan,
+                &[],
+                ctx.clone(),
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(row_cnt, EstimatedStatistic(TABLE1_ROW_CNT as f64));
+        assert_eq!(
+            cost_model
+                .storage_manager
+                .get_cost(ExprId(1))
+                .await
+                .unwrap(),
+            (None, Some(row_cnt.clone()))
+        );
+
+        // The cost is computed from the stored statistic and stored along with it.
+        let cost = cost_model
+            .compute_operation_cost(PhysicalNodeType::PhysicalScan, &scan, &[], &[], ctx.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            cost_model
+                .storage_manager
+                .get_cost(ExprId(1))
+                .await
+                .unwrap(),
+            (Some(cost.clone()), Some(row_cnt))
+        );
+
+        // A stored cost is returned as is, whatever the inputs are.
+        let cached_cost = cost_model
+            .compute_operation_cost(PhysicalNodeType::PhysicalEmptyRelation, &[], &[], &[], ctx)
+            .await
+            .unwrap();
+        assert_eq!(cached_cost, cost);
+    }
+
+    #[tokio::test]
+    async fn test_cost_for_analysis_mock() {
+        let cost_model = create_cost_model_for_operators();
+        let storage_manager = &cost_model.storage_manager;
+        let old_cost = compute_cost(1.0);
+        let new_cost = compute_cost(2.0);
+
+        storage_manager
+            .store_cost(ExprId(1), Some(old_cost.clone()), None, None)
+            .await
+            .unwrap();
+        let epoch_id = cost_model
+            .storage_manager
+            .update_statistics(
+                vec![table_stat(
+                    TEST_TABLE1_ID,
+                    StatType::TableRowCount,
+                    json!(10),
+                )],
+                "test".to_string(),
+                "test_cost_for_analysis_mock".to_string(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The cost of the old epoch is invalidated by the statistic update.
+        assert_eq!(
+            storage_manager.get_cost(ExprId(1)).await.unwrap(),
+            (None, None)
+        );
+        storage_manager
+            .store_cost(ExprId(1), Some(new_cost.clone()), None, None)
+            .await
+            .unwrap();
+
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(1), Some(EpochId(0)))
+            .await
+            .unwrap();
+        assert_eq!(res, Some(old_cost));
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(1), Some(epoch_id))
+            .await
+            .unwrap();
+        assert_eq!(res, Some(new_cost.clone()));
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(1), None)
+            .await
+            .unwrap();
+        assert_eq!(res, Some(new_cost));
+        // No cost has been recorded for the expression in a later epoch.
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(1), Some(EpochId(epoch_id.0 + 1)))
+            .await
+            .unwrap();
+        assert_eq!(res, None);
+        let res = cost_model
+            .get_cost_for_analysis(ExprId(2), Some(EpochId(0)))
+            .await
+            .unwrap();
+        assert_eq!(res, None);
+
+        // Costs cannot be stored in an epoch that does not exist yet.
+        assert!(storage_manager
+            .store_cost(ExprId(1), Some(compute_cost(3.0)), None, Some(EpochId(42)))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use std::{collections::BTreeMap, path::Path};
+
+use datafusion_expr::AggregateFunction;
+use serde::{Deserialize, Serialize};
+
+use crate::{CostModelError, CostModelResult};
+
+/// The cost of computing one aggregate function.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AggFuncCost {
+    /// The cost of feeding one input row to the function, e.g. adding it to a running sum or
+    /// appending it to a buffer.
+    pub per_row: f64,
+    /// The cost of producing the result of one group, e.g. sorting the buffered values of a
+    /// MEDIAN.
+    pub per_group: f64,
+}
+
+impl AggFuncCost {
+    pub const fn new(per_row: f64, per_group: f64) -> Self {
+        Self { per_row, per_group }
+    }
+}
+
+/// The constants the cost formulas are calibrated with, so that the same formulas can model
+/// different execution engines.
+///
+/// The default profile is calibrated for optd. Missing fields take their default values when a
+/// profile is deserialized, so a profile only needs to list what it overrides.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostProfile {
+    /// The cost of processing one row that an operator outputs.
+    pub cpu_tuple_cost: f64,
+    /// The weight of the cost of evaluating the predicates and expressions of an operator on one
+    /// row.
+    pub cpu_predicate_cost: f64,
+    /// The cost of reading one page sequentially.
+    pub seq_page_io_cost: f64,
+    /// The cost of reading one page at a random position.
+    pub random_page_io_cost: f64,
+    /// The size in bytes of a page.
+    pub page_size: f64,
+    /// The width in bytes of the header that is stored along with each row.
+    pub tuple_header_width: f64,
+    /// The number of entries in an inner page of a B-tree index, which determines its height.
+    pub btree_fanout: f64,
+    /// The cost of inserting one row of the build side into the hash table of a hash join.
+    pub hash_build_cost: f64,
+    /// The cost of probing the hash table of a hash join with one row of the probe side.
+    pub hash_probe_cost: f64,
+    /// The cost of comparing two rows when sorting.
+    pub sort_comparison_cost: f64,
+    /// The cost of advancing the merge of a sort-merge join by one input row.
+    pub merge_join_per_row: f64,
+    /// The cost of producing an empty relation.
+    pub empty_relation_cost: f64,
+    /// The cost of hashing the GROUP BY keys of an input row and probing the hash table in a
+    /// hash aggregation.
+    pub hash_agg_per_row: f64,
+    /// The cost of inserting a new group into the hash table in a hash aggregation.
+    pub hash_agg_per_group: f64,
+    /// The cost of comparing the GROUP BY keys of an input row with the current group in a
+    /// sort-based aggregation.
+    pub sort_agg_per_row: f64,
+    /// The cost of aggregate functions that are not in `agg_funcs`.
+    pub default_agg_func: AggFuncCost,
+    /// The cost of aggregate functions, by their SQL name, e.g. `"COUNT"` or `"ARRAY_AGG"`.
+    pub agg_funcs: BTreeMap<String, AggFuncCost>,
+    /// The cost of hashing the PARTITION BY keys of one row to assign it to its partition in a
+    /// window operator.
+    pub window_partition_per_row: f64,
+    /// The cost of computing a window function that is not an aggregate, e.g. `ROW_NUMBER()`
+    /// or `LAG(a)`, for one row.
+    pub window_func_per_row: f64,
+    /// The extra per-row cost of removing duplicated inputs of a DISTINCT aggregate, e.g.
+    /// `COUNT(DISTINCT a)`.
+    pub distinct_agg_per_row: f64,
+}
+
+impl Default for CostProfile {
+    fn default() -> Self {
+        use AggregateFunction::*;
+        let agg_funcs = [
+            // Functions that keep all the values of a group.
+            (ArrayAgg, AggFuncCost::new(4.0, 1.0)),
+            (Median, AggFuncCost::new(4.0, 4.0)),
+            // Functions that keep a sketch of the values of a group.
+            (ApproxDistinct, AggFuncCost::new(2.0, 2.0)),
+            (ApproxMedian, AggFuncCost::new(3.0, 2.0)),
+            (ApproxPercentileCont, AggFuncCost::new(3.0, 2.0)),
+            (ApproxPercentileContWithWeight, AggFuncCost::new(3.0, 2.0)),
+        ]
+        .into_iter()
+        .map(|(func, cost)| (func.to_string(), cost))
+        .collect();
+        Self {
+            cpu_tuple_cost: 1.0,
+            cpu_predicate_cost: 1.0,
+            seq_page_io_cost: 1.0,
+            random_page_io_cost: 4.0,
+            page_size: 8192.0,
+            tuple_header_width: 24.0,
+            btree_fanout: 256.0,
+            hash_build_cost: 2.0,
+            hash_probe_cost: 1.0,
+            sort_comparison_cost: 1.0,
+            merge_join_per_row: 1.0,
+            empty_relation_cost: 0.1,
+            hash_agg_per_row: 1.0,
+            hash_agg_per_group: 2.0,
+            sort_agg_per_row: 0.5,
+            default_agg_func: AggFuncCost::new(1.0, 0.0),
+            agg_funcs,
+            distinct_agg_per_row: 4.0,
+            window_partition_per_row: 1.0,
+            window_func_per_row: 1.0,
+        }
+    }
+}
+
+impl CostProfile {
+    /// Parses a profile from JSON.
+    pub fn from_json_str(json: &str) -> CostModelResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|err| CostModelError::InvalidCostProfile(format!("invalid JSON: {err}")))
+    }
+
+    /// Parses a profile from TOML.
+    pub fn from_toml_str(toml: &str) -> CostModelResult<Self> {
+        toml::from_str(toml)
+            .map_err(|err| CostModelError::InvalidCostProfile(format!("invalid TOML: {err}")))
+    }
+
+    /// Loads a profile from a `.json` or a `.toml` file.
+    pub fn from_file(path: impl AsRef<Path>) -> CostModelResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|err| {
+            CostModelError::InvalidCostProfile(format!("cannot read {}: {err}", path.display()))
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json_str(&content),
+            Some("toml") => Self::from_toml_str(&content),
+            _ => Err(CostModelError::InvalidCostProfile(format!(
+                "{} is neither a .json nor a .toml file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Returns the cost of an aggregate function, without the DISTINCT overhead.
+    pub fn agg_func_cost(&self, func: &AggregateFunction) -> AggFuncCost {
+        self.agg_funcs
+            .get(&func.to_string())
+            .copied()
+            .unwrap_or(self.default_agg_func)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion_expr::AggregateFunction;
+
+    use super::{AggFuncCost, CostProfile};
+    use crate::CostModelError;
+
+    #[test]
+    fn test_agg_func_cost() {
+        let profile = CostProfile::default();
+        assert_eq!(
+            profile.agg_func_cost(&AggregateFunction::Sum),
+            profile.default_agg_func
+        );
+        assert_eq!(
+            profile.agg_func_cost(&AggregateFunction::Median),
+            AggFuncCost::new(4.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_partial_profile() {
+        let profile = CostProfile::from_json_str(
+            r#"{"hash_agg_per_row": 3.0, "agg_funcs": {"SUM": {"per_row": 0.5, "per_group": 0.0}}}"#,
+        )
+        .unwrap();
+        assert_eq!(profile.hash_agg_per_row, 3.0);
+        assert_eq!(
+            profile.sort_agg_per_row,
+            CostProfile::default().sort_agg_per_row
+        );
+        assert_eq!(
+            profile.agg_func_cost(&AggregateFunction::Sum),
+            AggFuncCost::new(0.5, 0.0)
+        );
+        // The functions of the default table are replaced.
+        assert_eq!(
+            profile.agg_func_cost(&AggregateFunction::Median),
+            profile.default_agg_func
+        );
+    }
+
+    #[test]
+    fn test_deserialize_toml_profile() {
+        let profile = CostProfile::from_toml_str(
+            r#"
+            random_page_io_cost = 1.5
+            hash_build_cost = 3.0
+
+            [agg_funcs]
+            MEDIAN = { per_row = 8.0, per_group = 2.0 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            profile,
+            CostProfile {
+                random_page_io_cost: 1.5,
+                hash_build_cost: 3.0,
+                agg_funcs: [("MEDIAN".to_string(), AggFuncCost::new(8.0, 2.0))].into(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_profile_round_trip() {
+        let profile = CostProfile {
+            cpu_tuple_cost: 0.01,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&profile).unwrap();
+        assert_eq!(CostProfile::from_json_str(&json).unwrap(), profile);
+        let toml = toml::to_string(&profile).unwrap();
+        assert_eq!(CostProfile::from_toml_str(&toml).unwrap(), profile);
+    }
+
+    #[test]
+    fn test_invalid_profile() {
+        assert!(matches!(
+            CostProfile::from_json_str(r#"{"cpu_tuple_cost": "cheap"}"#),
+            Err(CostModelError::InvalidCostProfile(_))
+        ));
+        assert!(matches!(
+            CostProfile::from_toml_str("cpu_tuple_cost = "),
+            Err(CostModelError::InvalidCostProfile(_))
+        ));
+        assert!(matches!(
+            CostProfile::from_file("cost_profile.yaml"),
+            Err(CostModelError::InvalidCostProfile(_))
+        ));
+    }
+
+    #[test]
+    fn test_profile_from_file() {
+        let path = std::env::temp_dir().join("optd_test_profile_from_file.toml");
+        std::fs::write(&path, "seq_page_io_cost = 2.0\n").unwrap();
+        let profile = CostProfile::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(profile.seq_page_io_cost, 2.0);
+    }
+}
@@ -1,6 +1,7 @@
 use common::{
-    nodes::{ArcPredicateNode, PhysicalNodeType},
-    types::{AttrId, EpochId, ExprId, GroupId, TableId},
+    nodes::{ArcPredicateNode, JoinType, PhysicalNodeType},
+    predicates::sort_order_pred::SortOrderType,
+    types::{AttrId, EpochId, ExprId, GroupId, IndexId, TableId},
 };
 use optd_persistent::{
     cost_model::interface::{Stat, StatType},
@@ -10,23 +11,48 @@ use optd_persistent::{
 pub mod common;
 pub mod cost;
 pub mod cost_model;
+pub mod cost_profile;
 pub mod memo_ext;
 pub mod stats;
 pub mod storage;
 pub mod test_utils;
 pub mod utils;
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum StatValue {
     Int(i64),
     Float(f64),
     String(String),
 }
 
+/// Converts a statistic value stored as JSON into a [`StatValue`].
+///
+/// Integral numbers become [`StatValue::Int`], other numbers become [`StatValue::Float`], and
+/// strings are kept as is. Structured statistics (e.g. most common values or distributions) are
+/// kept in their serialized JSON form as [`StatValue::String`].
+impl From<serde_json::Value> for StatValue {
+    fn from(value: serde_json::Value) -> StatValue {
+        match value {
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => StatValue::Int(i),
+                None => StatValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(s) => StatValue::String(s),
+            serde_json::Value::Bool(b) => StatValue::Int(b as i64),
+            other => StatValue::String(other.to_string()),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash)]
 pub struct ComputeCostContext {
     pub group_id: GroupId,
     pub expr_id: ExprId,
     pub children_group_ids: Vec<GroupId>,
+    /// The order each child delivers its rows in, as the indices of the attributes of the child
+    /// the rows are sorted on and their directions, from the most significant one. Missing or
+    /// empty if the rows of a child are in no known order.
+    pub children_sort_orders: Vec<Vec<(u64, SortOrderType)>>,
 }
 
 #[derive(Default, Clone, Debug, PartialOrd, PartialEq)]
@@ -80,12 +106,26 @@ pub type CostModelResult<T> = Result<T, CostModelError>;
 
 #[derive(Debug)]
 pub enum SemanticError {
-    // TODO: Add more error types
     UnknownStatisticType,
     VersionedStatisticNotFound,
     AttributeNotFound(TableId, u64), // (table_id, attribute_base_index)
-    // FIXME: not sure if this should be put here
+    IndexNotFound(IndexId),
+    /// A predicate tree that is malformed, e.g. a node with missing children or data.
     InvalidPredicate(String),
+    /// A well-formed predicate that cannot be estimated, e.g. a numerical expression used as a
+    /// filter.
+    UnsupportedPredicate(String),
+    /// A value or an attribute that does not have the type an operation expects.
+    TypeMismatch(String),
+    UnsupportedDataType(String),
+    UnsupportedJoinType(JoinType),
+    ColumnCountMismatch(usize, usize), // (expected, found)
+    /// A configuration of the statistics that cannot be used, e.g. an empty sample.
+    InvalidStatsConfig(String),
+    /// Statistics that cannot be combined, e.g. sketches of different precisions.
+    IncompatibleStats(String),
+    /// A statistic that breaks the invariants of its type, e.g. unsorted histogram bounds.
+    InvalidStatistic(String),
 }
 
 #[derive(Debug)]
@@ -93,6 +133,11 @@ pub enum CostModelError {
     ORMError(BackendError),
     SemanticError(SemanticError),
     SerdeError(serde_json::Error),
+    /// A statistic read from the storage that cannot be deserialized, with the name of the
+    /// statistic.
+    CorruptStatistic(String, serde_json::Error),
+    /// A cost profile that cannot be loaded.
+    InvalidCostProfile(String),
 }
 
 impl From<BackendError> for CostModelError {
@@ -0,0 +1,767 @@
+//! An ANALYZE-style builder that computes [`AttributeCombValueStats`] from Arrow record batches.
+//!
+//! The builder sees every row of a table exactly once, batch by batch, and keeps a reservoir
+//! sample of a bounded number of rows in memory. The number of distinct values is tracked with a HyperLogLog
+//! over all the rows instead, so that it does not have to be extrapolated from the sample. When
+//! all the batches have been fed, the statistics of every single attribute and of every requested
+//! attribute combination are computed, and emitted as [`Stat`] records that can be written under one new epoch with
+//! [`CostModelStorageManager::update_statistics`](crate::storage::CostModelStorageManager::update_statistics).
+
+use std::collections::HashMap;
+
+use datafusion::{arrow::record_batch::RecordBatch, scalar::ScalarValue};
+use itertools::Itertools;
+use optd_persistent::cost_model::interface::{Stat, StatType};
+use ordered_float::OrderedFloat;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::json;
+
+use crate::{
+    common::{
+        types::{AttrId, TableId},
+        values::{SerializableOrderedF64, Value},
+    },
+    CostModelResult, SemanticError,
+};
+
+use super::{
+    utilities::{
+        counter::Counter,
+        histogram::{EquiDepthHistogram, DEFAULT_NUM_BUCKETS},
+        hyperloglog::{self, HyperLogLog},
+        tdigest::{TDigest, DEFAULT_COMPRESSION},
+    },
+    AttributeCombValue, AttributeCombValueStats, Distribution, MostCommonValues,
+};
+
+/// The default number of most common values kept for each attribute combination.
+pub const DEFAULT_NUM_MCVS: usize = 100;
+
+/// The default number of sampled rows, which is what PostgreSQL samples with its default
+/// statistics target.
+pub const DEFAULT_SAMPLE_SIZE: usize = 30_000;
+
+/// The kind of [`Distribution`] built for single attributes.
+#[derive(Clone, Copy, Debug)]
+pub enum DistributionKind {
+    TDigest { compression: f64 },
+    EquiDepthHistogram { num_buckets: usize },
+}
+
+impl Default for DistributionKind {
+    fn default() -> Self {
+        DistributionKind::TDigest {
+            compression: DEFAULT_COMPRESSION,
+        }
+    }
+}
+
+impl DistributionKind {
+    pub fn equi_depth_histogram() -> Self {
+        DistributionKind::EquiDepthHistogram {
+            num_buckets: DEFAULT_NUM_BUCKETS,
+        }
+    }
+}
+
+/// How a [`StatsBuilder`] samples rows and which statistics it computes.
+#[derive(Clone, Debug)]
+pub struct StatsBuilderConfig {
+    /// The maximum number of rows that are sampled. Tables with fewer rows are sampled entirely.
+    pub sample_size: usize,
+    /// The seed of the sampler, so that the statistics are reproducible.
+    pub seed: u64,
+    /// The maximum number of most common values kept for each attribute combination.
+    pub num_mcvs: usize,
+    /// The kind of distribution built for single attributes.
+    pub distribution: DistributionKind,
+    /// The precision of the HyperLogLog sketches that estimate the number of distinct values.
+    pub hll_precision: u8,
+    /// The multi-attribute combinations to compute statistics for, as column indices in the
+    /// record batches. Statistics of every single attribute are always computed.
+    pub attr_combs: Vec<Vec<usize>>,
+}
+
+impl Default for StatsBuilderConfig {
+    fn default() -> Self {
+        Self {
+            sample_size: DEFAULT_SAMPLE_SIZE,
+            seed: 0,
+            num_mcvs: DEFAULT_NUM_MCVS,
+            distribution: DistributionKind::default(),
+            hll_precision: hyperloglog::DEFAULT_PRECISION,
+            attr_combs: vec![],
+        }
+    }
+}
+
+/// The statistics of one attribute combination, as computed by [`StatsBuilder`].
+pub struct AttributeCombStats {
+    /// The column indices of the attributes in the record batches.
+    pub attr_indices: Vec<usize>,
+    pub stats: AttributeCombValueStats,
+    /// The estimated number of rows where at least one of the attributes is not null.
+    pub non_null_cnt: u64,
+    /// The sketch that `stats.ndistinct` is estimated from.
+    pub distinct_sketch: HyperLogLog<AttributeCombValue>,
+    /// The average width in bytes of the non-null sampled values, for single attributes only.
+    pub avg_width: Option<f64>,
+}
+
+/// Computes the statistics of one table from the record batches of its rows.
+pub struct StatsBuilder {
+    table_id: TableId,
+    /// The catalog ids of the attributes, in the order of the columns of the record batches.
+    attr_ids: Vec<AttrId>,
+    config: StatsBuilderConfig,
+    rng: StdRng,
+    /// The number of rows that have been fed, sampled or not.
+    row_cnt: u64,
+    /// A uniform sample of at most `config.sample_size` of the rows that have been fed.
+    sampled_rows: Vec<Vec<Option<Value>>>,
+    /// Every single attribute, followed by the configured attribute combinations.
+    attr_combs: Vec<Vec<usize>>,
+    /// The distinct values of every attribute combination, over all the rows.
+    distinct_sketches: Vec<HyperLogLog<AttributeCombValue>>,
+}
+
+impl StatsBuilder {
+    pub fn new(
+        table_id: TableId,
+        attr_ids: Vec<AttrId>,
+        config: StatsBuilderConfig,
+    ) -> CostModelResult<Self> {
+        if config.sample_size == 0 {
+            return Err(SemanticError::InvalidStatsConfig(
+                "the sample size should be positive".to_string(),
+            )
+            .into());
+        }
+        if let Some(attr_comb) = config.attr_combs.iter().find(|attr_comb| {
+            attr_comb.is_empty() || attr_comb.iter().any(|&idx| idx >= attr_ids.len())
+        }) {
+            return Err(SemanticError::InvalidStatsConfig(format!(
+                "the attribute combination {:?} is not a set of columns among {}",
+                attr_comb,
+                attr_ids.len()
+            ))
+            .into());
+        }
+        let rng = StdRng::seed_from_u64(config.seed);
+        let attr_combs = (0..attr_ids.len())
+            .map(|idx| vec![idx])
+            .chain(config.attr_combs.iter().cloned())
+            .collect_vec();
+        let distinct_sketches = attr_combs
+            .iter()
+            .map(|_| HyperLogLog::new(config.hll_precision))
+            .collect::<CostModelResult<_>>()?;
+        Ok(Self {
+            table_id,
+            attr_ids,
+            config,
+            rng,
+            row_cnt: 0,
+            sampled_rows: vec![],
+            attr_combs,
+            distinct_sketches,
+        })
+    }
+
+    /// Feeds a batch of rows of the table. The columns of the batch should match the attribute
+    /// ids the builder was created with.
+    pub fn update(&mut self, batch: &RecordBatch) -> CostModelResult<()> {
+        if batch.num_columns() != self.attr_ids.len() {
+            return Err(SemanticError::ColumnCountMismatch(
+                self.attr_ids.len(),
+                batch.num_columns(),
+            )
+            .into());
+        }
+        for row_idx in 0..batch.num_rows() {
+            self.row_cnt += 1;
+            let row = batch
+                .columns()
+                .iter()
+                .map(|column| {
+                    ScalarValue::try_from_array(column, row_idx)
+                        .map_err(|e| SemanticError::UnsupportedDataType(e.to_string()))
+                        .and_then(scalar_to_value)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for (attr_indices, sketch) in self.attr_combs.iter().zip(&mut self.distinct_sketches) {
+                let value = attr_comb_value(&row, attr_indices);
+                if value.iter().any(|v| v.is_some()) {
+                    sketch.insert(&value);
+                }
+            }
+            self.sample(row);
+        }
+        Ok(())
+    }
+
+    /// Adds a row to the sample with reservoir sampling (Vitter's algorithm R), so that every row
+    /// fed so far is in the sample with the same probability.
+    fn sample(&mut self, row: Vec<Option<Value>>) {
+        if self.sampled_rows.len() < self.config.sample_size {
+            self.sampled_rows.push(row);
+            return;
+        }
+        let idx = self.rng.gen_range(0..self.row_cnt);
+        if idx < self.sampled_rows.len() as u64 {
+            self.sampled_rows[idx as usize] = row;
+        }
+    }
+
+    /// Merges the distinct values sketch of an attribute combination from a previous epoch, so
+    /// that the number of distinct values covers both the rows of that epoch and the rows fed
+    /// to this builder. The sketch is what [`StatsBuilder::finish`] emits as
+    /// [`StatType::DistinctSketch`]. Returns false if the builder does not compute statistics
+    /// for that combination, and an error if the sketch has a different precision.
+    pub fn merge_distinct_sketch(
+        &mut self,
+        attr_ids: &[AttrId],
+        sketch: &HyperLogLog<AttributeCombValue>,
+    ) -> CostModelResult<bool> {
+        let position = self.attr_combs.iter().position(|attr_indices| {
+            let comb_attr_ids = attr_indices.iter().map(|&idx| self.attr_ids[idx]);
+            comb_attr_ids.sorted().eq(attr_ids.iter().copied().sorted())
+        });
+        match position {
+            Some(position) => {
+                self.distinct_sketches[position].merge(sketch)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Computes the statistics of every single attribute, followed by the statistics of the
+    /// configured attribute combinations.
+    pub fn build(&self) -> CostModelResult<Vec<AttributeCombStats>> {
+        self.attr_combs
+            .iter()
+            .zip(&self.distinct_sketches)
+            .map(|(attr_indices, sketch)| self.build_attr_comb_stats(attr_indices, sketch))
+            .collect()
+    }
+
+    /// Computes all the statistics and turns them into [`Stat`] records: the row count of the
+    /// table, and for every attribute combination its MCVs, distribution and average width
+    /// (single attributes only), number of distinct values and the sketch it comes from, number
+    /// of non-null values and row count.
+    ///
+    /// The row count is also recorded per attribute combination, because that is what the
+    /// storage manager divides the non-null count by to get the null fraction.
+    pub fn finish(self) -> CostModelResult<Vec<Stat>> {
+        let mut stats = vec![Stat {
+            stat_type: StatType::TableRowCount,
+            stat_value: json!(self.row_cnt),
+            attr_ids: vec![],
+            table_id: Some(self.table_id.0 as i32),
+            name: format!("{:?}({})", StatType::TableRowCount, self.table_id),
+        }];
+        for attr_comb_stats in self.build()? {
+            let attr_ids = attr_comb_stats
+                .attr_indices
+                .iter()
+                .map(|&idx| self.attr_ids[idx].0 as i32)
+                .collect_vec();
+            let mut push = |stat_type: StatType, stat_value: serde_json::Value| {
+                stats.push(Stat {
+                    stat_type,
+                    stat_value,
+                    name: format!("{:?}({})", stat_type, attr_ids.iter().join(",")),
+                    attr_ids: attr_ids.clone(),
+                    table_id: None,
+                });
+            };
+            push(StatType::TableRowCount, json!(self.row_cnt));
+            push(StatType::NonNullCount, json!(attr_comb_stats.non_null_cnt));
+            push(
+                StatType::Cardinality,
+                json!(attr_comb_stats.stats.ndistinct),
+            );
+            push(
+                StatType::MostCommonValues,
+                serde_json::to_value(&attr_comb_stats.stats.mcvs)?,
+            );
+            if let Some(distr) = &attr_comb_stats.stats.distr {
+                push(StatType::Distribution, serde_json::to_value(distr)?);
+            }
+            if let Some(avg_width) = attr_comb_stats.avg_width {
+                push(StatType::AvgWidth, json!(avg_width));
+            }
+            push(
+                StatType::DistinctSketch,
+                serde_json::to_value(&attr_comb_stats.distinct_sketch)?,
+            );
+        }
+        Ok(stats)
+    }
+
+    fn build_attr_comb_stats(
+        &self,
+        attr_indices: &[usize],
+        distinct_sketch: &HyperLogLog<AttributeCombValue>,
+    ) -> CostModelResult<AttributeCombStats> {
+        let values = self
+            .sampled_rows
+            .iter()
+            .map(|row| attr_comb_value(row, attr_indices))
+            .collect_vec();
+        let non_null_values = values
+            .iter()
+            .filter(|value| value.iter().any(|v| v.is_some()))
+            .collect_vec();
+
+        let sample_cnt = values.len();
+        let null_frac = if sample_cnt == 0 {
+            0.0
+        } else {
+            1.0 - non_null_values.len() as f64 / sample_cnt as f64
+        };
+        let non_null_cnt = ((1.0 - null_frac) * self.row_cnt as f64).round() as u64;
+
+        let mut value_cnts: HashMap<&AttributeCombValue, usize> = HashMap::new();
+        for value in &non_null_values {
+            *value_cnts.entry(value).or_default() += 1;
+        }
+        let ndistinct = distinct_sketch.n_distinct();
+
+        // If every distinct value fits, they are all MCVs. Otherwise, values that appear only
+        // once in the sample carry no information about their frequency.
+        let mcv_values = value_cnts
+            .iter()
+            .filter(|(_, &cnt)| value_cnts.len() <= self.config.num_mcvs || cnt > 1)
+            .sorted_by(|(v1, c1), (v2, c2)| c2.cmp(c1).then_with(|| v1.cmp(v2)))
+            .take(self.config.num_mcvs)
+            .map(|(value, _)| (*value).clone())
+            .collect_vec();
+        let mut counter = Counter::new(&mcv_values);
+        // Non-MCV and null values are not tracked, but they count towards the total, so that
+        // the frequencies are relative to all the rows.
+        counter.aggregate(&values);
+
+        let avg_width = (attr_indices.len() == 1 && !non_null_values.is_empty()).then(|| {
+            let total_width: usize = non_null_values
+                .iter()
+                .map(|value| value[0].as_ref().unwrap().width())
+                .sum();
+            total_width as f64 / non_null_values.len() as f64
+        });
+
+        let distr = if attr_indices.len() == 1 {
+            let distr_values = non_null_values
+                .iter()
+                .filter(|value| !counter.is_tracking(value))
+                .map(|value| value[0].clone().unwrap())
+                .collect_vec();
+            Some(self.build_distribution(&distr_values, sample_cnt)?)
+        } else {
+            None
+        };
+
+        Ok(AttributeCombStats {
+            attr_indices: attr_indices.to_vec(),
+            stats: AttributeCombValueStats::new(
+                MostCommonValues::Counter(counter),
+                distr,
+                ndistinct,
+                null_frac,
+            ),
+            non_null_cnt,
+            distinct_sketch: distinct_sketch.clone(),
+            avg_width,
+        })
+    }
+
+    /// Builds a distribution over the values that are not MCVs, normalized so that
+    /// [`Distribution::cdf`] is relative to all the sampled rows.
+    fn build_distribution(
+        &self,
+        values: &[Value],
+        sample_cnt: usize,
+    ) -> CostModelResult<Distribution> {
+        Ok(match self.config.distribution {
+            DistributionKind::TDigest { compression } => {
+                // A TDigest needs a numeric representation of the values.
+                let values = values
+                    .iter()
+                    .filter(|v| has_float_repr(v))
+                    .cloned()
+                    .collect_vec();
+                let mut tdigest = TDigest::new(compression);
+                tdigest.merge_values(&values);
+                tdigest.norm_freq = freq(values.len(), sample_cnt);
+                Distribution::TDigest(tdigest)
+            }
+            DistributionKind::EquiDepthHistogram { num_buckets } => {
+                Distribution::EquiDepthHistogram(EquiDepthHistogram::from_values(
+                    values,
+                    num_buckets,
+                    freq(values.len(), sample_cnt),
+                )?)
+            }
+        })
+    }
+}
+
+fn attr_comb_value(row: &[Option<Value>], attr_indices: &[usize]) -> AttributeCombValue {
+    attr_indices.iter().map(|&idx| row[idx].clone()).collect()
+}
+
+fn freq(cnt: usize, total_cnt: usize) -> f64 {
+    if total_cnt == 0 {
+        0.0
+    } else {
+        cnt as f64 / total_cnt as f64
+    }
+}
+
+fn has_float_repr(value: &Value) -> bool {
+    !matches!(
+        value,
+        Value::Decimal128(_) | Value::Int128(_) | Value::Serialized(_)
+    )
+}
+
+/// Converts an Arrow scalar into a [`Value`], or `None` if it is null.
+fn scalar_to_value(scalar: ScalarValue) -> Result<Option<Value>, SemanticError> {
+    if scalar.is_null() {
+        return Ok(None);
+    }
+    let value = match scalar {
+        ScalarValue::Boolean(Some(v)) => Value::Bool(v),
+        ScalarValue::Int8(Some(v)) => Value::Int8(v),
+        ScalarValue::Int16(Some(v)) => Value::Int16(v),
+        ScalarValue::Int32(Some(v)) => Value::Int32(v),
+        ScalarValue::Int64(Some(v)) => Value::Int64(v),
+        ScalarValue::UInt8(Some(v)) => Value::UInt8(v),
+        ScalarValue::UInt16(Some(v)) => Value::UInt16(v),
+        ScalarValue::UInt32(Some(v)) => Value::UInt32(v),
+        ScalarValue::UInt64(Some(v)) => Value::UInt64(v),
+        ScalarValue::Float32(Some(v)) => {
+            Value::Float(SerializableOrderedF64(OrderedFloat(v as f64)))
+        }
+        ScalarValue::Float64(Some(v)) => Value::Float(SerializableOrderedF64(OrderedFloat(v))),
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => Value::String(v.into()),
+        ScalarValue::Date32(Some(v)) => Value::Date32(v),
+        ScalarValue::Decimal128(Some(v), _, _) => Value::Decimal128(v),
+        ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => {
+            Value::Serialized(v.into())
+        }
+        other => {
+            return Err(SemanticError::UnsupportedDataType(format!(
+                "cannot collect statistics for values of type {}",
+                other.data_type()
+            )))
+        }
+    };
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::arrow::{
+        array::{ArrayRef, Int32Array, StringArray},
+        datatypes::{DataType, Field, Schema},
+        record_batch::RecordBatch,
+    };
+    use optd_persistent::cost_model::interface::StatType;
+
+    use crate::{
+        common::{
+            types::{AttrId, TableId},
+            values::Value,
+        },
+        stats::{
+            utilities::hyperloglog::HyperLogLog, AttributeCombValue, Distribution, MostCommonValues,
+        },
+        storage::CostModelStorageManager,
+        test_utils::tests::{create_persistent_cost_model, remove_db_file},
+        CostModelError, SemanticError,
+    };
+
+    use super::{DistributionKind, StatsBuilder, StatsBuilderConfig};
+
+    /// Two batches of an (int, string) table with 100 rows. The int column has 10 values that
+    /// each appear 10 times, and is null every 5th row. The string column is "a" for 90 rows
+    /// and "b" for 10.
+    fn batches() -> Vec<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        (0..2)
+            .map(|batch_idx| {
+                let ids: Vec<Option<i32>> = (0..50)
+                    .map(|i| batch_idx * 50 + i)
+                    .map(|i| if i % 5 == 0 { None } else { Some(i % 10) })
+                    .collect();
+                let names: Vec<&str> = (0..50)
+                    .map(|i| if batch_idx == 1 && i >= 40 { "b" } else { "a" })
+                    .collect();
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int32Array::from(ids)) as ArrayRef,
+                        Arc::new(StringArray::from(names)) as ArrayRef,
+                    ],
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn build(config: StatsBuilderConfig) -> StatsBuilder {
+        let mut builder =
+            StatsBuilder::new(TableId(1), vec![AttrId(1), AttrId(2)], config).unwrap();
+        for batch in batches() {
+            builder.update(&batch).unwrap();
+        }
+        builder
+    }
+
+    #[test]
+    fn test_single_attribute_stats() {
+        let builder = build(StatsBuilderConfig::default());
+        let stats = builder.build().unwrap();
+        assert_eq!(stats.len(), 2);
+
+        // Every 5th row is null, so only 8 of the 10 values are left.
+        let id_stats = &stats[0];
+        assert_eq!(id_stats.attr_indices, vec![0]);
+        assert_eq!(id_stats.non_null_cnt, 80);
+        assert_eq!(id_stats.stats.ndistinct, 8);
+        assert_approx_eq::assert_approx_eq!(id_stats.stats.null_frac, 0.2);
+        assert_eq!(id_stats.stats.mcvs.cnt(), 8);
+        assert_eq!(
+            id_stats.stats.mcvs.freq(&vec![Some(Value::Int32(1))]),
+            Some(0.1)
+        );
+        assert_approx_eq::assert_approx_eq!(id_stats.stats.mcvs.total_freq(), 0.8);
+        assert_eq!(id_stats.avg_width, Some(4.0));
+
+        let name_stats = &stats[1];
+        assert_eq!(name_stats.stats.ndistinct, 2);
+        assert_eq!(name_stats.stats.null_frac, 0.0);
+        assert_eq!(
+            name_stats
+                .stats
+                .mcvs
+                .freq(&vec![Some(Value::String("b".into()))]),
+            Some(0.1)
+        );
+        assert_eq!(name_stats.avg_width, Some(1.0));
+    }
+
+    #[test]
+    fn test_mcv_limit_and_distribution() {
+        let builder = build(StatsBuilderConfig {
+            num_mcvs: 2,
+            ..Default::default()
+        });
+        let id_stats = &builder.build().unwrap()[0];
+        assert_eq!(id_stats.stats.mcvs.cnt(), 2);
+        assert_approx_eq::assert_approx_eq!(id_stats.stats.mcvs.total_freq(), 0.2);
+
+        // The distribution holds the other 6 values, i.e. 60% of the rows.
+        let distr = id_stats.stats.distr.as_ref().unwrap();
+        assert_approx_eq::assert_approx_eq!(distr.cdf(&Value::Int32(100)), 0.6, 0.05);
+        assert!(distr.cdf(&Value::Int32(-1)) < distr.cdf(&Value::Int32(5)));
+    }
+
+    #[test]
+    fn test_equi_depth_histogram() {
+        let builder = build(StatsBuilderConfig {
+            num_mcvs: 2,
+            distribution: DistributionKind::EquiDepthHistogram { num_buckets: 5 },
+            ..Default::default()
+        });
+        let id_stats = &builder.build().unwrap()[0];
+        let distr = id_stats.stats.distr.as_ref().unwrap();
+        assert!(matches!(distr, Distribution::EquiDepthHistogram(_)));
+        assert_approx_eq::assert_approx_eq!(distr.cdf(&Value::Int32(100)), 0.6);
+        assert_approx_eq::assert_approx_eq!(distr.cdf(&Value::Int32(-1)), 0.0);
+    }
+
+    #[test]
+    fn test_multi_attribute_stats() {
+        let builder = build(StatsBuilderConfig {
+            attr_combs: vec![vec![0, 1]],
+            ..Default::default()
+        });
+        let stats = builder.build().unwrap();
+        let comb_stats = &stats[2];
+        assert_eq!(comb_stats.attr_indices, vec![0, 1]);
+        // The rows where the id is null still have a name, so no row is fully null.
+        assert_eq!(comb_stats.stats.null_frac, 0.0);
+        // The 8 ids appear with "a", the "b" rows cover ids 1-9, and the nulls add 2 more.
+        assert_eq!(comb_stats.stats.ndistinct, 8 + 8 + 2);
+        assert!(comb_stats.stats.distr.is_none());
+    }
+
+    #[test]
+    fn test_sampling() {
+        let builder = build(StatsBuilderConfig {
+            sample_size: 50,
+            seed: 42,
+            ..Default::default()
+        });
+        // The sample is bounded, and mixes the rows of both batches.
+        assert_eq!(builder.sampled_rows.len(), 50);
+        assert!(builder
+            .sampled_rows
+            .iter()
+            .any(|row| row[1] == Some(Value::String("b".into()))));
+        let stats = builder.build().unwrap();
+        // The row count is exact, the rest is estimated from half of the rows.
+        assert!(stats[0].non_null_cnt > 50 && stats[0].non_null_cnt < 100);
+        assert_eq!(stats[0].stats.ndistinct, 8);
+    }
+
+    #[test]
+    fn test_invalid_input_is_error() {
+        let new_builder =
+            |config| StatsBuilder::new(TableId(1), vec![AttrId(1), AttrId(2)], config);
+        assert!(matches!(
+            new_builder(StatsBuilderConfig {
+                sample_size: 0,
+                ..Default::default()
+            }),
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidStatsConfig(_)
+            ))
+        ));
+        assert!(matches!(
+            new_builder(StatsBuilderConfig {
+                attr_combs: vec![vec![0, 2]],
+                ..Default::default()
+            }),
+            Err(CostModelError::SemanticError(
+                SemanticError::InvalidStatsConfig(_)
+            ))
+        ));
+
+        let mut builder = new_builder(StatsBuilderConfig::default()).unwrap();
+        assert!(matches!(
+            builder.update(&int_batch(0..10)),
+            Err(CostModelError::SemanticError(
+                SemanticError::ColumnCountMismatch(2, 1)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_stats_under_one_epoch() {
+        const DATABASE_FILE: &str = "test_write_stats_under_one_epoch.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        let storage_manager = &cost_model.storage_manager;
+
+        let stats = build(StatsBuilderConfig {
+            attr_combs: vec![vec![0, 1]],
+            ..Default::default()
+        })
+        .finish()
+        .unwrap();
+        assert!(stats
+            .iter()
+            .any(|stat| stat.stat_type == StatType::TableRowCount && stat.table_id == Some(1)));
+        let epoch_id = storage_manager
+            .update_statistics(stats, "analyze".to_string(), "table1".to_string())
+            .await
+            .unwrap();
+        assert!(epoch_id.is_some());
+
+        assert_eq!(
+            storage_manager
+                .get_table_row_count(TableId(1))
+                .await
+                .unwrap(),
+            Some(100)
+        );
+        // The attributes with base indices 0 and 1 of table 1 are attributes 1 and 2.
+        let id_stats = storage_manager
+            .get_attributes_comb_statistics(TableId(1), &[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(id_stats.ndistinct, 8);
+        assert_approx_eq::assert_approx_eq!(id_stats.null_frac, 0.2);
+        assert!(matches!(id_stats.mcvs, MostCommonValues::Counter(_)));
+        assert_eq!(id_stats.mcvs.freq(&vec![Some(Value::Int32(3))]), Some(0.1));
+        assert!(id_stats.distr.is_some());
+        assert_eq!(
+            storage_manager
+                .get_attribute_avg_width(TableId(1), 1)
+                .await
+                .unwrap(),
+            Some(1.0)
+        );
+
+        let comb_stats = storage_manager
+            .get_attributes_comb_statistics(TableId(1), &[0, 1])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(comb_stats.ndistinct, 18);
+
+        remove_db_file(DATABASE_FILE);
+    }
+
+    fn int_batch(values: std::ops::Range<i32>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, true)]));
+        let array = Int32Array::from(values.collect::<Vec<_>>());
+        RecordBatch::try_new(schema, vec![Arc::new(array) as ArrayRef]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_merge_distinct_sketch_across_epochs() {
+        const DATABASE_FILE: &str = "test_merge_distinct_sketch_across_epochs.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        let storage_manager = &cost_model.storage_manager;
+
+        let mut builder =
+            StatsBuilder::new(TableId(1), vec![AttrId(1)], StatsBuilderConfig::default()).unwrap();
+        builder.update(&int_batch(0..50)).unwrap();
+        storage_manager
+            .update_statistics(builder.finish().unwrap(), "analyze".into(), "0..50".into())
+            .await
+            .unwrap();
+
+        // The second epoch only sees the new rows, half of which were already in the table.
+        let sketch: HyperLogLog<AttributeCombValue> = serde_json::from_value(
+            storage_manager
+                .get_attribute_statistic(vec![AttrId(1)], StatType::DistinctSketch, None)
+                .await
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        let mut builder =
+            StatsBuilder::new(TableId(1), vec![AttrId(1)], StatsBuilderConfig::default()).unwrap();
+        builder.update(&int_batch(25..75)).unwrap();
+        assert!(builder
+            .merge_distinct_sketch(&[AttrId(1)], &sketch)
+            .unwrap());
+        assert!(!builder
+            .merge_distinct_sketch(&[AttrId(2)], &sketch)
+            .unwrap());
+        storage_manager
+            .update_statistics(builder.finish().unwrap(), "analyze".into(), "25..75".into())
+            .await
+            .unwrap();
+
+        let stats = storage_manager
+            .get_attributes_comb_statistics(TableId(1), &[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stats.ndistinct, 75);
+
+        remove_db_file(DATABASE_FILE);
+    }
+}
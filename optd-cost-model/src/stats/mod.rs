@@ -1,18 +1,23 @@
 #![allow(unused)]
 
 mod arith_encoder;
+pub mod builder;
 pub mod utilities;
 
 use crate::common::values::Value;
 use serde::{Deserialize, Serialize};
 use utilities::counter::Counter;
 use utilities::{
+    histogram::EquiDepthHistogram,
     simple_map::{self, SimpleMap},
     tdigest::TDigest,
 };
 
 // Default n-distinct estimate for derived columns or columns lacking statistics
 pub const DEFAULT_NUM_DISTINCT: u64 = 200;
+// Default average width in bytes for variable-width attributes lacking statistics, such as strings
+// See `get_typavgwidth` in Postgres.
+pub const DEFAULT_VARLEN_WIDTH: f64 = 32.0;
 // A placeholder for unimplemented!() for codepaths which are accessed by plannertest
 pub const UNIMPLEMENTED_SEL: f64 = 0.01;
 // Default statistics. All are from selfuncs.h in Postgres unless specified otherwise
@@ -20,6 +25,12 @@ pub const UNIMPLEMENTED_SEL: f64 = 0.01;
 pub const DEFAULT_EQ_SEL: f64 = 0.005;
 // Default selectivity estimate for inequalities such as "A < b"
 pub const DEFAULT_INEQ_SEL: f64 = 0.3333333333333333;
+// Default selectivity estimate for boolean attributes lacking statistics, such as "WHERE A"
+// See `boolvarsel` in Postgres.
+pub const DEFAULT_BOOL_SEL: f64 = 0.5;
+// Default selectivity estimate for boolean functions, such as "WHERE f(A)"
+// See `function_selectivity` in Postgres.
+pub const DEFAULT_FUNC_SEL: f64 = 0.3333333333333333;
 // Used for estimating pattern selectivity character-by-character. These numbers
 // are not used on their own. Depending on the characters in the pattern, the
 // selectivity is multiplied by these factors.
@@ -27,6 +38,12 @@ pub const DEFAULT_INEQ_SEL: f64 = 0.3333333333333333;
 // See `FULL_WILDCARD_SEL` and `FIXED_CHAR_SEL` in Postgres.
 pub const FULL_WILDCARD_SEL_FACTOR: f64 = 5.0;
 pub const FIXED_CHAR_SEL_FACTOR: f64 = 0.2;
+// The most attributes in a combination whose statistics are looked up when estimating several
+// attributes of a table together. Postgres' extended statistics cover at most 8 attributes.
+pub const MAX_ATTR_COMB_LEN: usize = 4;
+// The most multi-attribute combinations whose statistics are looked up per table when estimating
+// several attributes together, since the number of combinations grows exponentially.
+pub const MAX_ATTR_COMB_LOOKUPS: usize = 64;
 
 pub type AttributeCombValue = Vec<Option<Value>>;
 
@@ -95,23 +112,20 @@ impl MostCommonValues {
 pub enum Distribution {
     TDigest(TDigest<Value>),
     SimpleDistribution(SimpleMap<Value>),
+    EquiDepthHistogram(EquiDepthHistogram),
     // Add more types here...
 }
 
 impl Distribution {
+    /// The fraction of all the rows (including nulls and MCVs) whose value is in the
+    /// distribution and less than or equal to `value`.
     pub fn cdf(&self, value: &Value) -> f64 {
         match self {
-            Distribution::TDigest(tdigest) => {
-                let nb_rows = tdigest.norm_weight;
-                if nb_rows == 0 {
-                    tdigest.cdf(value)
-                } else {
-                    tdigest.centroids.len() as f64 * tdigest.cdf(value) / nb_rows as f64
-                }
-            }
+            Distribution::TDigest(tdigest) => tdigest.norm_cdf(value),
             Distribution::SimpleDistribution(simple_distribution) => {
                 *simple_distribution.m.get(value).unwrap_or(&0.0)
             }
+            Distribution::EquiDepthHistogram(histogram) => histogram.cdf(value),
         }
     }
 
@@ -120,19 +134,17 @@ impl Distribution {
     }
 }
 
-// TODO: Remove the clone. Now I have to add this because
-// persistent.rs doesn't have a memory cache, so we have to
-// return AttributeCombValueStats rather than &AttributeCombValueStats.
-// But this poses a problem for mock.rs when testing, since mock storage
-// only has memory hash map, so we need to return a clone of AttributeCombValueStats.
-// Later, if memory cache is added, we should change this to return a reference.
-// **and** remove the clone.
+// TODO: Remove the clone. The storage managers hand out `Arc<AttributeCombValueStats>`,
+// and persistent.rs shares them through its statistics cache, but mock.rs still clones
+// the stats it was created with into a new `Arc` on every lookup.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AttributeCombValueStats {
     pub mcvs: MostCommonValues,      // Does NOT contain full nulls.
     pub distr: Option<Distribution>, // Does NOT contain mcvs; optional.
     pub ndistinct: u64,              // Does NOT contain full nulls.
     pub null_frac: f64,              // % of full nulls.
+    #[serde(default)]
+    pub avg_width: Option<f64>, // Of the non-nulls, in bytes; only for single attributes.
 }
 
 impl AttributeCombValueStats {
@@ -147,6 +159,7 @@ impl AttributeCombValueStats {
             ndistinct,
             null_frac,
             distr,
+            avg_width: None,
         }
     }
 }
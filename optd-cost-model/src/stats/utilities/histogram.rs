@@ -0,0 +1,282 @@
+//! Implementation of a Postgres-style equi-depth histogram: the values are split into buckets
+//! that each hold the same number of values, and only the bounds of the buckets are kept.
+//! For more details, refer to `compute_scalar_stats` and `ineq_histogram_selectivity` in
+//! Postgres.
+
+use std::ops::Bound;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{common::values::Value, CostModelResult, SemanticError};
+
+use super::tdigest::IntoFloat;
+
+pub const DEFAULT_NUM_BUCKETS: usize = 100;
+
+/// The equi-depth histogram of the values of an attribute.
+///
+/// Deserialization goes through [`EquiDepthHistogram::new`], so a stored histogram that breaks
+/// the invariants is rejected.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(try_from = "SerializedEquiDepthHistogram")]
+pub struct EquiDepthHistogram {
+    /// The bounds of the buckets, in increasing order. Bucket `i` covers the values between
+    /// `bounds[i]` and `bounds[i + 1]`, so there is one more bound than there are buckets.
+    bounds: Vec<Value>,
+    /// The fraction of all the rows of the attribute that the histogram stands for, e.g. the
+    /// non-null values that are not MCVs.
+    freq: f64,
+}
+
+/// The serialized form of an [`EquiDepthHistogram`], before its invariants are checked.
+#[derive(Deserialize)]
+struct SerializedEquiDepthHistogram {
+    bounds: Vec<Value>,
+    freq: f64,
+}
+
+impl TryFrom<SerializedEquiDepthHistogram> for EquiDepthHistogram {
+    type Error = String;
+
+    fn try_from(histogram: SerializedEquiDepthHistogram) -> Result<Self, Self::Error> {
+        check_invariants(&histogram.bounds, histogram.freq)?;
+        Ok(Self {
+            bounds: histogram.bounds,
+            freq: histogram.freq,
+        })
+    }
+}
+
+impl EquiDepthHistogram {
+    /// Creates a histogram from the bucket bounds, which should be sorted, and the fraction of
+    /// the rows it stands for, which should be in [0, 1].
+    pub fn new(bounds: Vec<Value>, freq: f64) -> CostModelResult<Self> {
+        check_invariants(&bounds, freq).map_err(SemanticError::InvalidStatistic)?;
+        Ok(Self { bounds, freq })
+    }
+
+    /// Builds a histogram with at most `num_buckets` buckets over `values`, which stand for
+    /// `freq` of all the rows.
+    pub fn from_values(values: &[Value], num_buckets: usize, freq: f64) -> CostModelResult<Self> {
+        if num_buckets == 0 {
+            return Err(SemanticError::InvalidStatsConfig(
+                "a histogram should have at least one bucket".to_string(),
+            )
+            .into());
+        }
+        if values.is_empty() {
+            return Self::new(vec![], freq);
+        }
+        let sorted = values.iter().sorted().collect_vec();
+        let num_buckets = num_buckets.min(sorted.len().saturating_sub(1)).max(1);
+        let bounds = (0..=num_buckets)
+            .map(|i| sorted[i * (sorted.len() - 1) / num_buckets].clone())
+            .collect();
+        Self::new(bounds, freq)
+    }
+
+    pub fn num_buckets(&self) -> usize {
+        self.bounds.len().saturating_sub(1)
+    }
+
+    /// The fraction of all the rows whose value is less than or equal to `value`.
+    ///
+    /// The values are assumed to be spread uniformly inside a bucket, so the position of `value`
+    /// is linearly interpolated between the bounds of its bucket. Values that cannot be
+    /// interpolated, like binary values, are assumed to be in the middle of their bucket.
+    pub fn cdf(&self, value: &Value) -> f64 {
+        let (Some(first), Some(last)) = (self.bounds.first(), self.bounds.last()) else {
+            return 0.0;
+        };
+        if value < first {
+            return 0.0;
+        }
+        if value >= last {
+            return self.freq;
+        }
+        // The last bucket whose lower bound is <= value. It is never the last bound, since
+        // `value < last`.
+        let bucket = self.bounds.partition_point(|bound| bound <= value) - 1;
+        let (low, high) = (&self.bounds[bucket], &self.bounds[bucket + 1]);
+        let fraction = interpolate(low, high, value).unwrap_or(0.5);
+        self.freq * (bucket as f64 + fraction) / self.num_buckets() as f64
+    }
+
+    /// The fraction of all the rows whose value is between `start` and `end`.
+    ///
+    /// The histogram does not know how often a single value appears, so whether the bounds are
+    /// included or not does not change the result.
+    pub fn range_freq(&self, start: Bound<&Value>, end: Bound<&Value>) -> f64 {
+        let start_freq = match start {
+            Bound::Unbounded => 0.0,
+            Bound::Included(value) | Bound::Excluded(value) => self.cdf(value),
+        };
+        let end_freq = match end {
+            Bound::Unbounded => self.freq,
+            Bound::Included(value) | Bound::Excluded(value) => self.cdf(value),
+        };
+        (end_freq - start_freq).max(0.0)
+    }
+}
+
+fn check_invariants(bounds: &[Value], freq: f64) -> Result<(), String> {
+    if !bounds.windows(2).all(|w| w[0] <= w[1]) {
+        return Err("histogram bounds should be sorted".to_string());
+    }
+    if !(0.0..=1.0).contains(&freq) {
+        return Err(format!("histogram freq ({}) should be in [0, 1]", freq));
+    }
+    Ok(())
+}
+
+/// The relative position of `value` between `low` and `high`, in [0, 1], or `None` if the
+/// values do not have a numeric representation.
+fn interpolate(low: &Value, high: &Value, value: &Value) -> Option<f64> {
+    let (low, high, value) = (to_float(low)?, to_float(high)?, to_float(value)?);
+    if high <= low {
+        return Some(1.0);
+    }
+    Some(((value - low) / (high - low)).clamp(0.0, 1.0))
+}
+
+fn to_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int128(v) | Value::Decimal128(v) => Some(*v as f64),
+        Value::Serialized(_) => None,
+        _ => Some(value.to_float()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use crate::common::values::Value;
+
+    use super::EquiDepthHistogram;
+
+    #[test]
+    fn test_cdf_interpolates_inside_buckets() {
+        // 4 buckets of 25 values: [0, 25], [25, 50], [50, 75], [75, 100].
+        let values = (0..=100).map(Value::Int32).collect::<Vec<_>>();
+        let histogram = EquiDepthHistogram::from_values(&values, 4, 0.5).unwrap();
+        assert_eq!(histogram.num_buckets(), 4);
+
+        assert_approx_eq!(histogram.cdf(&Value::Int32(-1)), 0.0);
+        assert_approx_eq!(histogram.cdf(&Value::Int32(0)), 0.0);
+        assert_approx_eq!(histogram.cdf(&Value::Int32(10)), 0.05);
+        assert_approx_eq!(histogram.cdf(&Value::Int32(50)), 0.25);
+        assert_approx_eq!(histogram.cdf(&Value::Int32(60)), 0.3);
+        assert_approx_eq!(histogram.cdf(&Value::Int32(100)), 0.5);
+        assert_approx_eq!(histogram.cdf(&Value::Int32(1000)), 0.5);
+    }
+
+    #[test]
+    fn test_skewed_values() {
+        // Half the values are 0, so the first two buckets are [0, 0].
+        let values = (0..100)
+            .map(|i| Value::Int64(if i < 50 { 0 } else { i }))
+            .collect::<Vec<_>>();
+        let histogram = EquiDepthHistogram::from_values(&values, 4, 1.0).unwrap();
+        assert_approx_eq!(histogram.cdf(&Value::Int64(0)), 0.5);
+        assert_approx_eq!(
+            histogram.range_freq(Bound::Excluded(&Value::Int64(0)), Bound::Unbounded),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_range_freq() {
+        let values = (0..=100).map(Value::Int32).collect::<Vec<_>>();
+        let histogram = EquiDepthHistogram::from_values(&values, 10, 1.0).unwrap();
+        assert_approx_eq!(
+            histogram.range_freq(
+                Bound::Included(&Value::Int32(20)),
+                Bound::Excluded(&Value::Int32(45))
+            ),
+            0.25
+        );
+        assert_approx_eq!(
+            histogram.range_freq(Bound::Unbounded, Bound::Included(&Value::Int32(30))),
+            0.3
+        );
+        assert_approx_eq!(
+            histogram.range_freq(Bound::Included(&Value::Int32(70)), Bound::Unbounded),
+            0.3
+        );
+        // An empty range.
+        assert_approx_eq!(
+            histogram.range_freq(
+                Bound::Included(&Value::Int32(70)),
+                Bound::Included(&Value::Int32(30))
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_non_numeric_values() {
+        let values = ["apple", "banana", "cherry", "date", "elderberry"]
+            .into_iter()
+            .map(|s| Value::String(s.into()))
+            .collect::<Vec<_>>();
+        let histogram = EquiDepthHistogram::from_values(&values, 4, 1.0).unwrap();
+        let cdf = histogram.cdf(&Value::String("c".into()));
+        assert!(
+            cdf > 0.25 && cdf < 0.5,
+            "cdf ({}) should be in bucket 1",
+            cdf
+        );
+
+        let values = (0..5u8)
+            .map(|i| Value::Serialized(vec![i].into()))
+            .collect::<Vec<_>>();
+        let histogram = EquiDepthHistogram::from_values(&values, 4, 1.0).unwrap();
+        // Binary values cannot be interpolated, so they are in the middle of their bucket.
+        assert_approx_eq!(histogram.cdf(&Value::Serialized(vec![1, 0].into())), 0.375);
+    }
+
+    #[test]
+    fn test_empty_histogram() {
+        let histogram = EquiDepthHistogram::from_values(&[], 4, 0.0).unwrap();
+        assert_eq!(histogram.num_buckets(), 0);
+        assert_approx_eq!(histogram.cdf(&Value::Int32(1)), 0.0);
+        assert_approx_eq!(
+            histogram.range_freq(Bound::Unbounded, Bound::Unbounded),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_invalid_histogram_is_error() {
+        let bounds = vec![Value::Int32(2), Value::Int32(1)];
+        assert!(EquiDepthHistogram::new(bounds, 1.0).is_err());
+        assert!(EquiDepthHistogram::new(vec![Value::Int32(1)], 1.5).is_err());
+        assert!(EquiDepthHistogram::from_values(&[Value::Int32(1)], 0, 1.0).is_err());
+
+        let json =
+            serde_json::to_value(EquiDepthHistogram::new(vec![Value::Int32(1)], 1.0).unwrap())
+                .unwrap();
+        let mut unsorted = json.clone();
+        unsorted["bounds"] = serde_json::to_value([Value::Int32(2), Value::Int32(1)]).unwrap();
+        assert!(serde_json::from_value::<EquiDepthHistogram>(unsorted).is_err());
+        let mut invalid_freq = json;
+        invalid_freq["freq"] = serde_json::json!(-0.5);
+        assert!(serde_json::from_value::<EquiDepthHistogram>(invalid_freq).is_err());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let values = (0..10).map(Value::Int32).collect::<Vec<_>>();
+        let histogram = EquiDepthHistogram::from_values(&values, 3, 0.9).unwrap();
+        let json = serde_json::to_string(&histogram).unwrap();
+        let deserialized: EquiDepthHistogram = serde_json::from_str(&json).unwrap();
+        assert_approx_eq!(
+            deserialized.cdf(&Value::Int32(4)),
+            histogram.cdf(&Value::Int32(4))
+        );
+    }
+}
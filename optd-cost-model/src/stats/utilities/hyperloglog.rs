@@ -0,0 +1,286 @@
+//! Implementation of the HyperLogLog data structure as described in Flajolet et al. paper:
+//! "HyperLogLog: the analysis of a near-optimal cardinality estimation algorithm" (2007).
+//! For more details, refer to: https://algo.inria.fr/flajolet/Publications/FlFuGaMe07.pdf
+//! Objects are hashed into 64-bit values instead of 32-bit ones, which makes collisions
+//! unlikely enough to drop the large range correction.
+
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{common::values::Value, CostModelResult, SemanticError};
+
+use super::murmur2::murmur_hash;
+
+/// The default precision: 2^12 registers, for a standard error of about 1.6%.
+pub const DEFAULT_PRECISION: u8 = 12;
+
+/// Trait to transform any object into a stream of bytes.
+pub trait ByteSerializable {
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+/// The HyperLogLog (HLL) structure to estimate the number of distinct elements.
+/// HLLs can only count, and be merged with HLLs of, elements of the same type.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct HyperLogLog<T: ByteSerializable> {
+    /// The maximum number of leading zeros (plus one) seen in each bucket.
+    registers: Vec<u8>,
+    /// The number of bits of the hash used to pick a bucket; 4 <= precision <= 16.
+    precision: u8,
+
+    data_type: PhantomData<T>, // For type checker.
+}
+
+// ByteSerializable implementation of optd's Value. Every value is prefixed with a tag, so that
+// equal payloads of different types are still distinct elements.
+impl ByteSerializable for Value {
+    fn to_bytes(&self) -> Vec<u8> {
+        let (tag, mut payload): (u8, Vec<u8>) = match self {
+            Value::UInt8(v) => (0, v.to_le_bytes().to_vec()),
+            Value::UInt16(v) => (1, v.to_le_bytes().to_vec()),
+            Value::UInt32(v) => (2, v.to_le_bytes().to_vec()),
+            Value::UInt64(v) => (3, v.to_le_bytes().to_vec()),
+            Value::Int8(v) => (4, v.to_le_bytes().to_vec()),
+            Value::Int16(v) => (5, v.to_le_bytes().to_vec()),
+            Value::Int32(v) => (6, v.to_le_bytes().to_vec()),
+            Value::Int64(v) => (7, v.to_le_bytes().to_vec()),
+            Value::Int128(v) => (8, v.to_le_bytes().to_vec()),
+            Value::Float(v) => (9, v.0.to_le_bytes().to_vec()),
+            Value::String(v) => (10, v.as_bytes().to_vec()),
+            Value::Bool(v) => (11, vec![*v as u8]),
+            Value::Date32(v) => (12, v.to_le_bytes().to_vec()),
+            Value::Decimal128(v) => (13, v.to_le_bytes().to_vec()),
+            Value::Serialized(v) => (14, v.to_vec()),
+        };
+        // The length makes the encoding of a combination of values unambiguous.
+        let mut bytes = Vec::with_capacity(payload.len() + 9);
+        bytes.push(tag);
+        bytes.extend((payload.len() as u64).to_le_bytes());
+        bytes.append(&mut payload);
+        bytes
+    }
+}
+
+impl<T: ByteSerializable> ByteSerializable for Option<T> {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            None => vec![0],
+            Some(v) => {
+                let mut bytes = vec![1];
+                bytes.extend(v.to_bytes());
+                bytes
+            }
+        }
+    }
+}
+
+impl<T: ByteSerializable> ByteSerializable for Vec<T> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.iter().flat_map(|v| v.to_bytes()).collect()
+    }
+}
+
+// Self-contained implementation of the HyperLogLog data structure.
+impl<T> HyperLogLog<T>
+where
+    T: ByteSerializable,
+{
+    /// Creates and initializes a new empty HyperLogLog with 2^precision registers.
+    pub fn new(precision: u8) -> CostModelResult<Self> {
+        if !(4..=16).contains(&precision) {
+            return Err(SemanticError::InvalidStatsConfig(format!(
+                "HyperLogLog precision ({precision}) should be in [4, 16]"
+            ))
+            .into());
+        }
+        Ok(HyperLogLog {
+            registers: vec![0; 1 << precision],
+            precision,
+            data_type: PhantomData,
+        })
+    }
+
+    /// Digests an array of data into the HyperLogLog structure.
+    pub fn aggregate(&mut self, data: &[T]) {
+        data.iter().for_each(|elem| self.insert(elem));
+    }
+
+    /// Digests one element into the HyperLogLog structure.
+    pub fn insert(&mut self, elem: &T) {
+        let hash = murmur_hash(&elem.to_bytes(), 0);
+        let index = (hash >> (64 - self.precision)) as usize;
+        // The sentinel bit bounds the number of leading zeros of the remaining bits.
+        let remaining = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = remaining.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Merges another HyperLogLog into the current one.
+    /// Particularly useful for parallel execution, or to combine sketches of several epochs.
+    pub fn merge(&mut self, other: &HyperLogLog<T>) -> CostModelResult<()> {
+        if self.precision != other.precision || self.registers.len() != other.registers.len() {
+            return Err(SemanticError::IncompatibleStats(format!(
+                "cannot merge a HyperLogLog of precision {} into one of precision {}",
+                other.precision, self.precision
+            ))
+            .into());
+        }
+        self.registers
+            .iter_mut()
+            .zip(other.registers.iter())
+            .for_each(|(reg, &other_reg)| *reg = (*reg).max(other_reg));
+        Ok(())
+    }
+
+    /// Returns the estimated number of distinct elements.
+    pub fn n_distinct(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&reg| 2f64.powi(-(reg as i32)))
+            .sum();
+        let raw_estimate = self.alpha() * m * m / sum;
+
+        // Small range correction: fall back to linear counting while registers are empty.
+        let empty_registers = self.registers.iter().filter(|&&reg| reg == 0).count();
+        let estimate = if raw_estimate <= 2.5 * m && empty_registers != 0 {
+            m * (m / empty_registers as f64).ln()
+        } else {
+            raw_estimate
+        };
+        estimate.round() as u64
+    }
+
+    // The bias correction constant, which depends on the number of registers.
+    fn alpha(&self) -> f64 {
+        match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            m => 0.7213 / (1.0 + 1.079 / m as f64),
+        }
+    }
+}
+
+impl<T: ByteSerializable> Default for HyperLogLog<T> {
+    fn default() -> Self {
+        HyperLogLog {
+            registers: vec![0; 1 << DEFAULT_PRECISION],
+            precision: DEFAULT_PRECISION,
+            data_type: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crossbeam::thread;
+    use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
+
+    use crate::common::values::Value;
+
+    use super::{ByteSerializable, HyperLogLog, DEFAULT_PRECISION};
+
+    impl ByteSerializable for String {
+        fn to_bytes(&self) -> Vec<u8> {
+            self.as_bytes().to_vec()
+        }
+    }
+
+    // Whether obtained = expected +/- relative error.
+    fn is_close(obtained: u64, expected: u64, relative_error: f64) -> bool {
+        (obtained as f64 - expected as f64).abs() <= expected as f64 * relative_error
+    }
+
+    fn random_strings(n: usize, len: usize, seed: u64) -> Vec<String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| {
+                (&mut rng)
+                    .sample_iter(&Alphanumeric)
+                    .take(len)
+                    .map(char::from)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hll_small_cardinality_is_exact() {
+        let mut hll = HyperLogLog::<Value>::default();
+        let data: Vec<Value> = (0..100).map(|i| Value::Int32(i % 10)).collect();
+        hll.aggregate(&data);
+        assert_eq!(hll.n_distinct(), 10);
+        assert_eq!(HyperLogLog::<Value>::default().n_distinct(), 0);
+    }
+
+    #[test]
+    fn test_hll_large_cardinality() {
+        let mut hll = HyperLogLog::<String>::default();
+        let data = random_strings(100_000, 16, 0);
+        hll.aggregate(&data);
+        hll.aggregate(&data);
+        assert!(is_close(hll.n_distinct(), 100_000, 0.05));
+    }
+
+    #[test]
+    fn test_hll_distinguishes_types_and_nulls() {
+        let mut hll = HyperLogLog::<Vec<Option<Value>>>::default();
+        hll.aggregate(&[
+            vec![Some(Value::Int32(1)), None],
+            vec![None, Some(Value::Int32(1))],
+            vec![Some(Value::Int64(1)), None],
+            vec![Some(Value::Int32(1)), None],
+        ]);
+        assert_eq!(hll.n_distinct(), 3);
+    }
+
+    #[test]
+    fn test_hll_parallel_merge() {
+        let n_jobs = 8;
+        let data = random_strings(80_000, 16, 1);
+        let result = Arc::new(Mutex::new(
+            HyperLogLog::<String>::new(DEFAULT_PRECISION).unwrap(),
+        ));
+        thread::scope(|s| {
+            for chunk in data.chunks(data.len() / n_jobs) {
+                let result = Arc::clone(&result);
+                s.spawn(move |_| {
+                    let mut local = HyperLogLog::new(DEFAULT_PRECISION).unwrap();
+                    local.aggregate(chunk);
+                    result.lock().unwrap().merge(&local).unwrap();
+                });
+            }
+        })
+        .unwrap();
+
+        let mut sequential = HyperLogLog::<String>::new(DEFAULT_PRECISION).unwrap();
+        sequential.aggregate(&data);
+        let merged = result.lock().unwrap();
+        assert_eq!(merged.n_distinct(), sequential.n_distinct());
+        assert!(is_close(merged.n_distinct(), 80_000, 0.05));
+    }
+
+    #[test]
+    fn test_hll_invalid_precision_is_error() {
+        assert!(HyperLogLog::<Value>::new(3).is_err());
+        assert!(HyperLogLog::<Value>::new(17).is_err());
+
+        let mut hll = HyperLogLog::<Value>::new(8).unwrap();
+        let other = HyperLogLog::<Value>::new(10).unwrap();
+        assert!(hll.merge(&other).is_err());
+    }
+
+    #[test]
+    fn test_hll_serde_round_trip() {
+        let mut hll = HyperLogLog::<Value>::new(8).unwrap();
+        hll.aggregate(&[Value::Bool(true), Value::String("a".into())]);
+        let json = serde_json::to_value(&hll).unwrap();
+        let deserialized: HyperLogLog<Value> = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.n_distinct(), 2);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod counter;
+pub mod histogram;
+pub mod hyperloglog;
+pub mod murmur2;
 pub mod simple_map;
 pub mod tdigest;
@@ -0,0 +1,64 @@
+//! Implementation of the 64-bit MurmurHash2 (MurmurHash64A) by Austin Appleby.
+//! The hash is stable across platforms and runs, so that sketches built from it can be
+//! serialized and merged later on.
+
+const M: u64 = 0xc6a4a7935bd1e995;
+const R: u32 = 47;
+
+/// Hashes a stream of bytes into a 64-bit value.
+pub fn murmur_hash(bytes: &[u8], seed: u64) -> u64 {
+    let mut h = seed ^ (bytes.len() as u64).wrapping_mul(M);
+
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::murmur_hash;
+
+    /// The verification value that SMHasher publishes for MurmurHash64A: the keys {}, {0},
+    /// {0, 1}, ..., {0, ..., 254} are hashed with the seeds 256, 255, ..., 1, and the
+    /// concatenation of their little-endian hashes is hashed again with the seed 0. The value is
+    /// the low 32 bits of that hash.
+    #[test]
+    fn test_murmur_hash_smhasher_verification() {
+        let key: Vec<u8> = (0..=255).collect();
+        let hashes: Vec<u8> = (0..256)
+            .flat_map(|i| murmur_hash(&key[..i], 256 - i as u64).to_le_bytes())
+            .collect();
+        assert_eq!(murmur_hash(&hashes, 0) as u32, 0x1F0D3804);
+    }
+
+    #[test]
+    fn test_murmur_hash_is_stable() {
+        assert_eq!(murmur_hash(b"", 0), 0);
+        assert_eq!(murmur_hash(b"hello", 0), 0x1e68d17c457bf117);
+        assert_eq!(
+            murmur_hash(b"The quick brown fox jumps over the lazy dog", 0),
+            0x5589ca33042a861b
+        );
+        assert_ne!(murmur_hash(b"optd", 0), murmur_hash(b"optd", 1));
+    }
+}
@@ -26,25 +26,80 @@ pub trait IntoFloat {
 
 /// The TDigest structure for the statistical aggregator to query quantiles.
 #[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(try_from = "SerializedTDigest<T>")]
 pub struct TDigest<T: IntoFloat + Eq + Hash + Clone> {
     /// A sorted array of Centroids, according to their mean.
-    pub centroids: Vec<Centroid>, /* TODO(Alexis): Temporary fix to normalize the stats in
-                                   * stats.rs [pub]. */
+    centroids: Vec<Centroid>,
     /// Compression factor: higher is more precise, but has higher memory requirements.
     compression: f64,
     /// Number of values in the TDigest (sum of all centroids).
     total_weight: usize,
 
-    // TODO(Alexis): Temporary fix to normalize the stats in stats.rs [field].
-    pub norm_weight: usize,
+    /// The fraction of a larger population that the values in the TDigest stand for, e.g. the
+    /// non-null values of an attribute that are not MCVs. [`TDigest::norm_cdf`] scales the CDF
+    /// by it. It is 1 if the TDigest holds the whole population.
+    pub norm_freq: f64,
 
     data_type: PhantomData<T>, // For type checker.
 }
 
+/// The serialized form of a [`TDigest`]. TDigests written before `norm_freq` was introduced
+/// store a `norm_weight` instead, which is converted to the same scale of the CDF: the CDF is
+/// scaled by the number of centroids over it, so it must be positive.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+struct SerializedTDigest<T> {
+    centroids: Vec<Centroid>,
+    compression: f64,
+    total_weight: usize,
+    #[serde(default)]
+    norm_freq: Option<f64>,
+    #[serde(default)]
+    norm_weight: Option<f64>,
+    #[serde(skip)]
+    data_type: PhantomData<T>,
+}
+
+impl<T: IntoFloat + Eq + Hash + Clone> TryFrom<SerializedTDigest<T>> for TDigest<T> {
+    type Error = String;
+
+    fn try_from(tdigest: SerializedTDigest<T>) -> Result<Self, Self::Error> {
+        let norm_freq = match (tdigest.norm_freq, tdigest.norm_weight) {
+            (Some(norm_freq), None) => norm_freq,
+            (None, Some(norm_weight)) if norm_weight.is_finite() && norm_weight > 0.0 => {
+                tdigest.centroids.len() as f64 / norm_weight
+            }
+            (None, Some(norm_weight)) => {
+                return Err(format!(
+                    "TDigest norm_weight ({}) should be positive and finite",
+                    norm_weight
+                ))
+            }
+            (None, None) => return Err("TDigest has neither norm_freq nor norm_weight".into()),
+            (Some(_), Some(_)) => return Err("TDigest has both norm_freq and norm_weight".into()),
+        };
+        if !(0.0..=1.0).contains(&norm_freq) {
+            return Err(format!(
+                "TDigest norm_freq ({}) should be in [0, 1]",
+                norm_freq
+            ));
+        }
+        if tdigest.centroids.iter().map(|c| c.weight).sum::<usize>() != tdigest.total_weight {
+            return Err("TDigest total_weight should be the sum of its centroid weights".into());
+        }
+        Ok(TDigest {
+            centroids: tdigest.centroids,
+            compression: tdigest.compression,
+            total_weight: tdigest.total_weight,
+            norm_freq,
+            data_type: PhantomData,
+        })
+    }
+}
+
 /// A Centroid is a cluster of aggregated data points.
 #[derive(PartialEq, PartialOrd, Clone, Serialize, Deserialize, Debug)]
 pub struct Centroid {
-    // TODO(Alexis): Temporary fix to normalize the stats in stats.rs [pub].
     /// Mean of all aggregated points in this cluster.
     mean: f64,
     /// The number of points in this cluster.
@@ -94,8 +149,7 @@ where
             centroids: Vec::new(),
             compression,
             total_weight: 0,
-
-            norm_weight: 0,
+            norm_freq: 1.0,
             data_type: PhantomData,
         }
     }
@@ -116,8 +170,7 @@ where
             centroids,
             compression,
             total_weight,
-
-            norm_weight: 0,
+            norm_freq: 1.0,
             data_type: PhantomData,
         });
     }
@@ -228,6 +281,12 @@ where
         }
     }
 
+    /// Obtains the CDF corresponding to a given value, relative to the population that the
+    /// TDigest is part of, i.e. scaled by `norm_freq`.
+    pub fn norm_cdf(&self, v: &T) -> f64 {
+        self.norm_freq * self.cdf(v)
+    }
+
     // Obtains the k-distance for a given quantile.
     // Note: The scaling function implemented is k1 in Ted Dunning's paper.
     fn k_scale(&self, quantile: f64) -> f64 {
@@ -390,4 +449,48 @@ mod tests {
             assert!(is_close(obtained_cdf, estimate_cdf, error));
         }
     }
+
+    #[test]
+    fn serde_round_trip_and_legacy_norm_weight() {
+        let mut tdigest = TDigest::new(100.0);
+        tdigest.merge_values(&[OrderedFloat(1.0), OrderedFloat(2.0), OrderedFloat(3.0)]);
+        tdigest.norm_freq = 0.5;
+        let json = serde_json::to_value(&tdigest).unwrap();
+        let deserialized: TDigest<OrderedFloat<f64>> =
+            serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(deserialized.norm_freq, 0.5);
+
+        // A TDigest written with `norm_weight` keeps the scale of its CDF.
+        let mut legacy = json.clone();
+        legacy.as_object_mut().unwrap().remove("norm_freq");
+        legacy["norm_weight"] = serde_json::json!(6);
+        let deserialized: TDigest<OrderedFloat<f64>> = serde_json::from_value(legacy).unwrap();
+        assert_eq!(deserialized.norm_freq, 0.5);
+
+        let mut invalid = json.clone();
+        invalid["norm_freq"] = serde_json::json!(2.0);
+        assert!(serde_json::from_value::<TDigest<OrderedFloat<f64>>>(invalid).is_err());
+        let mut invalid = json;
+        invalid["total_weight"] = serde_json::json!(4);
+        assert!(serde_json::from_value::<TDigest<OrderedFloat<f64>>>(invalid).is_err());
+    }
+
+    #[test]
+    fn legacy_norm_weight_must_be_positive() {
+        let mut tdigest = TDigest::new(100.0);
+        tdigest.merge_values(&[OrderedFloat(1.0), OrderedFloat(2.0), OrderedFloat(3.0)]);
+        let mut legacy = serde_json::to_value(&tdigest).unwrap();
+        legacy.as_object_mut().unwrap().remove("norm_freq");
+
+        // A zero or negative weight would give an infinite or negative frequency, and a weight
+        // below the number of centroids a frequency above 1.
+        for norm_weight in [
+            serde_json::json!(0),
+            serde_json::json!(-6),
+            serde_json::json!(2),
+        ] {
+            legacy["norm_weight"] = norm_weight;
+            assert!(serde_json::from_value::<TDigest<OrderedFloat<f64>>>(legacy.clone()).is_err());
+        }
+    }
 }
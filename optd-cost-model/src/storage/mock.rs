@@ -1,15 +1,23 @@
 #![allow(unused_variables, dead_code)]
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
+use optd_persistent::{
+    cost_model::interface::{Stat, StatType},
+    BackendError,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::{
-    common::types::{EpochId, ExprId, TableId},
+    common::types::{AttrId, EpochId, ExprId, IndexId, TableId},
     stats::AttributeCombValueStats,
     Cost, CostModelResult, EstimatedStatistic,
 };
 
-use super::CostModelStorageManager;
+use super::{CostModelStorageManager, IndexInfo};
 
 pub type AttrIndices = Vec<u64>;
 
@@ -19,6 +27,12 @@ pub struct TableStats {
     pub row_cnt: u64,
     #[serde_as(as = "HashMap<serde_with::json::JsonString, _>")]
     pub column_comb_stats: HashMap<AttrIndices, AttributeCombValueStats>,
+    /// The average width in bytes of the attributes, by attribute base index.
+    #[serde(default)]
+    pub attr_avg_widths: HashMap<u64, f64>,
+    /// The indexes of the table.
+    #[serde(skip)]
+    pub indexes: Vec<IndexInfo>,
 }
 
 impl TableStats {
@@ -29,22 +43,79 @@ impl TableStats {
         Self {
             row_cnt,
             column_comb_stats,
+            attr_avg_widths: HashMap::new(),
+            indexes: vec![],
         }
     }
 }
 
 pub type BaseTableStats = HashMap<TableId, TableStats>;
 
+/// What a real statistic describes: either a table or a (sorted) combination of attributes,
+/// together with the statistic type.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum StatKey {
+    Table(TableId, i32),
+    Attr(Vec<AttrId>, i32),
+}
+
+impl StatKey {
+    fn new_attr(mut attr_ids: Vec<AttrId>, stat_type: StatType) -> Self {
+        attr_ids.sort();
+        StatKey::Attr(attr_ids, stat_type as i32)
+    }
+}
+
+/// Real statistics written through `update_statistics`, versioned by epoch.
+#[derive(Default)]
+struct VersionedStats {
+    latest_epoch_id: u64,
+    /// The values of every statistic, in increasing epoch order.
+    history: HashMap<StatKey, Vec<(EpochId, Value)>>,
+}
+
+impl VersionedStats {
+    fn get(&self, key: &StatKey, epoch_id: Option<EpochId>) -> Option<Value> {
+        let versions = self.history.get(key)?;
+        match epoch_id {
+            Some(epoch_id) => versions
+                .iter()
+                .find(|(id, _)| *id == epoch_id)
+                .map(|(_, value)| value.clone()),
+            None => versions.last().map(|(_, value)| value.clone()),
+        }
+    }
+}
+
+/// A record of the cost and estimated statistic of an expression in one epoch, like a row in
+/// the `plan_cost` table.
+#[derive(Clone, Debug)]
+struct CostRecord {
+    epoch_id: EpochId,
+    cost: Option<Cost>,
+    estimated_statistic: Option<EstimatedStatistic>,
+    is_valid: bool,
+}
+
 pub struct CostModelStorageMockManagerImpl {
     pub(crate) per_table_stats_map: BaseTableStats,
+    versioned_stats: RwLock<VersionedStats>,
+    /// The cost records of every expression, in increasing epoch order.
+    costs: RwLock<HashMap<ExprId, Vec<CostRecord>>>,
 }
 
 impl CostModelStorageMockManagerImpl {
     pub fn new(per_table_stats_map: BaseTableStats) -> Self {
         Self {
             per_table_stats_map,
+            versioned_stats: RwLock::new(VersionedStats::default()),
+            costs: RwLock::new(HashMap::new()),
         }
     }
+
+    fn latest_epoch_id(&self) -> EpochId {
+        EpochId(self.versioned_stats.read().unwrap().latest_epoch_id)
+    }
 }
 
 impl CostModelStorageManager for CostModelStorageMockManagerImpl {
@@ -52,13 +123,13 @@ impl CostModelStorageManager for CostModelStorageMockManagerImpl {
         &self,
         table_id: TableId,
         attr_base_indices: &[u64],
-    ) -> CostModelResult<Option<AttributeCombValueStats>> {
+    ) -> CostModelResult<Option<Arc<AttributeCombValueStats>>> {
         let table_stats = self.per_table_stats_map.get(&table_id);
         match table_stats {
             None => Ok(None),
             Some(table_stats) => match table_stats.column_comb_stats.get(attr_base_indices) {
                 None => Ok(None),
-                Some(stats) => Ok(Some(stats.clone())),
+                Some(stats) => Ok(Some(Arc::new(stats.clone()))),
             },
         }
     }
@@ -68,15 +139,44 @@ impl CostModelStorageManager for CostModelStorageMockManagerImpl {
         Ok(table_stats.map(|stats| stats.row_cnt))
     }
 
-    /// TODO: finish this when implementing the cost get/store tests
+    async fn get_attribute_avg_width(
+        &self,
+        table_id: TableId,
+        attr_base_index: u64,
+    ) -> CostModelResult<Option<f64>> {
+        Ok(self
+            .per_table_stats_map
+            .get(&table_id)
+            .and_then(|stats| stats.attr_avg_widths.get(&attr_base_index))
+            .copied())
+    }
+
+    async fn get_index(&self, index_id: IndexId) -> CostModelResult<Option<IndexInfo>> {
+        Ok(self
+            .per_table_stats_map
+            .values()
+            .flat_map(|stats| &stats.indexes)
+            .find(|index| index.index_id == index_id)
+            .cloned())
+    }
+
+    /// Returns the record of the latest epoch, or nothing if that record has been invalidated.
     async fn get_cost(
         &self,
         expr_id: ExprId,
     ) -> CostModelResult<(Option<crate::Cost>, Option<EstimatedStatistic>)> {
-        todo!()
+        let costs = self.costs.read().unwrap();
+        match costs.get(&expr_id).and_then(|records| records.last()) {
+            Some(record) if record.is_valid => {
+                Ok((record.cost.clone(), record.estimated_statistic.clone()))
+            }
+            _ => Ok((None, None)),
+        }
     }
 
-    /// TODO: finish this when implementing the cost get/store tests
+    /// Follows the same rules as the persistent `store_cost`: if `epoch_id` is given, the record
+    /// of that epoch is updated or created. Otherwise, the latest valid record is updated, or a
+    /// new record is created in the latest epoch if there is no valid record.
     async fn store_cost(
         &self,
         expr_id: ExprId,
@@ -84,6 +184,135 @@ impl CostModelStorageManager for CostModelStorageMockManagerImpl {
         estimated_statistic: Option<EstimatedStatistic>,
         epoch_id: Option<EpochId>,
     ) -> CostModelResult<()> {
-        todo!()
+        assert!(cost.is_some() || estimated_statistic.is_some());
+        let latest_epoch_id = self.latest_epoch_id();
+        if let Some(epoch_id) = epoch_id {
+            if epoch_id > latest_epoch_id {
+                return Err(BackendError::CostModel(
+                    format!("epoch id {} not found when storing cost", epoch_id.0).into(),
+                )
+                .into());
+            }
+        }
+
+        let mut costs = self.costs.write().unwrap();
+        let records = costs.entry(expr_id).or_default();
+        let existed_record = match epoch_id {
+            Some(epoch_id) => records.iter_mut().find(|r| r.epoch_id == epoch_id),
+            None => records.iter_mut().rev().find(|r| r.is_valid),
+        };
+        match existed_record {
+            Some(record) => {
+                if cost.is_some() {
+                    record.cost = cost;
+                }
+                if estimated_statistic.is_some() {
+                    record.estimated_statistic = estimated_statistic;
+                }
+            }
+            None => {
+                let record = CostRecord {
+                    epoch_id: epoch_id.unwrap_or(latest_epoch_id),
+                    cost,
+                    estimated_statistic,
+                    is_valid: true,
+                };
+                let pos = records.partition_point(|r| r.epoch_id < record.epoch_id);
+                records.insert(pos, record);
+            }
+        }
+        Ok(())
+    }
+
+    /// Falls back to the row count the mock was created with if no row count has been written
+    /// through `update_statistics`.
+    async fn get_table_statistic(
+        &self,
+        table_id: TableId,
+        stat_type: StatType,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<Option<Value>> {
+        let key = StatKey::Table(table_id, stat_type as i32);
+        let value = self.versioned_stats.read().unwrap().get(&key, epoch_id);
+        match (value, stat_type, epoch_id) {
+            (None, StatType::TableRowCount, None) => Ok(self
+                .per_table_stats_map
+                .get(&table_id)
+                .map(|stats| stats.row_cnt.into())),
+            (value, _, _) => Ok(value),
+        }
+    }
+
+    async fn get_attribute_statistic(
+        &self,
+        attr_ids: Vec<AttrId>,
+        stat_type: StatType,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<Option<Value>> {
+        let key = StatKey::new_attr(attr_ids, stat_type);
+        Ok(self.versioned_stats.read().unwrap().get(&key, epoch_id))
+    }
+
+    /// Returns the record of `epoch_id` even if it has been invalidated, so that the costs of
+    /// past epochs can be analyzed. Without an epoch, this is the same as `get_cost`.
+    async fn get_cost_analysis(
+        &self,
+        expr_id: ExprId,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<(Option<Cost>, Option<EstimatedStatistic>)> {
+        let Some(epoch_id) = epoch_id else {
+            return self.get_cost(expr_id).await;
+        };
+        let costs = self.costs.read().unwrap();
+        match costs
+            .get(&expr_id)
+            .and_then(|records| records.iter().find(|r| r.epoch_id == epoch_id))
+        {
+            Some(record) => Ok((record.cost.clone(), record.estimated_statistic.clone())),
+            None => Ok((None, None)),
+        }
+    }
+
+    async fn update_statistics(
+        &self,
+        stats: Vec<Stat>,
+        source: String,
+        data: String,
+    ) -> CostModelResult<Option<EpochId>> {
+        let mut versioned_stats = self.versioned_stats.write().unwrap();
+        let new_epoch_id = EpochId(versioned_stats.latest_epoch_id + 1);
+        let mut changed = false;
+        for stat in stats {
+            let key = match stat.table_id {
+                Some(table_id) => StatKey::Table(TableId(table_id as u64), stat.stat_type as i32),
+                None => StatKey::new_attr(
+                    stat.attr_ids.iter().map(|&id| AttrId(id as u64)).collect(),
+                    stat.stat_type,
+                ),
+            };
+            let versions = versioned_stats.history.entry(key).or_default();
+            if versions
+                .last()
+                .is_some_and(|(_, value)| *value == stat.stat_value)
+            {
+                continue;
+            }
+            versions.push((new_epoch_id, stat.stat_value));
+            changed = true;
+        }
+        if !changed {
+            return Ok(None);
+        }
+        versioned_stats.latest_epoch_id = new_epoch_id.0;
+
+        // The mock does not track which statistics an expression depends on, so unlike the
+        // persistent storage, every cost computed in an older epoch is invalidated.
+        for records in self.costs.write().unwrap().values_mut() {
+            records
+                .iter_mut()
+                .filter(|r| r.epoch_id < new_epoch_id)
+                .for_each(|r| r.is_valid = false);
+        }
+        Ok(Some(new_epoch_id))
     }
 }
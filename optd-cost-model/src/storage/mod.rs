@@ -1,11 +1,30 @@
+use std::sync::Arc;
+
+use optd_persistent::cost_model::interface::{IndexType, Stat, StatType};
+use serde_json::Value;
+
 use crate::{
-    common::types::{EpochId, ExprId, TableId},
+    common::types::{AttrId, EpochId, ExprId, IndexId, TableId},
     stats::AttributeCombValueStats,
     Cost, CostModelResult, EstimatedStatistic,
 };
 
 pub mod mock;
 pub mod persistent;
+pub mod stats_cache;
+
+/// An index of a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexInfo {
+    pub index_id: IndexId,
+    pub table_id: TableId,
+    pub index_type: IndexType,
+    pub is_unique: bool,
+    /// Whether the rows of the table are stored in the order of the index.
+    pub is_clustered: bool,
+    /// The base indices of the indexed attributes.
+    pub attr_base_indices: Vec<u64>,
+}
 
 #[trait_variant::make(Send)]
 pub trait CostModelStorageManager {
@@ -13,10 +32,21 @@ pub trait CostModelStorageManager {
         &self,
         table_id: TableId,
         attr_base_indices: &[u64],
-    ) -> CostModelResult<Option<AttributeCombValueStats>>;
+    ) -> CostModelResult<Option<Arc<AttributeCombValueStats>>>;
 
     async fn get_table_row_count(&self, table_id: TableId) -> CostModelResult<Option<u64>>;
 
+    /// Gets the average width in bytes of the non-null values of an attribute, or `None` if it
+    /// has not been analyzed.
+    async fn get_attribute_avg_width(
+        &self,
+        table_id: TableId,
+        attr_base_index: u64,
+    ) -> CostModelResult<Option<f64>>;
+
+    /// Gets an index, or `None` if it does not exist.
+    async fn get_index(&self, index_id: IndexId) -> CostModelResult<Option<IndexInfo>>;
+
     async fn get_cost(
         &self,
         expr_id: ExprId,
@@ -29,4 +59,42 @@ pub trait CostModelStorageManager {
         estimated_statistic: Option<EstimatedStatistic>,
         epoch_id: Option<EpochId>,
     ) -> CostModelResult<()>;
+
+    /// Gets the raw value of a table statistic. If `epoch_id` is `None`, the latest value is
+    /// returned; otherwise the value recorded in that epoch is returned.
+    async fn get_table_statistic(
+        &self,
+        table_id: TableId,
+        stat_type: StatType,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<Option<Value>>;
+
+    /// Gets the raw value of a statistic over a combination of attributes. If `epoch_id` is
+    /// `None`, the latest value is returned; otherwise the value recorded in that epoch is
+    /// returned.
+    async fn get_attribute_statistic(
+        &self,
+        attr_ids: Vec<AttrId>,
+        stat_type: StatType,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<Option<Value>>;
+
+    /// Gets the cost and estimated statistic of an expression. If `epoch_id` is `None`, the
+    /// latest valid record is returned; otherwise the record stored in that epoch is returned,
+    /// valid or not.
+    async fn get_cost_analysis(
+        &self,
+        expr_id: ExprId,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<(Option<Cost>, Option<EstimatedStatistic>)>;
+
+    /// Writes a batch of real statistics under one new epoch. Statistics whose value did not
+    /// change are skipped. The batch is written atomically: if any statistic fails, none of them
+    /// is stored. Returns the new epoch id, or `None` if nothing changed.
+    async fn update_statistics(
+        &self,
+        stats: Vec<Stat>,
+        source: String,
+        data: String,
+    ) -> CostModelResult<Option<EpochId>>;
 }
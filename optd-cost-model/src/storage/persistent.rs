@@ -1,49 +1,80 @@
 #![allow(unused_variables)]
 use std::sync::Arc;
 
-use optd_persistent::{cost_model::interface::StatType, CostModelStorageLayer};
+use optd_persistent::{
+    cost_model::interface::{Stat, StatType},
+    CostModelStorageLayer,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
 use crate::{
-    common::types::{EpochId, ExprId, TableId},
+    common::types::{AttrId, EpochId, ExprId, IndexId, TableId},
     stats::{utilities::counter::Counter, AttributeCombValueStats, Distribution, MostCommonValues},
-    Cost, CostModelResult, EstimatedStatistic,
+    Cost, CostModelError, CostModelResult, EstimatedStatistic,
 };
 
-use super::CostModelStorageManager;
+use super::{
+    stats_cache::{StatsCache, StatsCacheMetrics, DEFAULT_STATS_CACHE_CAPACITY},
+    CostModelStorageManager, IndexInfo,
+};
 
 /// TODO: documentation
 pub struct CostModelStorageManagerImpl<S: CostModelStorageLayer + Send + Sync> {
-    pub backend_manager: Arc<S>,
-    // TODO: in-memory cache
+    /// The backend is private, so that every statistic update goes through this manager and
+    /// invalidates the cache.
+    backend_manager: Arc<S>,
+    /// The attribute statistics read in the latest epoch this manager knows about.
+    stats_cache: StatsCache,
 }
 
 impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManagerImpl<S> {
-    pub fn new(backend_manager: Arc<S>) -> Self {
-        Self { backend_manager }
+    pub async fn new(backend_manager: Arc<S>) -> CostModelResult<Self> {
+        Self::with_cache_capacity(backend_manager, DEFAULT_STATS_CACHE_CAPACITY).await
     }
-}
 
-impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
-    for CostModelStorageManagerImpl<S>
-{
-    /// Gets the latest statistics for a given table. Currently we only support base table
-    /// statistic retrieval.
+    /// Creates a storage manager that caches the statistics of at most `capacity` attribute
+    /// combinations. A capacity of 0 disables the cache. The cache starts at the latest epoch
+    /// of the backend.
+    pub async fn with_cache_capacity(
+        backend_manager: Arc<S>,
+        capacity: usize,
+    ) -> CostModelResult<Self> {
+        let epoch_id = backend_manager
+            .get_latest_epoch_id()
+            .await?
+            .map(|id| EpochId(id as u64))
+            .unwrap_or_default();
+        Ok(Self {
+            backend_manager,
+            stats_cache: StatsCache::new(capacity, epoch_id),
+        })
+    }
+
+    /// Creates a new epoch in the backend, and invalidates the statistics cached in the
+    /// previous epochs.
+    pub async fn create_new_epoch(&self, source: String, data: String) -> CostModelResult<EpochId> {
+        let epoch_id = EpochId(self.backend_manager.create_new_epoch(source, data).await? as u64);
+        self.stats_cache.advance_epoch(epoch_id);
+        Ok(epoch_id)
+    }
+
+    /// Returns the hit/miss counters of the statistics cache.
+    pub fn stats_cache_metrics(&self) -> StatsCacheMetrics {
+        self.stats_cache.metrics()
+    }
+
+    /// Reads the latest statistics of an attribute combination from the backend.
     ///
     /// TODO: Currently, in `AttributeCombValueStats`, only `Distribution` is optional.
     /// This poses a question about the behavior of the system if there is no corresponding
     /// `MostCommonValues`, `ndistinct`, or other statistics. We should have a clear
     /// specification about the behavior of the system in the presence of missing statistics.
-    ///
-    /// TODO: if we have memory cache,
-    /// we should add the reference. (&AttributeCombValueStats)
-    ///
-    /// TODO: Shall we pass in an epoch here to make sure that the statistics are from the same
-    /// epoch?
-    async fn get_attributes_comb_statistics(
+    async fn load_attributes_comb_statistics(
         &self,
         table_id: TableId,
         attr_base_indices: &[u64],
-    ) -> CostModelResult<Option<AttributeCombValueStats>> {
+    ) -> CostModelResult<AttributeCombValueStats> {
         let dist: Option<Distribution> = self
             .backend_manager
             .get_stats_for_attr_indices_based(
@@ -53,7 +84,8 @@ impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
                 None,
             )
             .await?
-            .map(|json| serde_json::from_value(json).unwrap());
+            .map(|json| parse_stat(StatType::Distribution, json))
+            .transpose()?;
 
         let mcvs = self
             .backend_manager
@@ -64,7 +96,8 @@ impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
                 None,
             )
             .await?
-            .map(|json| serde_json::from_value(json).unwrap())
+            .map(|json| parse_stat(StatType::MostCommonValues, json))
+            .transpose()?
             .unwrap_or_else(|| MostCommonValues::Counter(Counter::default()));
 
         let ndistinct = self
@@ -76,7 +109,8 @@ impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
                 None,
             )
             .await?
-            .map(|json| serde_json::from_value(json).unwrap())
+            .map(|json| parse_stat(StatType::Cardinality, json))
+            .transpose()?
             .unwrap_or(0);
 
         let table_row_count = self
@@ -88,7 +122,8 @@ impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
                 None,
             )
             .await?
-            .map(|json| serde_json::from_value(json).unwrap())
+            .map(|json| parse_stat(StatType::TableRowCount, json))
+            .transpose()?
             .unwrap_or(0);
         let non_null_count = self
             .backend_manager
@@ -99,7 +134,8 @@ impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
                 None,
             )
             .await?
-            .map(|json| serde_json::from_value(json).unwrap())
+            .map(|json| parse_stat(StatType::NonNullCount, json))
+            .transpose()?
             .unwrap_or(0);
 
         // FIXME: Only minimal checks for invalid values is conducted here. We should have
@@ -111,18 +147,99 @@ impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
             1.0 - (non_null_count as f64 / table_row_count as f64)
         };
 
-        Ok(Some(AttributeCombValueStats::new(
-            mcvs, dist, ndistinct, null_frac,
-        )))
+        // Only single attributes have an average width.
+        let avg_width = match attr_base_indices {
+            [attr_base_index] => self
+                .backend_manager
+                .get_stats_for_attr_indices_based(
+                    table_id.into(),
+                    vec![*attr_base_index as i32],
+                    StatType::AvgWidth,
+                    None,
+                )
+                .await?
+                .map(|json| parse_stat(StatType::AvgWidth, json))
+                .transpose()?,
+            _ => None,
+        };
+
+        let mut stats = AttributeCombValueStats::new(mcvs, dist, ndistinct, null_frac);
+        stats.avg_width = avg_width;
+        Ok(stats)
+    }
+}
+
+impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
+    for CostModelStorageManagerImpl<S>
+{
+    /// Gets the latest statistics for a given table. Currently we only support base table
+    /// statistic retrieval.
+    ///
+    /// The statistics are served from the cache if they have been read in the current epoch.
+    /// Note that the cache only knows about the latest epoch of the backend when the manager was
+    /// created, and the epochs created through this manager, i.e. `create_new_epoch` and
+    /// `update_statistics`.
+    ///
+    /// TODO: Shall we pass in an epoch here to make sure that the statistics are from the same
+    /// epoch?
+    async fn get_attributes_comb_statistics(
+        &self,
+        table_id: TableId,
+        attr_base_indices: &[u64],
+    ) -> CostModelResult<Option<Arc<AttributeCombValueStats>>> {
+        if let Some(stats) = self.stats_cache.get(table_id, attr_base_indices) {
+            return Ok(Some(stats));
+        }
+        // Read the epoch before loading, so that stats loaded while a new epoch is being
+        // created are not cached under the new epoch.
+        let epoch_id = self.stats_cache.epoch_id();
+        let stats = Arc::new(
+            self.load_attributes_comb_statistics(table_id, attr_base_indices)
+                .await?,
+        );
+        self.stats_cache
+            .insert(table_id, attr_base_indices, epoch_id, stats.clone());
+        Ok(Some(stats))
     }
 
     async fn get_table_row_count(&self, table_id: TableId) -> CostModelResult<Option<u64>> {
+        self.backend_manager
+            .get_stats_for_table(table_id.into(), StatType::TableRowCount, None)
+            .await?
+            .map(|json| parse_stat(StatType::TableRowCount, json))
+            .transpose()
+    }
+
+    /// The average width is loaded with the other statistics of the attribute, so it is served
+    /// from the statistics cache.
+    async fn get_attribute_avg_width(
+        &self,
+        table_id: TableId,
+        attr_base_index: u64,
+    ) -> CostModelResult<Option<f64>> {
+        Ok(self
+            .get_attributes_comb_statistics(table_id, &[attr_base_index])
+            .await?
+            .and_then(|stats| stats.avg_width))
+    }
+
+    async fn get_index(&self, index_id: IndexId) -> CostModelResult<Option<IndexInfo>> {
         Ok(self
             .backend_manager
-            .get_stats_for_table(table_id.into(), StatType::TableRowCount, None)
+            .get_index(index_id.into())
             .await?
-            .map(serde_json::from_value)
-            .transpose()?)
+            .map(|index| IndexInfo {
+                index_id: IndexId(index.id as u64),
+                table_id: TableId(index.table_id as u64),
+                index_type: index.index_type,
+                is_unique: index.is_unique,
+                is_clustered: index.is_clustered,
+                attr_base_indices: index
+                    .attr_base_indices
+                    .into_iter()
+                    .map(|attr_base_index| attr_base_index as u64)
+                    .collect(),
+            }))
     }
 
     /// TODO: The name is misleading, since we can also get the estimated statistic. We should
@@ -162,5 +279,273 @@ impl<S: CostModelStorageLayer + Send + Sync> CostModelStorageManager
         Ok(())
     }
 
+    async fn get_table_statistic(
+        &self,
+        table_id: TableId,
+        stat_type: StatType,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<Option<Value>> {
+        Ok(self
+            .backend_manager
+            .get_stats_for_table(table_id.into(), stat_type, epoch_id.map(|id| id.into()))
+            .await?)
+    }
+
+    async fn get_attribute_statistic(
+        &self,
+        attr_ids: Vec<AttrId>,
+        stat_type: StatType,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<Option<Value>> {
+        Ok(self
+            .backend_manager
+            .get_stats_for_attr(
+                attr_ids.into_iter().map(|id| id.into()).collect(),
+                stat_type,
+                epoch_id.map(|id| id.into()),
+            )
+            .await?)
+    }
+
+    async fn get_cost_analysis(
+        &self,
+        expr_id: ExprId,
+        epoch_id: Option<EpochId>,
+    ) -> CostModelResult<(Option<Cost>, Option<EstimatedStatistic>)> {
+        let (cost, estimated_statistic) = match epoch_id {
+            Some(epoch_id) => {
+                self.backend_manager
+                    .get_cost_analysis(expr_id.into(), epoch_id.into())
+                    .await?
+            }
+            None => self.backend_manager.get_cost(expr_id.into()).await?,
+        };
+        Ok((
+            cost.map(|c| c.into()),
+            estimated_statistic.map(|x| x.into()),
+        ))
+    }
+
+    /// The whole batch is written in one transaction, so a failure leaves no statistic of the
+    /// batch behind. See `CostModelStorageLayer::update_stats_batch` for details.
+    async fn update_statistics(
+        &self,
+        stats: Vec<Stat>,
+        source: String,
+        data: String,
+    ) -> CostModelResult<Option<EpochId>> {
+        let epoch_id = self
+            .backend_manager
+            .update_stats_batch(stats, source, data)
+            .await?;
+        let epoch_id = epoch_id.map(|id| EpochId(id as u64));
+        if let Some(epoch_id) = epoch_id {
+            self.stats_cache.advance_epoch(epoch_id);
+        }
+        Ok(epoch_id)
+    }
+
     // TODO: Support querying for a specific type of statistics.
 }
+
+/// Deserializes a statistic read from the storage, which fails if the statistic was written by
+/// an incompatible version or corrupted.
+fn parse_stat<T: DeserializeOwned>(stat_type: StatType, json: Value) -> CostModelResult<T> {
+    serde_json::from_value(json)
+        .map_err(|err| CostModelError::CorruptStatistic(format!("{:?}", stat_type), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use optd_persistent::{
+        cost_model::interface::{Stat, StatType},
+        CostModelStorageLayer,
+    };
+    use serde_json::json;
+
+    use crate::{
+        common::types::{EpochId, TableId},
+        storage::{stats_cache::StatsCacheMetrics, CostModelStorageManager},
+        test_utils::tests::{create_persistent_cost_model, remove_db_file},
+        CostModelError,
+    };
+
+    use super::CostModelStorageManagerImpl;
+
+    #[tokio::test]
+    async fn test_stats_cache_hit_and_invalidation() {
+        const DATABASE_FILE: &str = "test_stats_cache_hit_and_invalidation.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        let storage_manager = &cost_model.storage_manager;
+        let table_id = TableId(1);
+
+        // The attribute with base index 0 of table 1 is attribute 1, whose cardinality is 0.
+        let first = storage_manager
+            .get_attributes_comb_statistics(table_id, &[0])
+            .await
+            .unwrap()
+            .unwrap();
+        let second = storage_manager
+            .get_attributes_comb_statistics(table_id, &[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.ndistinct, 0);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            storage_manager.stats_cache_metrics(),
+            StatsCacheMetrics { hits: 1, misses: 1 }
+        );
+
+        let epoch_id = storage_manager
+            .update_statistics(
+                vec![Stat {
+                    stat_type: StatType::Cardinality,
+                    stat_value: json!(42),
+                    attr_ids: vec![1],
+                    table_id: None,
+                    name: "cardinality".to_string(),
+                }],
+                "test".to_string(),
+                "test_stats_cache_hit_and_invalidation".to_string(),
+            )
+            .await
+            .unwrap();
+        assert!(epoch_id.is_some());
+
+        let third = storage_manager
+            .get_attributes_comb_statistics(table_id, &[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(third.ndistinct, 42);
+        assert_eq!(
+            storage_manager.stats_cache_metrics(),
+            StatsCacheMetrics { hits: 1, misses: 2 }
+        );
+
+        // A new epoch created without any statistic change also invalidates the cache.
+        let new_epoch_id = storage_manager
+            .create_new_epoch("test".to_string(), "new epoch".to_string())
+            .await
+            .unwrap();
+        assert!(new_epoch_id > epoch_id.unwrap());
+        storage_manager
+            .get_attributes_comb_statistics(table_id, &[0])
+            .await
+            .unwrap();
+        assert_eq!(
+            storage_manager.stats_cache_metrics(),
+            StatsCacheMetrics { hits: 1, misses: 3 }
+        );
+
+        remove_db_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_avg_width_is_cached() {
+        const DATABASE_FILE: &str = "test_avg_width_is_cached.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        let storage_manager = &cost_model.storage_manager;
+        let table_id = TableId(1);
+
+        storage_manager
+            .update_statistics(
+                vec![Stat {
+                    stat_type: StatType::AvgWidth,
+                    stat_value: json!(12.5),
+                    attr_ids: vec![1],
+                    table_id: None,
+                    name: "avg_width".to_string(),
+                }],
+                "test".to_string(),
+                "test_avg_width_is_cached".to_string(),
+            )
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(
+                storage_manager
+                    .get_attribute_avg_width(table_id, 0)
+                    .await
+                    .unwrap(),
+                Some(12.5)
+            );
+        }
+        // The width is loaded with the other statistics of the attribute.
+        let stats = storage_manager
+            .get_attributes_comb_statistics(table_id, &[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stats.avg_width, Some(12.5));
+        assert_eq!(
+            storage_manager.stats_cache_metrics(),
+            StatsCacheMetrics { hits: 2, misses: 1 }
+        );
+
+        remove_db_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_stats_cache_starts_at_latest_epoch() {
+        const DATABASE_FILE: &str = "test_stats_cache_starts_at_latest_epoch.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        // The test database has statistics in epoch 1.
+        assert_eq!(
+            cost_model.storage_manager.stats_cache.epoch_id(),
+            EpochId(1)
+        );
+
+        // An epoch created by another writer is picked up by a new manager.
+        let epoch_id = cost_model
+            .storage_manager
+            .backend_manager
+            .create_new_epoch("test".to_string(), "another writer".to_string())
+            .await
+            .unwrap();
+        let storage_manager =
+            CostModelStorageManagerImpl::new(cost_model.storage_manager.backend_manager.clone())
+                .await
+                .unwrap();
+        assert_eq!(
+            storage_manager.stats_cache.epoch_id(),
+            EpochId(epoch_id as u64)
+        );
+
+        remove_db_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_statistic_is_error() {
+        const DATABASE_FILE: &str = "test_corrupt_statistic_is_error.db";
+        let cost_model = create_persistent_cost_model(DATABASE_FILE).await;
+        let storage_manager = &cost_model.storage_manager;
+
+        storage_manager
+            .update_statistics(
+                vec![Stat {
+                    stat_type: StatType::Cardinality,
+                    stat_value: json!("forty-two"),
+                    attr_ids: vec![1],
+                    table_id: None,
+                    name: "cardinality".to_string(),
+                }],
+                "test".to_string(),
+                "test_corrupt_statistic_is_error".to_string(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            storage_manager
+                .get_attributes_comb_statistics(TableId(1), &[0])
+                .await,
+            Err(CostModelError::CorruptStatistic(name, _)) if name == "Cardinality"
+        ));
+
+        remove_db_file(DATABASE_FILE);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::{
+    common::types::{EpochId, TableId},
+    stats::AttributeCombValueStats,
+};
+
+/// The default maximum number of attribute combinations kept in a [`StatsCache`].
+pub const DEFAULT_STATS_CACHE_CAPACITY: usize = 1024;
+
+/// The key of a cached statistic: the table and the base indices of the attribute combination.
+/// All the entries of a cache are read in the same epoch, so the epoch is not part of the key.
+pub type StatsCacheKey = (TableId, Vec<u64>);
+
+/// A snapshot of the hit/miss counters of a [`StatsCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatsCacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+struct CacheEntry {
+    stats: Arc<AttributeCombValueStats>,
+    /// The logical time of the last access, which is the key of the entry in `lru`.
+    last_used: u64,
+}
+
+struct CacheState {
+    /// The epoch that all the entries in `entries` were read in.
+    epoch_id: EpochId,
+    entries: HashMap<StatsCacheKey, CacheEntry>,
+    /// The keys of `entries` ordered by the logical time of their last access, so that the least
+    /// recently used entry is the first one.
+    lru: BTreeMap<u64, StatsCacheKey>,
+    clock: u64,
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.lru.clear();
+    }
+}
+
+/// A bounded, concurrent cache of [`AttributeCombValueStats`].
+///
+/// Statistics are handed out as `Arc`s, so a lookup never copies the underlying MCVs or
+/// distribution. The cache only holds statistics of the current epoch: when a new epoch is
+/// observed through [`StatsCache::advance_epoch`], all the entries are dropped. When the cache
+/// is full, the least recently used entry is evicted in logarithmic time.
+pub struct StatsCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl StatsCache {
+    /// Creates a cache of at most `capacity` entries, which caches the statistics read in
+    /// `epoch_id`, the latest epoch of the storage.
+    pub fn new(capacity: usize, epoch_id: EpochId) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState {
+                epoch_id,
+                entries: HashMap::new(),
+                lru: BTreeMap::new(),
+                clock: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The epoch that cached statistics are read in.
+    pub fn epoch_id(&self) -> EpochId {
+        self.state.lock().unwrap().epoch_id
+    }
+
+    /// Looks up the statistics of an attribute combination in the current epoch, and records
+    /// a hit or a miss.
+    pub fn get(
+        &self,
+        table_id: TableId,
+        attr_base_indices: &[u64],
+    ) -> Option<Arc<AttributeCombValueStats>> {
+        let mut state = self.state.lock().unwrap();
+        let key = (table_id, attr_base_indices.to_vec());
+        let now = state.tick();
+        let CacheState { entries, lru, .. } = &mut *state;
+        match entries.get_mut(&key) {
+            Some(entry) => {
+                let key = lru.remove(&entry.last_used).unwrap();
+                lru.insert(now, key);
+                entry.last_used = now;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.stats.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Caches the statistics of an attribute combination read in `epoch_id`. Statistics read
+    /// in an epoch other than the current one are not cached.
+    pub fn insert(
+        &self,
+        table_id: TableId,
+        attr_base_indices: &[u64],
+        epoch_id: EpochId,
+        stats: Arc<AttributeCombValueStats>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.epoch_id != epoch_id {
+            return;
+        }
+        let key = (table_id, attr_base_indices.to_vec());
+        let now = state.tick();
+        if let Some(entry) = state.entries.remove(&key) {
+            state.lru.remove(&entry.last_used);
+        } else if state.entries.len() >= self.capacity {
+            if let Some((_, lru_key)) = state.lru.pop_first() {
+                state.entries.remove(&lru_key);
+            }
+        }
+        state.lru.insert(now, key.clone());
+        state.entries.insert(
+            key,
+            CacheEntry {
+                stats,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Moves the cache to a newer epoch and drops every entry of the older epochs.
+    pub fn advance_epoch(&self, epoch_id: EpochId) {
+        let mut state = self.state.lock().unwrap();
+        if epoch_id > state.epoch_id {
+            state.epoch_id = epoch_id;
+            state.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn metrics(&self) -> StatsCacheMetrics {
+        StatsCacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        common::types::{EpochId, TableId},
+        stats::{AttributeCombValueStats, MostCommonValues},
+    };
+
+    use super::{StatsCache, StatsCacheMetrics};
+
+    fn stats_with_ndistinct(ndistinct: u64) -> Arc<AttributeCombValueStats> {
+        Arc::new(AttributeCombValueStats::new(
+            MostCommonValues::empty(),
+            None,
+            ndistinct,
+            0.0,
+        ))
+    }
+
+    #[test]
+    fn test_hit_and_miss() {
+        let cache = StatsCache::new(4, EpochId(0));
+        assert!(cache.get(TableId(0), &[0]).is_none());
+        cache.insert(TableId(0), &[0], EpochId(0), stats_with_ndistinct(10));
+
+        let stats = cache.get(TableId(0), &[0]).unwrap();
+        assert_eq!(stats.ndistinct, 10);
+        assert!(cache.get(TableId(0), &[0, 1]).is_none());
+        assert_eq!(cache.metrics(), StatsCacheMetrics { hits: 1, misses: 2 });
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let cache = StatsCache::new(2, EpochId(0));
+        cache.insert(TableId(0), &[0], EpochId(0), stats_with_ndistinct(1));
+        cache.insert(TableId(0), &[1], EpochId(0), stats_with_ndistinct(2));
+        // Touch the first entry so that the second one becomes the least recently used.
+        assert!(cache.get(TableId(0), &[0]).is_some());
+        cache.insert(TableId(0), &[2], EpochId(0), stats_with_ndistinct(3));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(TableId(0), &[0]).is_some());
+        assert!(cache.get(TableId(0), &[1]).is_none());
+        assert!(cache.get(TableId(0), &[2]).is_some());
+
+        // Replacing an entry does not evict another one, and makes it the most recently used.
+        cache.insert(TableId(0), &[0], EpochId(0), stats_with_ndistinct(4));
+        assert_eq!(cache.len(), 2);
+        cache.insert(TableId(0), &[1], EpochId(0), stats_with_ndistinct(2));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(TableId(0), &[0]).unwrap().ndistinct, 4);
+        assert!(cache.get(TableId(0), &[1]).is_some());
+        assert!(cache.get(TableId(0), &[2]).is_none());
+    }
+
+    #[test]
+    fn test_advance_epoch_invalidates() {
+        let cache = StatsCache::new(4, EpochId(0));
+        cache.insert(TableId(0), &[0], EpochId(0), stats_with_ndistinct(1));
+        cache.advance_epoch(EpochId(3));
+        assert!(cache.is_empty());
+        assert_eq!(cache.epoch_id(), EpochId(3));
+
+        // Statistics read in a stale epoch are not cached.
+        cache.insert(TableId(0), &[0], EpochId(0), stats_with_ndistinct(1));
+        assert!(cache.is_empty());
+
+        // Going back to an older epoch is a no-op.
+        cache.insert(TableId(0), &[0], EpochId(3), stats_with_ndistinct(2));
+        cache.advance_epoch(EpochId(2));
+        assert_eq!(cache.get(TableId(0), &[0]).unwrap().ndistinct, 2);
+    }
+}
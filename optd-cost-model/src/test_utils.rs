@@ -7,16 +7,18 @@ pub mod tests {
     use std::{collections::HashMap, sync::Arc};
 
     use arrow_schema::DataType;
-    use optd_persistent::cost_model::interface::CatalogSource;
+    use optd_persistent::{cost_model::interface::CatalogSource, BackendManager};
 
     use crate::{
         common::{
-            nodes::{ArcPredicateNode, ReprPredicateNode},
+            nodes::{ArcPredicateNode, PredicateNode, PredicateType, ReprPredicateNode},
             predicates::{
                 attr_index_pred::AttrIndexPred,
+                between_pred::BetweenPred,
                 bin_op_pred::{BinOpPred, BinOpType},
                 cast_pred::CastPred,
                 constant_pred::{ConstantPred, ConstantType},
+                func_pred::FuncType,
                 in_list_pred::InListPred,
                 like_pred::LikePred,
                 list_pred::ListPred,
@@ -32,12 +34,16 @@ pub mod tests {
             values::Value,
         },
         cost_model::CostModelImpl,
+        cost_profile::CostProfile,
         memo_ext::MemoExt,
         stats::{
             utilities::simple_map::SimpleMap, AttributeCombValueStats, Distribution,
             MostCommonValues,
         },
-        storage::mock::{CostModelStorageMockManagerImpl, TableStats},
+        storage::{
+            mock::{CostModelStorageMockManagerImpl, TableStats},
+            persistent::CostModelStorageManagerImpl,
+        },
     };
 
     pub struct MemoGroupInfo {
@@ -113,8 +119,38 @@ pub mod tests {
     pub const TEST_ATTR4_NAME: &str = "attr4";
 
     pub type TestPerAttributeStats = AttributeCombValueStats;
-    // TODO: add tests for non-mock storage manager
     pub type TestOptCostModelMock = CostModelImpl<CostModelStorageMockManagerImpl>;
+    pub type TestOptCostModelPersistent =
+        CostModelImpl<CostModelStorageManagerImpl<BackendManager>>;
+
+    /// The SQLite database initialized by `optd-persistent`, which contains a mock catalog,
+    /// some statistics in epoch 1, and the cost of physical expression 1.
+    const TEST_INIT_DATABASE_FILE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../optd-persistent/src/db/init.db"
+    );
+
+    /// Create a cost model backed by a copy of the `optd-persistent` test database. Each test
+    /// should use its own `db_file` and remove it with [`remove_db_file`] at the end.
+    pub async fn create_persistent_cost_model(db_file: &str) -> TestOptCostModelPersistent {
+        let _ = std::fs::remove_file(db_file);
+        std::fs::copy(TEST_INIT_DATABASE_FILE, db_file).expect("Unable to copy the test database");
+        let backend_manager = BackendManager::new(Some(&format!("sqlite:{}?mode=rwc", db_file)))
+            .await
+            .expect("Unable to connect to the test database");
+        CostModelImpl::new(
+            CostModelStorageManagerImpl::new(Arc::new(backend_manager))
+                .await
+                .expect("Unable to read the latest epoch of the test database"),
+            CatalogSource::Mock,
+            Arc::new(MockMemoExtImpl::default()),
+            CostProfile::default(),
+        )
+    }
+
+    pub fn remove_db_file(db_file: &str) {
+        let _ = std::fs::remove_file(db_file);
+    }
 
     // Use this method, we only create one group `TEST_GROUP1_ID` in the memo.
     // We put the first attribute in the first table as the ref index 0 in the group.
@@ -132,8 +168,8 @@ pub mod tests {
             .enumerate()
             .map(|(idx, m)| (table_id[idx], m))
             .flat_map(|(table_id, m)| {
-                m.iter()
-                    .map(|(attr_idx, _)| (table_id, *attr_idx, None))
+                m.keys()
+                    .map(|attr_idx| (table_id, *attr_idx, None))
                     .collect_vec()
             })
             .sorted_by_key(|(table_id, attr_idx, _)| (*table_id, *attr_idx))
@@ -197,7 +233,27 @@ pub mod tests {
                 })
                 .collect(),
         );
-        CostModelImpl::new(storage_manager, CatalogSource::Mock, Arc::new(memo))
+        CostModelImpl::new(
+            storage_manager,
+            CatalogSource::Mock,
+            Arc::new(memo),
+            CostProfile::default(),
+        )
+    }
+
+    /// Create a cost model with the statistics of each table in `table_stats`, and only one group
+    /// `TEST_GROUP1_ID` in the memo with the base attributes `attr_ids`, as in
+    /// `create_one_group_all_base_attributes_mock_memo`.
+    pub fn create_mock_cost_model_with_table_stats(
+        table_stats: HashMap<TableId, TableStats>,
+        attr_ids: Vec<(TableId, u64, Option<ConstantType>)>,
+    ) -> TestOptCostModelMock {
+        CostModelImpl::new(
+            CostModelStorageMockManagerImpl::new(table_stats),
+            CatalogSource::Mock,
+            Arc::new(create_one_group_all_base_attributes_mock_memo(attr_ids)),
+            CostProfile::default(),
+        )
     }
 
     // attributes: Vec<(TableId, AttrBaseIndex)>
@@ -313,6 +369,7 @@ pub mod tests {
             storage_manager,
             CatalogSource::Mock,
             Arc::new(MockMemoExtImpl::from(memo)),
+            CostProfile::default(),
         )
     }
 
@@ -403,6 +460,7 @@ pub mod tests {
             storage_manager,
             CatalogSource::Mock,
             Arc::new(MockMemoExtImpl::from(memo)),
+            CostProfile::default(),
         )
     }
 
@@ -463,6 +521,7 @@ pub mod tests {
             storage_manager,
             CatalogSource::Mock,
             Arc::new(MockMemoExtImpl::from(memo)),
+            CostProfile::default(),
         )
     }
 
@@ -516,6 +575,31 @@ pub mod tests {
         ListPred::new(children).into_pred_node()
     }
 
+    pub fn agg_func(
+        func: datafusion_expr::AggregateFunction,
+        args: Vec<ArcPredicateNode>,
+        distinct: bool,
+    ) -> ArcPredicateNode {
+        PredicateNode {
+            typ: PredicateType::Func(FuncType::new_agg(func)),
+            children: args,
+            data: distinct.then_some(Value::Bool(true)),
+        }
+        .into()
+    }
+
+    pub fn window_func(
+        func: datafusion_expr::WindowFunction,
+        args: Vec<ArcPredicateNode>,
+    ) -> ArcPredicateNode {
+        PredicateNode {
+            typ: PredicateType::Func(FuncType::new_window(func)),
+            children: args,
+            data: None,
+        }
+        .into()
+    }
+
     pub fn in_list(attr_idx: u64, list: Vec<Value>, negated: bool) -> InListPred {
         InListPred::new(
             attr_index(attr_idx),
@@ -524,6 +608,10 @@ pub mod tests {
         )
     }
 
+    pub fn between(attr_idx: u64, lower: Value, upper: Value, negated: bool) -> BetweenPred {
+        BetweenPred::new(attr_index(attr_idx), cnst(lower), cnst(upper), negated)
+    }
+
     pub fn like(attr_idx: u64, pattern: &str, negated: bool) -> LikePred {
         LikePred::new(
             negated,
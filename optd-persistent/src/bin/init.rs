@@ -3,7 +3,9 @@ use optd_persistent::cost_model::interface::ConstraintType;
 use optd_persistent::cost_model::interface::IndexType;
 use optd_persistent::cost_model::interface::StatType;
 use optd_persistent::entities::*;
+use optd_persistent::fingerprint;
 use optd_persistent::migrate;
+use optd_persistent::FINGERPRINT_VERSION;
 use optd_persistent::TEST_DATABASE_FILENAME;
 use optd_persistent::TEST_DATABASE_URL;
 use sea_orm::sqlx::types::chrono::Utc;
@@ -276,6 +278,7 @@ async fn init_all_tables() -> Result<(), sea_orm::error::DbErr> {
         latest_winner: Set(None),
         in_progress: Set(true),
         is_optimized: Set(false),
+        parent_id: Set(None),
     };
     cascades_group::Entity::insert(cascades_group)
         .exec(&db)
@@ -286,7 +289,8 @@ async fn init_all_tables() -> Result<(), sea_orm::error::DbErr> {
     let logical_expression = logical_expression::ActiveModel {
         id: Set(1),
         group_id: Set(1),
-        fingerprint: Set(12345),
+        fingerprint: Set(fingerprint(0, &json!(r#"{"expr": "index_scan"}"#), &[])),
+        fingerprint_version: Set(FINGERPRINT_VERSION),
         variant_tag: Set(0),
         data: Set(json!(r#"{"expr": "index_scan"}"#)),
     };
@@ -299,7 +303,8 @@ async fn init_all_tables() -> Result<(), sea_orm::error::DbErr> {
     let physical_expression = physical_expression::ActiveModel {
         id: Set(1),
         group_id: Set(1),
-        fingerprint: Set(12345),
+        fingerprint: Set(fingerprint(0, &json!(r#"{"expr": "index_scan"}"#), &[])),
+        fingerprint_version: Set(FINGERPRINT_VERSION),
         variant_tag: Set(0),
         data: Set(json!(r#"{"expr": "index_scan"}"#)),
     };
@@ -312,6 +317,7 @@ async fn init_all_tables() -> Result<(), sea_orm::error::DbErr> {
     let physical_property = physical_property::ActiveModel {
         id: Set(1),
         physical_expression_id: Set(1),
+        group_winner_id: Set(None),
         variant_tag: Set(0),
         data: Set(json!(r#"{"property": "indexed"}"#)),
     };
@@ -334,6 +340,7 @@ async fn init_all_tables() -> Result<(), sea_orm::error::DbErr> {
 
     let logical_children = logical_children::ActiveModel {
         logical_expression_id: Set(1),
+        child_index: Set(0),
         group_id: Set(1),
     };
     logical_children::Entity::insert(logical_children)
@@ -343,6 +350,7 @@ async fn init_all_tables() -> Result<(), sea_orm::error::DbErr> {
 
     let physical_children = physical_children::ActiveModel {
         physical_expression_id: Set(1),
+        child_index: Set(0),
         group_id: Set(1),
     };
     physical_children::Entity::insert(physical_children)
@@ -384,6 +392,8 @@ async fn init_all_tables() -> Result<(), sea_orm::error::DbErr> {
         physical_expression_id: Set(1),
         cost_id: Set(1),
         epoch_id: Set(1),
+        // The default fingerprint is the one of the empty set of required properties.
+        properties_fingerprint: NotSet,
     };
     group_winner::Entity::insert(group_winner)
         .exec(&db)
@@ -18,6 +18,7 @@ pub type ExprId = i32;
 pub type EpochId = i32;
 pub type StatId = i32;
 pub type AttrIndex = i32;
+pub type IndexId = i32;
 
 /// TODO: documentation
 pub enum CatalogSource {
@@ -36,6 +37,8 @@ pub enum AttrType {
 }
 
 /// TODO: documentation
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, IntoPrimitive, TryFromPrimitive)]
 pub enum IndexType {
     BTree,
     Hash,
@@ -66,6 +69,11 @@ pub enum StatType {
     MostCommonValues,
     /// The distribution of values in a column.
     Distribution,
+    /// A mergeable sketch of the distinct values in a column, used to combine the number of
+    /// distinct values across epochs.
+    DistinctSketch,
+    /// The average width in bytes of the non-null values in a column.
+    AvgWidth,
 }
 
 /// TODO: documentation
@@ -103,11 +111,27 @@ pub struct Attr {
     pub nullable: bool,
 }
 
+/// An index of a table, as stored in `index_metadata`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexInfo {
+    pub id: IndexId,
+    pub table_id: TableId,
+    pub name: String,
+    pub index_type: IndexType,
+    pub is_unique: bool,
+    pub is_clustered: bool,
+    /// The base indices of the indexed attributes, in the order of the index key.
+    pub attr_base_indices: Vec<AttrIndex>,
+}
+
 /// TODO: documentation
 #[trait_variant::make(Send)]
 pub trait CostModelStorageLayer {
     async fn create_new_epoch(&self, source: String, data: String) -> StorageResult<EpochId>;
 
+    /// Get the latest epoch, or None if no epoch has been created yet.
+    async fn get_latest_epoch_id(&self) -> StorageResult<Option<EpochId>>;
+
     async fn update_stats_from_catalog(&self, c: CatalogSource) -> StorageResult<EpochId>;
 
     async fn update_stats(
@@ -116,6 +140,13 @@ pub trait CostModelStorageLayer {
         epoch_option: EpochOption,
     ) -> StorageResult<Option<EpochId>>;
 
+    async fn update_stats_batch(
+        &self,
+        stats: Vec<Stat>,
+        source: String,
+        data: String,
+    ) -> StorageResult<Option<EpochId>>;
+
     async fn store_cost(
         &self,
         expr_id: ExprId,
@@ -174,4 +205,7 @@ pub trait CostModelStorageLayer {
         table_id: TableId,
         attribute_base_index: AttrIndex,
     ) -> StorageResult<Option<Attr>>;
+
+    /// Get an index and the base indices of the attributes it covers.
+    async fn get_index(&self, index_id: IndexId) -> StorageResult<Option<IndexInfo>>;
 }
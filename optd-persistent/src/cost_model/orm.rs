@@ -1,5 +1,7 @@
 #![allow(dead_code, unused_imports, unused_variables)]
 
+use std::collections::HashMap;
+
 use crate::cost_model::interface::Cost;
 use crate::entities::{prelude::*, *};
 use crate::{BackendError, BackendManager, CostModelStorageLayer, StorageResult};
@@ -7,15 +9,16 @@ use sea_orm::prelude::{Expr, Json};
 use sea_orm::sea_query::{ExprTrait, Query};
 use sea_orm::{sqlx::types::chrono::Utc, EntityTrait};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DbBackend, DbErr, DeleteResult, EntityOrSelect,
-    ModelTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, RuntimeErr, TransactionTrait,
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseTransaction, DbBackend, DbErr, DeleteResult,
+    EntityOrSelect, ModelTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, RuntimeErr,
+    TransactionTrait,
 };
 use serde_json::json;
 
 use super::catalog::mock_catalog::{self, MockCatalog};
 use super::interface::{
-    Attr, AttrId, AttrIndex, AttrType, CatalogSource, EpochId, EpochOption, ExprId, Stat, StatId,
-    StatType, TableId,
+    Attr, AttrId, AttrIndex, AttrType, CatalogSource, EpochId, EpochOption, ExprId, IndexId,
+    IndexInfo, IndexType, Stat, StatId, StatType, TableId,
 };
 
 impl BackendManager {
@@ -30,6 +33,157 @@ impl BackendManager {
             .collect::<Vec<String>>()
             .join(",")
     }
+
+    /// Update one statistic within `transaction`. See `update_stats` for the semantics; the
+    /// caller is responsible for committing the transaction.
+    async fn update_stats_in_txn(
+        &self,
+        transaction: &DatabaseTransaction,
+        stat: Stat,
+        epoch_option: EpochOption,
+    ) -> StorageResult<Option<EpochId>> {
+        // 0. Check if the stat already exists. If exists, get stat_id, else insert into statistic table.
+        let stat_id = match stat.table_id {
+            Some(table_id) => {
+                // TODO: only select needed fields
+                let res = Statistic::find()
+                    .filter(statistic::Column::TableId.eq(table_id))
+                    .filter(statistic::Column::VariantTag.eq(stat.stat_type as i32))
+                    .inner_join(versioned_statistic::Entity)
+                    .select_also(versioned_statistic::Entity)
+                    .order_by_desc(versioned_statistic::Column::EpochId)
+                    .one(transaction)
+                    .await?;
+                match res {
+                    Some(stat_data) => {
+                        if stat_data.1.unwrap().statistic_value == stat.stat_value {
+                            return Ok(None);
+                        }
+                        stat_data.0.id
+                    }
+                    None => {
+                        let new_stat = statistic::ActiveModel {
+                            name: sea_orm::ActiveValue::Set(stat.name.clone()),
+                            table_id: sea_orm::ActiveValue::Set(Some(table_id)),
+                            number_of_attributes: sea_orm::ActiveValue::Set(
+                                stat.attr_ids.len() as i32
+                            ),
+                            creation_time: sea_orm::ActiveValue::Set(Utc::now()),
+                            variant_tag: sea_orm::ActiveValue::Set(stat.stat_type as i32),
+                            description: sea_orm::ActiveValue::Set("".to_string()),
+                            ..Default::default()
+                        };
+                        let res = Statistic::insert(new_stat).exec(transaction).await;
+                        match res {
+                            Ok(insert_res) => insert_res.last_insert_id,
+                            Err(_) => {
+                                return Err(BackendError::CostModel(
+                                    format!(
+                                        "failed to insert statistic {:?} into statistic table",
+                                        stat
+                                    )
+                                    .into(),
+                                ))
+                            }
+                        }
+                    }
+                }
+            }
+            None => {
+                let description = self.get_description_from_attr_ids(stat.attr_ids.clone());
+                let res = Statistic::find()
+                    .filter(statistic::Column::NumberOfAttributes.eq(stat.attr_ids.len() as i32))
+                    .filter(statistic::Column::Description.eq(description.clone()))
+                    .filter(statistic::Column::VariantTag.eq(stat.stat_type as i32))
+                    .inner_join(versioned_statistic::Entity)
+                    .select_also(versioned_statistic::Entity)
+                    .order_by_desc(versioned_statistic::Column::EpochId)
+                    .one(transaction)
+                    .await?;
+                match res {
+                    Some(stat_data) => {
+                        if stat_data.1.unwrap().statistic_value == stat.stat_value {
+                            return Ok(None);
+                        }
+                        stat_data.0.id
+                    }
+                    None => {
+                        let new_stat = statistic::ActiveModel {
+                            name: sea_orm::ActiveValue::Set(stat.name.clone()),
+                            number_of_attributes: sea_orm::ActiveValue::Set(
+                                stat.attr_ids.len() as i32
+                            ),
+                            creation_time: sea_orm::ActiveValue::Set(Utc::now()),
+                            variant_tag: sea_orm::ActiveValue::Set(stat.stat_type as i32),
+                            description: sea_orm::ActiveValue::Set(description),
+                            ..Default::default()
+                        };
+                        // TODO(lanlou): we should not clone here maybe...
+                        let insert_res = Statistic::insert(new_stat.clone())
+                            .exec(transaction)
+                            .await?;
+                        for attr_id in stat.attr_ids {
+                            let new_junction = statistic_to_attribute_junction::ActiveModel {
+                                statistic_id: sea_orm::ActiveValue::Set(insert_res.last_insert_id),
+                                attribute_id: sea_orm::ActiveValue::Set(attr_id),
+                            };
+                            let res = StatisticToAttributeJunction::insert(new_junction)
+                                .exec(transaction)
+                                .await?;
+                        }
+                        insert_res.last_insert_id
+                    }
+                }
+            }
+        };
+        // 1. Insert into attr_stats and related junction tables.
+        let epoch_id = match epoch_option {
+            EpochOption::Existed(e) => e,
+            EpochOption::New(source, data) => {
+                let new_event = event::ActiveModel {
+                    source_variant: sea_orm::ActiveValue::Set(source),
+                    timestamp: sea_orm::ActiveValue::Set(Utc::now()),
+                    data: sea_orm::ActiveValue::Set(sea_orm::JsonValue::String(data)),
+                    ..Default::default()
+                };
+                let insert_res = Event::insert(new_event).exec(transaction).await?;
+                insert_res.last_insert_id
+            }
+        };
+        let new_stats = versioned_statistic::ActiveModel {
+            epoch_id: sea_orm::ActiveValue::Set(epoch_id),
+            statistic_id: sea_orm::ActiveValue::Set(stat_id),
+            statistic_value: sea_orm::ActiveValue::Set(stat.stat_value),
+            ..Default::default()
+        };
+        let _ = VersionedStatistic::insert(new_stats)
+            .exec(transaction)
+            .await?;
+
+        // 2. Invalidate all the related cost.
+        let _ = plan_cost::Entity::update_many()
+            .col_expr(plan_cost::Column::IsValid, Expr::value(false))
+            .filter(plan_cost::Column::IsValid.eq(true))
+            .filter(plan_cost::Column::EpochId.lt(epoch_id))
+            .filter(
+                plan_cost::Column::PhysicalExpressionId.in_subquery(
+                    Query::select()
+                        .column(
+                            physical_expression_to_statistic_junction::Column::PhysicalExpressionId,
+                        )
+                        .from(physical_expression_to_statistic_junction::Entity)
+                        .cond_where(
+                            physical_expression_to_statistic_junction::Column::StatisticId
+                                .eq(stat_id),
+                        )
+                        .to_owned(),
+                ),
+            )
+            .exec(transaction)
+            .await?;
+
+        Ok(Some(epoch_id))
+    }
 }
 
 impl CostModelStorageLayer for BackendManager {
@@ -45,6 +199,14 @@ impl CostModelStorageLayer for BackendManager {
         Ok(insert_res.last_insert_id)
     }
 
+    async fn get_latest_epoch_id(&self) -> StorageResult<Option<EpochId>> {
+        Ok(Event::find()
+            .order_by_desc(event::Column::EpochId)
+            .one(&self.db)
+            .await?
+            .map(|event| event.epoch_id))
+    }
+
     /// TODO: documentation
     async fn update_stats_from_catalog(&self, c: CatalogSource) -> StorageResult<EpochId> {
         let transaction = self.db.begin().await?;
@@ -205,147 +367,40 @@ impl CostModelStorageLayer for BackendManager {
         epoch_option: EpochOption,
     ) -> StorageResult<Option<EpochId>> {
         let transaction = self.db.begin().await?;
-        // 0. Check if the stat already exists. If exists, get stat_id, else insert into statistic table.
-        let stat_id = match stat.table_id {
-            Some(table_id) => {
-                // TODO: only select needed fields
-                let res = Statistic::find()
-                    .filter(statistic::Column::TableId.eq(table_id))
-                    .inner_join(versioned_statistic::Entity)
-                    .select_also(versioned_statistic::Entity)
-                    .order_by_desc(versioned_statistic::Column::EpochId)
-                    .one(&transaction)
-                    .await?;
-                match res {
-                    Some(stat_data) => {
-                        if stat_data.1.unwrap().statistic_value == stat.stat_value {
-                            return Ok(None);
-                        }
-                        stat_data.0.id
-                    }
-                    None => {
-                        let new_stat = statistic::ActiveModel {
-                            name: sea_orm::ActiveValue::Set(stat.name.clone()),
-                            table_id: sea_orm::ActiveValue::Set(Some(table_id)),
-                            number_of_attributes: sea_orm::ActiveValue::Set(
-                                stat.attr_ids.len() as i32
-                            ),
-                            creation_time: sea_orm::ActiveValue::Set(Utc::now()),
-                            variant_tag: sea_orm::ActiveValue::Set(stat.stat_type as i32),
-                            description: sea_orm::ActiveValue::Set("".to_string()),
-                            ..Default::default()
-                        };
-                        let res = Statistic::insert(new_stat).exec(&transaction).await;
-                        match res {
-                            Ok(insert_res) => insert_res.last_insert_id,
-                            Err(_) => {
-                                return Err(BackendError::CostModel(
-                                    format!(
-                                        "failed to insert statistic {:?} into statistic table",
-                                        stat
-                                    )
-                                    .into(),
-                                ))
-                            }
-                        }
-                    }
-                }
-            }
-            None => {
-                let description = self.get_description_from_attr_ids(stat.attr_ids.clone());
-                let res = Statistic::find()
-                    .filter(statistic::Column::NumberOfAttributes.eq(stat.attr_ids.len() as i32))
-                    .filter(statistic::Column::Description.eq(description.clone()))
-                    .filter(statistic::Column::VariantTag.eq(stat.stat_type as i32))
-                    .inner_join(versioned_statistic::Entity)
-                    .select_also(versioned_statistic::Entity)
-                    .order_by_desc(versioned_statistic::Column::EpochId)
-                    .one(&transaction)
-                    .await?;
-                match res {
-                    Some(stat_data) => {
-                        if stat_data.1.unwrap().statistic_value == stat.stat_value {
-                            return Ok(None);
-                        }
-                        stat_data.0.id
-                    }
-                    None => {
-                        let new_stat = statistic::ActiveModel {
-                            name: sea_orm::ActiveValue::Set(stat.name.clone()),
-                            number_of_attributes: sea_orm::ActiveValue::Set(
-                                stat.attr_ids.len() as i32
-                            ),
-                            creation_time: sea_orm::ActiveValue::Set(Utc::now()),
-                            variant_tag: sea_orm::ActiveValue::Set(stat.stat_type as i32),
-                            description: sea_orm::ActiveValue::Set(description),
-                            ..Default::default()
-                        };
-                        // TODO(lanlou): we should not clone here maybe...
-                        let insert_res = Statistic::insert(new_stat.clone())
-                            .exec(&transaction)
-                            .await?;
-                        for attr_id in stat.attr_ids {
-                            let new_junction = statistic_to_attribute_junction::ActiveModel {
-                                statistic_id: sea_orm::ActiveValue::Set(insert_res.last_insert_id),
-                                attribute_id: sea_orm::ActiveValue::Set(attr_id),
-                            };
-                            let res = StatisticToAttributeJunction::insert(new_junction)
-                                .exec(&transaction)
-                                .await?;
-                        }
-                        insert_res.last_insert_id
-                    }
-                }
-            }
-        };
-        // 1. Insert into attr_stats and related junction tables.
-        let epoch_id = match epoch_option {
-            EpochOption::Existed(e) => e,
-            EpochOption::New(source, data) => {
-                let new_event = event::ActiveModel {
-                    source_variant: sea_orm::ActiveValue::Set(source),
-                    timestamp: sea_orm::ActiveValue::Set(Utc::now()),
-                    data: sea_orm::ActiveValue::Set(sea_orm::JsonValue::String(data)),
-                    ..Default::default()
-                };
-                let insert_res = Event::insert(new_event).exec(&transaction).await?;
-                insert_res.last_insert_id
-            }
-        };
-        let new_stats = versioned_statistic::ActiveModel {
-            epoch_id: sea_orm::ActiveValue::Set(epoch_id),
-            statistic_id: sea_orm::ActiveValue::Set(stat_id),
-            statistic_value: sea_orm::ActiveValue::Set(stat.stat_value),
-            ..Default::default()
-        };
-        let _ = VersionedStatistic::insert(new_stats)
-            .exec(&transaction)
-            .await?;
-
-        // 2. Invalidate all the related cost.
-        let _ = plan_cost::Entity::update_many()
-            .col_expr(plan_cost::Column::IsValid, Expr::value(false))
-            .filter(plan_cost::Column::IsValid.eq(true))
-            .filter(plan_cost::Column::EpochId.lt(epoch_id))
-            .filter(
-                plan_cost::Column::PhysicalExpressionId.in_subquery(
-                    Query::select()
-                        .column(
-                            physical_expression_to_statistic_junction::Column::PhysicalExpressionId,
-                        )
-                        .from(physical_expression_to_statistic_junction::Entity)
-                        .cond_where(
-                            physical_expression_to_statistic_junction::Column::StatisticId
-                                .eq(stat_id),
-                        )
-                        .to_owned(),
-                ),
-            )
-            .exec(&transaction)
+        let epoch_id = self
+            .update_stats_in_txn(&transaction, stat, epoch_option)
             .await?;
+        transaction.commit().await?;
+        Ok(epoch_id)
+    }
 
+    /// Update a batch of statistics in a single transaction.
+    ///
+    /// The first statistic that actually changes creates a new epoch with the given source and
+    /// data, and the rest of the batch is attached to it. Either all the statistics are written
+    /// or, if any of them fails, none of them is. Returns None if no statistic changed.
+    async fn update_stats_batch(
+        &self,
+        stats: Vec<Stat>,
+        source: String,
+        data: String,
+    ) -> StorageResult<Option<EpochId>> {
+        let transaction = self.db.begin().await?;
+        let mut epoch_id = None;
+        for stat in stats {
+            let epoch_option = match epoch_id {
+                Some(id) => EpochOption::Existed(id),
+                None => EpochOption::New(source.clone(), data.clone()),
+            };
+            if let Some(new_epoch_id) = self
+                .update_stats_in_txn(&transaction, stat, epoch_option)
+                .await?
+            {
+                epoch_id = Some(new_epoch_id);
+            }
+        }
         transaction.commit().await?;
-        Ok(Some(epoch_id))
+        Ok(epoch_id)
     }
 
     /// TODO: documentation
@@ -548,15 +603,15 @@ impl CostModelStorageLayer for BackendManager {
             ));
         }
         // Check if epoch_id exists in Event table
-        if epoch_id.is_some() {
+        if let Some(epoch_id) = epoch_id {
             let epoch_exists = Event::find()
-                .filter(event::Column::EpochId.eq(epoch_id.unwrap()))
+                .filter(event::Column::EpochId.eq(epoch_id))
                 .one(&self.db)
                 .await
                 .unwrap();
             if epoch_exists.is_none() {
                 return Err(BackendError::CostModel(
-                    format!("epoch id {} not found when storing cost", epoch_id.unwrap()).into(),
+                    format!("epoch id {} not found when storing cost", epoch_id).into(),
                 ));
             }
         }
@@ -607,8 +662,8 @@ impl CostModelStorageLayer for BackendManager {
             }
         }
 
-        if existed_cost.is_some() {
-            let mut new_cost: plan_cost::ActiveModel = existed_cost.unwrap().into();
+        if let Some(existed_cost) = existed_cost {
+            let mut new_cost: plan_cost::ActiveModel = existed_cost.into();
             let mut update = false;
             if cost.is_some() {
                 let input_cost = sea_orm::ActiveValue::Set(Some(json!({
@@ -689,6 +744,65 @@ impl CostModelStorageLayer for BackendManager {
             None => Ok(None),
         }
     }
+
+    /// The attributes of an index are stored as the description of the index: their ids
+    /// separated by commas, in the order of the index key. Unlike the attributes of statistics,
+    /// they are not sorted.
+    async fn get_index(&self, index_id: IndexId) -> StorageResult<Option<IndexInfo>> {
+        let Some(index) = IndexMetadata::find_by_id(index_id).one(&self.db).await? else {
+            return Ok(None);
+        };
+        let index_type = IndexType::try_from(index.variant_tag).map_err(|_| {
+            BackendError::BackendError(format!(
+                "Failed to convert variant tag {} to IndexType",
+                index.variant_tag
+            ))
+        })?;
+        let attr_ids = index
+            .description
+            .split(',')
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse::<AttrId>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                BackendError::BackendError(format!(
+                    "Invalid attributes {:?} of index {}",
+                    index.description, index_id
+                ))
+            })?;
+        if attr_ids.is_empty() {
+            return Err(BackendError::BackendError(format!(
+                "Index {} has no attributes",
+                index_id
+            )));
+        }
+        let base_attr_numbers: HashMap<AttrId, AttrIndex> = Attribute::find()
+            .filter(attribute::Column::Id.is_in(attr_ids.clone()))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|attr| (attr.id, attr.base_attribute_number))
+            .collect();
+        let attr_base_indices = attr_ids
+            .iter()
+            .map(|attr_id| base_attr_numbers.get(attr_id).copied())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                BackendError::BackendError(format!(
+                    "Some attributes of index {} do not exist",
+                    index_id
+                ))
+            })?;
+        Ok(Some(IndexInfo {
+            id: index.id,
+            table_id: index.table_id,
+            name: index.name,
+            index_type,
+            is_unique: index.is_unique,
+            is_clustered: index.is_clustered,
+            attr_base_indices,
+        }))
+    }
 }
 
 // TODO: add integration tests
@@ -746,12 +860,17 @@ mod tests {
         let database_url = run_migration(DATABASE_FILE).await;
         let mut binding = super::BackendManager::new(Some(&database_url)).await;
         let backend_manager = binding.as_mut().unwrap();
+        assert_eq!(backend_manager.get_latest_epoch_id().await.unwrap(), None);
         let res = backend_manager
             .create_new_epoch("source".to_string(), "data".to_string())
             .await;
         println!("{:?}", res);
         assert!(res.is_ok());
         let inserted_id = res.unwrap();
+        assert_eq!(
+            backend_manager.get_latest_epoch_id().await.unwrap(),
+            Some(inserted_id)
+        );
         let lookup_res = Event::find_by_id(inserted_id)
             .all(&backend_manager.db)
             .await
@@ -1062,6 +1181,56 @@ mod tests {
         remove_db_file(DATABASE_FILE);
     }
 
+    #[tokio::test]
+    async fn test_update_stats_batch_is_atomic() {
+        const DATABASE_FILE: &str = "test_update_stats_batch_is_atomic.db";
+        let database_url = copy_init_db(DATABASE_FILE).await;
+        let mut binding = super::BackendManager::new(Some(&database_url)).await;
+        let backend_manager = binding.as_mut().unwrap();
+        let event_cnt = Event::find().all(&backend_manager.db).await.unwrap().len();
+        let row_cnt = backend_manager
+            .get_stats_for_table(1, StatType::TableRowCount, None)
+            .await
+            .unwrap();
+
+        // The second statistic refers to a table that does not exist, so the whole batch fails.
+        let statistics = vec![
+            Stat {
+                stat_type: StatType::TableRowCount,
+                stat_value: json!(42),
+                attr_ids: vec![],
+                table_id: Some(1),
+                name: "row_count".to_string(),
+            },
+            Stat {
+                stat_type: StatType::TableRowCount,
+                stat_value: json!(42),
+                attr_ids: vec![],
+                table_id: Some(9999),
+                name: "row_count".to_string(),
+            },
+        ];
+        let res = backend_manager
+            .update_stats_batch(statistics, "source".to_string(), "data".to_string())
+            .await;
+        assert!(res.is_err());
+
+        // Neither the epoch nor the first statistic were written.
+        assert_eq!(
+            Event::find().all(&backend_manager.db).await.unwrap().len(),
+            event_cnt
+        );
+        assert_eq!(
+            backend_manager
+                .get_stats_for_table(1, StatType::TableRowCount, None)
+                .await
+                .unwrap(),
+            row_cnt
+        );
+
+        remove_db_file(DATABASE_FILE);
+    }
+
     #[tokio::test]
     async fn test_store_cost() {
         const DATABASE_FILE: &str = "test_store_cost.db";
@@ -1475,4 +1644,70 @@ mod tests {
 
         remove_db_file(DATABASE_FILE);
     }
+
+    #[tokio::test]
+    async fn test_get_index() {
+        const DATABASE_FILE: &str = "test_get_index.db";
+        let database_url = copy_init_db(DATABASE_FILE).await;
+        let mut binding = super::BackendManager::new(Some(&database_url)).await;
+        let backend_manager = binding.as_mut().unwrap();
+
+        // The index on `user_id`, the first attribute of table 1.
+        let index = backend_manager.get_index(1).await.unwrap().unwrap();
+        assert_eq!(
+            index,
+            super::IndexInfo {
+                id: 1,
+                table_id: 1,
+                name: "user_id_index".to_string(),
+                index_type: super::IndexType::Hash,
+                is_unique: true,
+                is_clustered: false,
+                attr_base_indices: vec![0],
+            }
+        );
+
+        assert!(backend_manager.get_index(2).await.unwrap().is_none());
+
+        // The attributes are in the order of the index key, not of their ids.
+        IndexMetadata::insert(index_metadata::ActiveModel {
+            id: sea_orm::ActiveValue::Set(2),
+            table_id: sea_orm::ActiveValue::Set(1),
+            name: sea_orm::ActiveValue::Set("username_user_id_index".to_string()),
+            number_of_attributes: sea_orm::ActiveValue::Set(2),
+            variant_tag: sea_orm::ActiveValue::Set(super::IndexType::BTree as i32),
+            is_unique: sea_orm::ActiveValue::Set(false),
+            nulls_not_distinct: sea_orm::ActiveValue::Set(false),
+            is_primary: sea_orm::ActiveValue::Set(false),
+            is_clustered: sea_orm::ActiveValue::Set(false),
+            is_exclusion: sea_orm::ActiveValue::Set(false),
+            description: sea_orm::ActiveValue::Set("2,1".to_string()),
+        })
+        .exec(&backend_manager.db)
+        .await
+        .unwrap();
+        let index = backend_manager.get_index(2).await.unwrap().unwrap();
+        assert_eq!(index.attr_base_indices, vec![1, 0]);
+
+        // An index without attributes is rejected.
+        IndexMetadata::insert(index_metadata::ActiveModel {
+            id: sea_orm::ActiveValue::Set(3),
+            table_id: sea_orm::ActiveValue::Set(1),
+            name: sea_orm::ActiveValue::Set("empty_index".to_string()),
+            number_of_attributes: sea_orm::ActiveValue::Set(0),
+            variant_tag: sea_orm::ActiveValue::Set(super::IndexType::Hash as i32),
+            is_unique: sea_orm::ActiveValue::Set(true),
+            nulls_not_distinct: sea_orm::ActiveValue::Set(false),
+            is_primary: sea_orm::ActiveValue::Set(false),
+            is_clustered: sea_orm::ActiveValue::Set(false),
+            is_exclusion: sea_orm::ActiveValue::Set(false),
+            description: sea_orm::ActiveValue::Set("".to_string()),
+        })
+        .exec(&backend_manager.db)
+        .await
+        .unwrap();
+        assert!(backend_manager.get_index(3).await.is_err());
+
+        remove_db_file(DATABASE_FILE);
+    }
 }
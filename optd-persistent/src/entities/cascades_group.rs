@@ -10,6 +10,7 @@ pub struct Model {
     pub latest_winner: Option<i32>,
     pub in_progress: bool,
     pub is_optimized: bool,
+    pub parent_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,6 +33,14 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     PhysicalExpression,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::ParentId",
+        to = "Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    SelfRef,
 }
 
 impl Related<super::group_winner::Entity> for Entity {
@@ -11,6 +11,7 @@ pub struct Model {
     pub physical_expression_id: i32,
     pub cost_id: i32,
     pub epoch_id: i32,
+    pub properties_fingerprint: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -39,6 +40,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     PhysicalExpression,
+    #[sea_orm(has_many = "super::physical_property::Entity")]
+    PhysicalProperty,
     #[sea_orm(
         belongs_to = "super::plan_cost::Entity",
         from = "Column::CostId",
@@ -67,6 +70,12 @@ impl Related<super::physical_expression::Entity> for Entity {
     }
 }
 
+impl Related<super::physical_property::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PhysicalProperty.def()
+    }
+}
+
 impl Related<super::plan_cost::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PlanCost.def()
@@ -8,6 +8,7 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub logical_expression_id: i32,
     #[sea_orm(primary_key, auto_increment = false)]
+    pub child_index: i32,
     pub group_id: i32,
 }
 
@@ -23,7 +24,7 @@ pub enum Relation {
     CascadesGroup,
     #[sea_orm(
         belongs_to = "super::logical_expression::Entity",
-        from = "Column::GroupId",
+        from = "Column::LogicalExpressionId",
         to = "super::logical_expression::Column::Id",
         on_update = "Cascade",
         on_delete = "Cascade"
@@ -8,6 +8,7 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub physical_expression_id: i32,
     #[sea_orm(primary_key, auto_increment = false)]
+    pub child_index: i32,
     pub group_id: i32,
 }
 
@@ -8,12 +8,21 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub physical_expression_id: i32,
+    pub group_winner_id: Option<i32>,
     pub variant_tag: i16,
     pub data: Json,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::group_winner::Entity",
+        from = "Column::GroupWinnerId",
+        to = "super::group_winner::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    GroupWinner,
     #[sea_orm(
         belongs_to = "super::physical_expression::Entity",
         from = "Column::PhysicalExpressionId",
@@ -24,6 +33,12 @@ pub enum Relation {
     PhysicalExpression,
 }
 
+impl Related<super::group_winner::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GroupWinner.def()
+    }
+}
+
 impl Related<super::physical_expression::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PhysicalExpression.def()
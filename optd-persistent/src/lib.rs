@@ -1,6 +1,9 @@
 #![allow(dead_code)]
 
-use std::sync::LazyLock;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    LazyLock,
+};
 
 use sea_orm::*;
 use sea_orm_migration::prelude::*;
@@ -14,6 +17,8 @@ pub mod cost_model;
 pub use cost_model::interface::CostModelStorageLayer;
 
 mod memo;
+pub use memo::expression::{fingerprint, FINGERPRINT_VERSION};
+pub use memo::in_memory::InMemoryMemo;
 pub use memo::interface::Memo;
 
 /// The filename of the SQLite database for migration.
@@ -58,6 +63,7 @@ pub enum MemoError {
     UnknownLogicalExpression,
     UnknownPhysicalExpression,
     InvalidExpression,
+    InvalidExport,
 }
 
 /// TODO convert this to `thiserror`
@@ -98,13 +104,18 @@ pub type StorageResult<T> = Result<T, BackendError>;
 
 pub struct BackendManager {
     db: DatabaseConnection,
+    /// A unique ID of the backend manager, which identifies the backend that an [`InMemoryMemo`]
+    /// was imported from.
+    id: u64,
 }
 
 impl BackendManager {
     /// Creates a new `BackendManager`.
     pub async fn new(database_url: Option<&str>) -> StorageResult<Self> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
         Ok(Self {
             db: Database::connect(database_url.unwrap_or(DATABASE_URL)).await?,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
         })
     }
 }
@@ -9,10 +9,7 @@ use sea_orm::*;
 use sea_orm_migration::prelude::*;
 use serde_json::json;
 
-mod entities;
-mod migrator;
-
-use entities::{prelude::*, *};
+use optd_persistent::entities::{prelude::*, *};
 use optd_persistent::DATABASE_URL;
 
 #[tokio::main]
@@ -61,6 +58,7 @@ async fn basic_demo() {
     let _link = logical_children::ActiveModel {
         group_id: group.id.clone(),
         logical_expression_id: l_expr.id.clone(),
+        child_index: ActiveValue::Set(0),
     }
     .insert(&db)
     .await
@@ -0,0 +1,620 @@
+//! A conformance test suite that every implementation of the [`Memo`] trait must pass, so that
+//! they all have the same semantics.
+//!
+//! Each test is a function that takes an empty memo table. The tests are run against an
+//! implementation with [`memo_conformance_tests`], given an async function that creates an empty
+//! memo table, runs a test on it, and cleans up after it. An implementation also implements
+//! [`TestMemo`] to provide the costs that the winners refer to.
+
+use serde_json::json;
+
+use super::expression::{LogicalOperator, PhysicalOperator, PhysicalPropertyType};
+use crate::{entities::*, BackendError, Memo, MemoError};
+
+/// A [`Memo`] that stores the entities of the persistent memo table.
+pub(crate) trait TestMemo:
+    Memo<
+    Group = cascades_group::Model,
+    GroupId = i32,
+    LogicalExpression = logical_expression::Model,
+    LogicalExpressionId = i32,
+    PhysicalExpression = physical_expression::Model,
+    PhysicalExpressionId = i32,
+    PhysicalProperty = physical_property::Model,
+    GroupWinner = group_winner::Model,
+>
+{
+    /// Records a new cost of a physical expression in a new epoch, and returns a winner with the
+    /// expression, the cost and the epoch.
+    async fn new_winner(&self, physical_expression_id: i32) -> group_winner::Model;
+}
+
+/// Generates a `#[tokio::test]` for each test of the conformance suite, which runs it with
+/// `$with_memo(test_name, test)`.
+macro_rules! memo_conformance_tests {
+    ($with_memo:path) => {
+        $crate::memo::conformance::memo_conformance_tests!(
+            @tests $with_memo;
+            test_add_logical_expression_with_children,
+            test_add_expressions_to_group,
+            test_update_group_winner,
+            test_merge_groups,
+            test_merge_group_winners,
+            test_add_existing_expression_merges_groups
+        );
+    };
+    (@tests $with_memo:path; $($test:ident),+) => {
+        $(
+            #[tokio::test]
+            async fn $test() {
+                $with_memo(stringify!($test), $crate::memo::conformance::$test).await;
+            }
+        )+
+    };
+}
+
+pub(crate) use memo_conformance_tests;
+
+pub(crate) fn scan(table: &str) -> logical_expression::Model {
+    logical_expression::Model::new(LogicalOperator::Scan, json!({ "table": table }))
+}
+
+pub(crate) fn join() -> logical_expression::Model {
+    logical_expression::Model::new(LogicalOperator::Join, json!({ "type": "inner" }))
+}
+
+pub(crate) fn sorted_on(attr: &str) -> physical_property::Model {
+    physical_property::Model::new(PhysicalPropertyType::SortOrder, json!([attr]))
+}
+
+pub(crate) fn hash_join(group_id: i32) -> physical_expression::Model {
+    let mut expr =
+        physical_expression::Model::new(PhysicalOperator::HashJoin, json!({ "type": "inner" }));
+    expr.group_id = group_id;
+    expr
+}
+
+pub(crate) async fn test_add_logical_expression_with_children<M: TestMemo>(memo: M) {
+    let (group_a, scan_a) = memo
+        .add_logical_expression(scan("a"), vec![])
+        .await
+        .unwrap();
+    let (group_b, _) = memo
+        .add_logical_expression(scan("b"), vec![])
+        .await
+        .unwrap();
+    assert_ne!(group_a, group_b);
+    assert_eq!(
+        memo.add_logical_expression(scan("a"), vec![])
+            .await
+            .unwrap(),
+        (group_a, scan_a)
+    );
+
+    let (join_ab_group, join_ab) = memo
+        .add_logical_expression(join(), vec![group_a, group_b])
+        .await
+        .unwrap();
+    assert_eq!(
+        memo.get_logical_children(join_ab).await.unwrap(),
+        vec![group_a, group_b]
+    );
+    assert_eq!(
+        memo.add_logical_expression(join(), vec![group_a, group_b])
+            .await
+            .unwrap(),
+        (join_ab_group, join_ab)
+    );
+
+    // The same operator and data on other inputs is another expression.
+    let (join_ba_group, join_ba) = memo
+        .add_logical_expression(join(), vec![group_b, group_a])
+        .await
+        .unwrap();
+    assert_ne!(join_ba_group, join_ab_group);
+    assert_eq!(
+        memo.get_logical_children(join_ba).await.unwrap(),
+        vec![group_b, group_a]
+    );
+
+    // A group may be a child more than once.
+    let (_, self_join) = memo
+        .add_logical_expression(join(), vec![group_a, group_a])
+        .await
+        .unwrap();
+    assert_eq!(
+        memo.get_logical_children(self_join).await.unwrap(),
+        vec![group_a, group_a]
+    );
+
+    assert!(matches!(
+        memo.add_logical_expression(join(), vec![group_a, 42]).await,
+        Err(BackendError::Memo(MemoError::UnknownGroup))
+    ));
+    assert!(matches!(
+        memo.get_logical_children(42).await,
+        Err(BackendError::Memo(MemoError::UnknownLogicalExpression))
+    ));
+}
+
+pub(crate) async fn test_add_expressions_to_group<M: TestMemo>(memo: M) {
+    let (group_a, _) = memo
+        .add_logical_expression(scan("a"), vec![])
+        .await
+        .unwrap();
+    let (group_b, _) = memo
+        .add_logical_expression(scan("b"), vec![])
+        .await
+        .unwrap();
+    let (join_group, join_ab) = memo
+        .add_logical_expression(join(), vec![group_a, group_b])
+        .await
+        .unwrap();
+
+    // Add the commuted join to the group of the join.
+    let mut join_ba = join();
+    join_ba.group_id = join_group;
+    let join_ba_id = memo
+        .add_logical_expression_to_group(join_group, join_ba.clone(), vec![group_b, group_a])
+        .await
+        .unwrap();
+    assert_eq!(
+        memo.add_logical_expression_to_group(join_group, join_ba, vec![group_b, group_a])
+            .await
+            .unwrap(),
+        join_ba_id
+    );
+    let mut group_exprs = memo
+        .get_group_logical_expressions(join_group)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|expr| expr.id)
+        .collect::<Vec<_>>();
+    group_exprs.sort();
+    assert_eq!(group_exprs, vec![join_ab, join_ba_id]);
+
+    let hash_join = hash_join(join_group);
+    let hash_join_id = memo
+        .add_physical_expression_to_group(join_group, hash_join.clone(), vec![group_a, group_b])
+        .await
+        .unwrap();
+    assert_eq!(
+        memo.add_physical_expression_to_group(
+            join_group,
+            hash_join.clone(),
+            vec![group_a, group_b]
+        )
+        .await
+        .unwrap(),
+        hash_join_id
+    );
+    assert_eq!(
+        memo.get_physical_children(hash_join_id).await.unwrap(),
+        vec![group_a, group_b]
+    );
+    assert_eq!(
+        memo.get_group_physical_expressions(join_group)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    assert!(matches!(
+        memo.add_physical_expression_to_group(join_group, hash_join.clone(), vec![42])
+            .await,
+        Err(BackendError::Memo(MemoError::UnknownGroup))
+    ));
+    assert!(matches!(
+        memo.add_physical_expression_to_group(group_a, hash_join, vec![])
+            .await,
+        Err(BackendError::Memo(MemoError::InvalidExpression))
+    ));
+}
+
+pub(crate) async fn test_update_group_winner<M: TestMemo>(memo: M) {
+    let (group_a, _) = memo
+        .add_logical_expression(scan("a"), vec![])
+        .await
+        .unwrap();
+    let (group_b, _) = memo
+        .add_logical_expression(scan("b"), vec![])
+        .await
+        .unwrap();
+    let (join_group, _) = memo
+        .add_logical_expression(join(), vec![group_a, group_b])
+        .await
+        .unwrap();
+    let hash_join_ab = memo
+        .add_physical_expression_to_group(join_group, hash_join(join_group), vec![group_a, group_b])
+        .await
+        .unwrap();
+    let hash_join_ba = memo
+        .add_physical_expression_to_group(join_group, hash_join(join_group), vec![group_b, group_a])
+        .await
+        .unwrap();
+
+    let group = memo.get_group(join_group).await.unwrap();
+    assert_eq!(group.latest_winner, None);
+    assert!(!group.in_progress);
+    assert!(!group.is_optimized);
+    assert_eq!(memo.get_winner(join_group, vec![]).await.unwrap(), None);
+
+    // Without required properties.
+    let (unordered_1, previous) = memo
+        .update_group_winner(join_group, vec![], memo.new_winner(hash_join_ab).await)
+        .await
+        .unwrap();
+    assert_eq!(previous, None);
+    assert_eq!(unordered_1.group_id, join_group);
+    assert_eq!(unordered_1.physical_expression_id, hash_join_ab);
+    let (unordered_2, previous) = memo
+        .update_group_winner(join_group, vec![], memo.new_winner(hash_join_ba).await)
+        .await
+        .unwrap();
+    assert_eq!(previous, Some(unordered_1.clone()));
+    assert_ne!(unordered_2.cost_id, unordered_1.cost_id);
+    assert_eq!(
+        memo.get_winner(join_group, vec![]).await.unwrap(),
+        Some(unordered_2.clone())
+    );
+    assert_eq!(
+        memo.get_group(join_group).await.unwrap().latest_winner,
+        Some(hash_join_ba)
+    );
+
+    // Each set of required properties has its own winners.
+    assert_eq!(
+        memo.get_winner(join_group, vec![sorted_on("a")])
+            .await
+            .unwrap(),
+        None
+    );
+    let (sorted_on_a, previous) = memo
+        .update_group_winner(
+            join_group,
+            vec![sorted_on("a")],
+            memo.new_winner(hash_join_ab).await,
+        )
+        .await
+        .unwrap();
+    assert_eq!(previous, None);
+    let (sorted_on_a_b, _) = memo
+        .update_group_winner(
+            join_group,
+            vec![sorted_on("a"), sorted_on("b")],
+            memo.new_winner(hash_join_ab).await,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        memo.get_winner(join_group, vec![sorted_on("a")])
+            .await
+            .unwrap(),
+        Some(sorted_on_a.clone())
+    );
+    // The required properties are a set.
+    assert_eq!(
+        memo.get_winner(
+            join_group,
+            vec![sorted_on("b"), sorted_on("a"), sorted_on("b")]
+        )
+        .await
+        .unwrap(),
+        Some(sorted_on_a_b)
+    );
+    assert_eq!(
+        memo.get_winner(join_group, vec![sorted_on("b")])
+            .await
+            .unwrap(),
+        None
+    );
+    // The latest winner is the winner without required properties.
+    assert_eq!(
+        memo.get_group(join_group).await.unwrap().latest_winner,
+        Some(hash_join_ba)
+    );
+    assert_eq!(
+        memo.get_winner_history(join_group, vec![]).await.unwrap(),
+        vec![unordered_1, unordered_2]
+    );
+    assert_eq!(
+        memo.get_winner_history(join_group, vec![sorted_on("a")])
+            .await
+            .unwrap(),
+        vec![sorted_on_a]
+    );
+    assert_eq!(memo.get_winner(group_a, vec![]).await.unwrap(), None);
+    assert_eq!(
+        memo.get_group_from_physical_expression(hash_join_ba)
+            .await
+            .unwrap(),
+        join_group
+    );
+
+    assert!(matches!(
+        memo.get_winner(42, vec![]).await,
+        Err(BackendError::Memo(MemoError::UnknownGroup))
+    ));
+    assert!(matches!(
+        memo.get_winner_history(42, vec![]).await,
+        Err(BackendError::Memo(MemoError::UnknownGroup))
+    ));
+    let winner = memo.new_winner(hash_join_ab).await;
+    assert!(matches!(
+        memo.update_group_winner(42, vec![], winner.clone()).await,
+        Err(BackendError::Memo(MemoError::UnknownGroup))
+    ));
+    assert!(matches!(
+        memo.update_group_winner(group_a, vec![], winner.clone())
+            .await,
+        Err(BackendError::Memo(MemoError::InvalidExpression))
+    ));
+    assert!(matches!(
+        memo.update_group_winner(
+            join_group,
+            vec![],
+            group_winner::Model {
+                physical_expression_id: 42,
+                ..winner
+            }
+        )
+        .await,
+        Err(BackendError::Memo(MemoError::UnknownPhysicalExpression))
+    ));
+    assert!(matches!(
+        memo.get_physical_expression(42).await,
+        Err(BackendError::Memo(MemoError::UnknownPhysicalExpression))
+    ));
+}
+
+pub(crate) async fn test_merge_groups<M: TestMemo>(memo: M) {
+    let (group_z, _) = memo
+        .add_logical_expression(scan("z"), vec![])
+        .await
+        .unwrap();
+    let (group_a, scan_a) = memo
+        .add_logical_expression(scan("a"), vec![])
+        .await
+        .unwrap();
+    let (group_b, scan_b) = memo
+        .add_logical_expression(scan("b"), vec![])
+        .await
+        .unwrap();
+    let (group_c, _) = memo
+        .add_logical_expression(scan("c"), vec![])
+        .await
+        .unwrap();
+    let (join_ac_group, join_ac) = memo
+        .add_logical_expression(join(), vec![group_a, group_c])
+        .await
+        .unwrap();
+    let (join_bc_group, _) = memo
+        .add_logical_expression(join(), vec![group_b, group_c])
+        .await
+        .unwrap();
+
+    // Each join group has a hash join, and the second one is the winner of its group.
+    let hash_join_ac = memo
+        .add_physical_expression_to_group(
+            join_ac_group,
+            hash_join(join_ac_group),
+            vec![group_a, group_c],
+        )
+        .await
+        .unwrap();
+    let hash_join_bc = memo
+        .add_physical_expression_to_group(
+            join_bc_group,
+            hash_join(join_bc_group),
+            vec![group_b, group_c],
+        )
+        .await
+        .unwrap();
+    memo.update_group_winner(join_bc_group, vec![], memo.new_winner(hash_join_bc).await)
+        .await
+        .unwrap();
+
+    // The older group is the representative, and the merged group is forwarded to it.
+    assert_eq!(memo.merge_groups(group_b, group_a).await.unwrap(), group_a);
+    assert_eq!(
+        memo.get_representative_group_id(group_b).await.unwrap(),
+        group_a
+    );
+    assert_eq!(memo.get_group(group_b).await.unwrap().id, group_a);
+    let mut scans = memo
+        .get_group_logical_expressions(group_b)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|expr| expr.id)
+        .collect::<Vec<_>>();
+    scans.sort();
+    assert_eq!(scans, vec![scan_a, scan_b]);
+    assert_eq!(
+        memo.add_logical_expression(scan("b"), vec![])
+            .await
+            .unwrap(),
+        (group_a, scan_b)
+    );
+
+    // The joins became identical, so their groups are merged and the duplicates removed.
+    assert_eq!(
+        memo.get_representative_group_id(join_bc_group)
+            .await
+            .unwrap(),
+        join_ac_group
+    );
+    let joins = memo
+        .get_group_logical_expressions(join_ac_group)
+        .await
+        .unwrap();
+    assert_eq!(joins.len(), 1);
+    assert_eq!(joins[0].id, join_ac);
+    assert_eq!(
+        memo.get_logical_children(join_ac).await.unwrap(),
+        vec![group_a, group_c]
+    );
+    let hash_joins = memo
+        .get_group_physical_expressions(join_bc_group)
+        .await
+        .unwrap();
+    assert_eq!(hash_joins.len(), 1);
+    assert_eq!(hash_joins[0].id, hash_join_ac);
+    // The winner of the merged group refers to the expression that was kept.
+    assert_eq!(
+        memo.get_winner(join_bc_group, vec![])
+            .await
+            .unwrap()
+            .map(|winner| winner.physical_expression_id),
+        Some(hash_join_ac)
+    );
+    assert_eq!(
+        memo.get_group(join_bc_group).await.unwrap().latest_winner,
+        Some(hash_join_ac)
+    );
+    assert!(!memo.get_group(join_ac_group).await.unwrap().is_optimized);
+
+    let all_groups = memo
+        .get_all_groups()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|group| group.id)
+        .collect::<Vec<_>>();
+    assert_eq!(all_groups, vec![group_z, group_a, group_c, join_ac_group]);
+
+    // Groups that were merged before are forwarded to the new representative.
+    assert_eq!(memo.merge_groups(group_a, group_z).await.unwrap(), group_z);
+    assert_eq!(
+        memo.get_representative_group_id(group_b).await.unwrap(),
+        group_z
+    );
+
+    assert!(matches!(
+        memo.merge_groups(group_a, 42).await,
+        Err(BackendError::Memo(MemoError::UnknownGroup))
+    ));
+}
+
+pub(crate) async fn test_add_existing_expression_merges_groups<M: TestMemo>(memo: M) {
+    let (group_a, scan_a) = memo
+        .add_logical_expression(scan("a"), vec![])
+        .await
+        .unwrap();
+    let (group_b, _) = memo
+        .add_logical_expression(scan("b"), vec![])
+        .await
+        .unwrap();
+
+    // A rule finds that the scan of `a` is equivalent to the scan of `b`.
+    let mut expr = scan("a");
+    expr.group_id = group_b;
+    assert_eq!(
+        memo.add_logical_expression_to_group(group_b, expr, vec![])
+            .await
+            .unwrap(),
+        scan_a
+    );
+    assert_eq!(
+        memo.get_representative_group_id(group_b).await.unwrap(),
+        group_a
+    );
+    assert_eq!(
+        memo.get_group_logical_expressions(group_a)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+pub(crate) async fn test_merge_group_winners<M: TestMemo>(memo: M) {
+    let (group_a, _) = memo
+        .add_logical_expression(scan("a"), vec![])
+        .await
+        .unwrap();
+    let (group_b, _) = memo
+        .add_logical_expression(scan("b"), vec![])
+        .await
+        .unwrap();
+    let (join_ab_group, _) = memo
+        .add_logical_expression(join(), vec![group_a, group_b])
+        .await
+        .unwrap();
+    let (join_ba_group, _) = memo
+        .add_logical_expression(join(), vec![group_b, group_a])
+        .await
+        .unwrap();
+    let hash_join_ab = memo
+        .add_physical_expression_to_group(
+            join_ab_group,
+            hash_join(join_ab_group),
+            vec![group_a, group_b],
+        )
+        .await
+        .unwrap();
+    let hash_join_ba = memo
+        .add_physical_expression_to_group(
+            join_ba_group,
+            hash_join(join_ba_group),
+            vec![group_b, group_a],
+        )
+        .await
+        .unwrap();
+
+    // Only the representative has a winner without required properties, and the merged group has
+    // the most recent winner sorted on `a`.
+    let (unordered, _) = memo
+        .update_group_winner(join_ab_group, vec![], memo.new_winner(hash_join_ab).await)
+        .await
+        .unwrap();
+    let (sorted_ab, _) = memo
+        .update_group_winner(
+            join_ab_group,
+            vec![sorted_on("a")],
+            memo.new_winner(hash_join_ab).await,
+        )
+        .await
+        .unwrap();
+    let (sorted_ba, _) = memo
+        .update_group_winner(
+            join_ba_group,
+            vec![sorted_on("a")],
+            memo.new_winner(hash_join_ba).await,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        memo.merge_groups(join_ba_group, join_ab_group)
+            .await
+            .unwrap(),
+        join_ab_group
+    );
+    assert_eq!(
+        memo.get_winner(join_ba_group, vec![]).await.unwrap(),
+        Some(unordered)
+    );
+    let sorted_ba = group_winner::Model {
+        group_id: join_ab_group,
+        ..sorted_ba
+    };
+    assert_eq!(
+        memo.get_winner(join_ab_group, vec![sorted_on("a")])
+            .await
+            .unwrap(),
+        Some(sorted_ba.clone())
+    );
+    assert_eq!(
+        memo.get_winner_history(join_ab_group, vec![sorted_on("a")])
+            .await
+            .unwrap(),
+        vec![sorted_ab, sorted_ba]
+    );
+    assert_eq!(
+        memo.get_group(join_ab_group).await.unwrap().latest_winner,
+        Some(hash_join_ab)
+    );
+}
@@ -1,5 +1,4 @@
 use crate::entities::*;
-use std::hash::{DefaultHasher, Hash, Hasher};
 
 /// All of the different types of fixed logical operators.
 ///
@@ -27,30 +26,120 @@ pub enum PhysicalOperator {
     HashJoin,
 }
 
-/// A method to generate a fingerprint used to efficiently check if two
-/// expressions are equivalent.
+/// All of the different types of fixed physical properties.
 ///
-/// TODO actually make efficient.
-fn fingerprint(variant_tag: i16, data: &serde_json::Value) -> i64 {
-    let mut hasher = DefaultHasher::new();
+/// Note that there could be more properties that the memo table must support that are not
+/// enumerated in this enum, as there can be up to `2^16` different types of properties.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+#[non_exhaustive]
+#[repr(i16)]
+pub enum PhysicalPropertyType {
+    SortOrder,
+}
 
-    variant_tag.hash(&mut hasher);
-    data.hash(&mut hasher);
+/// The version of [`fingerprint`], which is stored along with every fingerprint in the memo
+/// table. It must be incremented whenever the encoding or the hash function changes, with a
+/// migration that recomputes the existing fingerprints.
+pub const FINGERPRINT_VERSION: i16 = 1;
 
+/// Generates a fingerprint used to efficiently check if two expressions are identical, from the
+/// operator tag, the data and the child groups of an expression.
+///
+/// The fingerprint only depends on its inputs, so that a memo table persisted by one process can
+/// be deduplicated by another one: it is the 64-bit FNV-1a hash of a canonical encoding of the
+/// inputs, in which the keys of JSON objects are sorted. Different expressions may still have the
+/// same fingerprint, so expressions with the same fingerprint are compared before being treated
+/// as identical.
+pub fn fingerprint(variant_tag: i16, data: &serde_json::Value, children: &[i32]) -> i64 {
+    let mut hasher = Fnv1aHasher::new();
+    hasher.write(&variant_tag.to_le_bytes());
+    hasher.write_json(data);
+    hasher.write_len(children.len());
+    for child in children {
+        hasher.write(&child.to_le_bytes());
+    }
     hasher.finish() as i64
 }
 
+/// The 64-bit FNV-1a hash function, see <http://www.isthe.com/chongo/tech/comp/fnv/>.
+struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+
+    /// Writes a JSON value, prefixed by a tag of its type. Strings and collections are prefixed
+    /// by their length, so that the encoding of a value is never a prefix of another one.
+    fn write_json(&mut self, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Null => self.write(b"n"),
+            serde_json::Value::Bool(false) => self.write(b"f"),
+            serde_json::Value::Bool(true) => self.write(b"t"),
+            serde_json::Value::Number(n) => {
+                self.write(b"d");
+                self.write_str(&n.to_string());
+            }
+            serde_json::Value::String(s) => {
+                self.write(b"s");
+                self.write_str(s);
+            }
+            serde_json::Value::Array(values) => {
+                self.write(b"a");
+                self.write_len(values.len());
+                for value in values {
+                    self.write_json(value);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                self.write(b"o");
+                self.write_len(map.len());
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by_key(|(key, _)| *key);
+                for (key, value) in entries {
+                    self.write_str(key);
+                    self.write_json(value);
+                }
+            }
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
 impl logical_expression::Model {
     /// Creates a new logical expression with an unset `id` and `group_id`.
+    ///
+    /// The fingerprint depends on the child groups, so it is computed when the expression is
+    /// added to the memo table.
     pub fn new(variant_tag: LogicalOperator, data: serde_json::Value) -> Self {
-        let tag = variant_tag as i16;
-        let fingerprint = fingerprint(tag, &data);
-
         Self {
             id: 0,
             group_id: 0,
-            fingerprint,
-            variant_tag: tag,
+            fingerprint: 0,
+            fingerprint_version: FINGERPRINT_VERSION,
+            variant_tag: variant_tag as i16,
             data,
         }
     }
@@ -58,16 +147,140 @@ impl logical_expression::Model {
 
 impl physical_expression::Model {
     /// Creates a new physical expression with an unset `id` and `group_id`.
+    ///
+    /// The fingerprint depends on the child groups, so it is computed when the expression is
+    /// added to the memo table.
     pub fn new(variant_tag: PhysicalOperator, data: serde_json::Value) -> Self {
-        let tag = variant_tag as i16;
-        let fingerprint = fingerprint(tag, &data);
-
         Self {
             id: 0,
             group_id: 0,
-            fingerprint,
-            variant_tag: tag,
+            fingerprint: 0,
+            fingerprint_version: FINGERPRINT_VERSION,
+            variant_tag: variant_tag as i16,
+            data,
+        }
+    }
+}
+
+impl physical_property::Model {
+    /// Creates a new physical property with an unset `id`, `physical_expression_id` and
+    /// `group_winner_id`, e.g. to require it from a group.
+    pub fn new(variant_tag: PhysicalPropertyType, data: serde_json::Value) -> Self {
+        Self {
+            id: 0,
+            physical_expression_id: 0,
+            group_winner_id: None,
+            variant_tag: variant_tag as i16,
             data,
         }
     }
 }
+
+/// A set of physical properties as their variant tags and data, sorted and without duplicates,
+/// so that two sets are equal if and only if they have the same properties.
+pub(crate) type PropertySet = Vec<(i16, serde_json::Value)>;
+
+/// Returns the set of the variant tags and data of physical properties.
+pub(crate) fn property_set(
+    properties: impl IntoIterator<Item = physical_property::Model>,
+) -> PropertySet {
+    let mut properties: Vec<_> = properties
+        .into_iter()
+        .map(|property| (property.variant_tag, property.data))
+        .collect();
+    // JSON values are not ordered, but their serializations are canonical since the keys of
+    // objects are sorted.
+    properties.sort_by_cached_key(|(variant_tag, data)| (*variant_tag, data.to_string()));
+    properties.dedup();
+    properties
+}
+
+/// Generates a fingerprint of a set of physical properties, which is stored with the winners that
+/// were chosen for the set so that they are looked up without loading their properties.
+///
+/// Like [`fingerprint`], different sets may have the same fingerprint, so the properties of the
+/// winners with the same fingerprint are compared with the set. The encoding is the one of
+/// [`fingerprint`], so the migration of a new [`FINGERPRINT_VERSION`] recomputes these too.
+pub(crate) fn property_set_fingerprint(properties: &PropertySet) -> i64 {
+    let mut hasher = Fnv1aHasher::new();
+    hasher.write_len(properties.len());
+    for (variant_tag, data) in properties {
+        hasher.write(&variant_tag.to_le_bytes());
+        hasher.write_json(data);
+    }
+    hasher.finish() as i64
+}
+
+impl group_winner::Model {
+    /// Creates a new winner with an unset `id` and `group_id`, which was chosen with the cost
+    /// `cost_id` in the epoch `epoch_id`.
+    ///
+    /// The fingerprint of the required properties is set when the winner is stored.
+    pub fn new(physical_expression_id: i32, cost_id: i32, epoch_id: i32) -> Self {
+        Self {
+            id: 0,
+            group_id: 0,
+            physical_expression_id,
+            cost_id,
+            epoch_id,
+            properties_fingerprint: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{fingerprint, property_set, property_set_fingerprint, LogicalOperator};
+    use crate::memo::conformance::sorted_on;
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        let tag = LogicalOperator::Join as i16;
+        let data = json!({ "type": "inner", "cond": [1, "a", null, true] });
+        // The fingerprint must not change within a `FINGERPRINT_VERSION`.
+        assert_eq!(fingerprint(tag, &data, &[1, 2]), -2942538586987315858);
+        // The keys of objects are canonically ordered.
+        assert_eq!(
+            fingerprint(tag, &data, &[1, 2]),
+            fingerprint(
+                tag,
+                &json!({ "cond": [1, "a", null, true], "type": "inner" }),
+                &[1, 2]
+            )
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_inputs() {
+        let tag = LogicalOperator::Join as i16;
+        let data = json!({ "type": "inner" });
+        let fp = fingerprint(tag, &data, &[1, 2]);
+        assert_ne!(fp, fingerprint(tag, &data, &[2, 1]));
+        assert_ne!(fp, fingerprint(tag, &data, &[1, 2, 3]));
+        assert_ne!(
+            fp,
+            fingerprint(LogicalOperator::Scan as i16, &data, &[1, 2])
+        );
+        assert_ne!(fp, fingerprint(tag, &json!({ "type": "left" }), &[1, 2]));
+        assert_ne!(
+            fingerprint(tag, &json!(["ab", "c"]), &[]),
+            fingerprint(tag, &json!(["a", "bc"]), &[])
+        );
+    }
+
+    #[test]
+    fn test_property_set_fingerprint() {
+        // The migration that adds the fingerprints assigns this one to the existing winners.
+        assert_eq!(property_set_fingerprint(&vec![]), -6284781860667377211);
+        assert_eq!(
+            property_set_fingerprint(&property_set([sorted_on("a"), sorted_on("b")])),
+            property_set_fingerprint(&property_set([sorted_on("b"), sorted_on("a")]))
+        );
+        assert_ne!(
+            property_set_fingerprint(&property_set([sorted_on("a")])),
+            property_set_fingerprint(&property_set([sorted_on("b")]))
+        );
+    }
+}
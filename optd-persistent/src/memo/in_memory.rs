@@ -0,0 +1,1446 @@
+//! An implementation of the [`Memo`] trait that keeps the memo table in memory.
+//!
+//! It has the same semantics as the SQLite-backed [`BackendManager`] implementation, without the
+//! round trips to the database. An optimization can run on an [`InMemoryMemo`] and checkpoint it
+//! to a [`BackendManager`] with [`InMemoryMemo::export`] once it is done, or resume from one with
+//! [`InMemoryMemo::import`].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use sea_orm::{sea_query::OnConflict, *};
+
+use super::{
+    expression::{
+        fingerprint, property_set, property_set_fingerprint, PropertySet, FINGERPRINT_VERSION,
+    },
+    orm,
+};
+use crate::{
+    entities::{prelude::*, *},
+    BackendManager, {Memo, MemoError, StorageResult},
+};
+
+/// The number of rows that are written to the database per statement, so that a statement stays
+/// below the limit of SQLite on the number of bound variables.
+const EXPORT_CHUNK_SIZE: usize = 1000;
+
+/// A memo table that is stored in memory.
+///
+/// IDs are allocated like in the database: they start at 1 and are never reused. A memo table that
+/// is exported to a [`BackendManager`] must first be imported from it, see [`Self::export`].
+#[derive(Debug, Default)]
+pub struct InMemoryMemo {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    groups: BTreeMap<i32, cascades_group::Model>,
+    logical_expressions: Expressions<logical_expression::Model>,
+    physical_expressions: Expressions<physical_expression::Model>,
+    /// The winners of all groups with the properties they were chosen for, ordered by their IDs.
+    winners: Vec<(group_winner::Model, PropertySet)>,
+    /// The IDs of the physical expressions that were removed as duplicates, mapped to the IDs of
+    /// the expressions that were kept instead.
+    replaced_physical_expressions: BTreeMap<i32, i32>,
+    /// The backend that the memo table was imported from, if any.
+    source: Option<Source>,
+}
+
+/// The backend that a memo table was imported from, and the highest IDs of the rows that the memo
+/// table shares with it. The rows with higher IDs were added in memory.
+#[derive(Debug, Clone, Copy)]
+struct Source {
+    backend_id: u64,
+    max_ids: MaxIds,
+}
+
+/// The highest IDs of the groups, the expressions and the winners of a memo table, or 0 if there
+/// are none.
+#[derive(Debug, Clone, Copy, Default)]
+struct MaxIds {
+    group: i32,
+    logical_expression: i32,
+    physical_expression: i32,
+    winner: i32,
+}
+
+impl MaxIds {
+    /// Returns whether any of the IDs is higher than the same ID of `other`.
+    fn any_above(&self, other: &MaxIds) -> bool {
+        self.group > other.group
+            || self.logical_expression > other.logical_expression
+            || self.physical_expression > other.physical_expression
+            || self.winner > other.winner
+    }
+}
+
+/// The fields of the logical and physical expression models that the memo table works with.
+trait Expression: Clone {
+    fn id(&self) -> i32;
+    fn set_id(&mut self, id: i32);
+    fn group_id(&self) -> i32;
+    fn set_group_id(&mut self, group_id: i32);
+    fn variant_tag(&self) -> i16;
+    fn data(&self) -> &serde_json::Value;
+    fn set_fingerprint(&mut self, fingerprint: i64);
+}
+
+macro_rules! impl_expression {
+    ($model:ty) => {
+        impl Expression for $model {
+            fn id(&self) -> i32 {
+                self.id
+            }
+
+            fn set_id(&mut self, id: i32) {
+                self.id = id;
+            }
+
+            fn group_id(&self) -> i32 {
+                self.group_id
+            }
+
+            fn set_group_id(&mut self, group_id: i32) {
+                self.group_id = group_id;
+            }
+
+            fn variant_tag(&self) -> i16 {
+                self.variant_tag
+            }
+
+            fn data(&self) -> &serde_json::Value {
+                &self.data
+            }
+
+            fn set_fingerprint(&mut self, fingerprint: i64) {
+                self.fingerprint = fingerprint;
+                self.fingerprint_version = FINGERPRINT_VERSION;
+            }
+        }
+    };
+}
+
+impl_expression!(logical_expression::Model);
+impl_expression!(physical_expression::Model);
+
+/// The expressions of one kind, with their ordered child groups, indexed by their fingerprints.
+#[derive(Debug)]
+struct Expressions<E> {
+    expressions: BTreeMap<i32, E>,
+    children: HashMap<i32, Vec<i32>>,
+    by_fingerprint: HashMap<i64, Vec<i32>>,
+    next_id: i32,
+}
+
+impl<E> Default for Expressions<E> {
+    fn default() -> Self {
+        Self {
+            expressions: BTreeMap::new(),
+            children: HashMap::new(),
+            by_fingerprint: HashMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl<E: Expression> Expressions<E> {
+    fn get(&self, id: i32) -> Option<&E> {
+        self.expressions.get(&id)
+    }
+
+    fn children(&self, id: i32) -> &[i32] {
+        self.children
+            .get(&id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn group_expressions(&self, group_id: i32) -> Vec<E> {
+        self.expressions
+            .values()
+            .filter(|expression| expression.group_id() == group_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Finds the oldest expression that is identical to `expression` with the given children.
+    ///
+    /// Different expressions may have the same fingerprint, so the expressions with the same
+    /// fingerprint are compared with `expression`.
+    fn find(&self, expression: &E, children: &[i32]) -> Option<&E> {
+        let fingerprint = fingerprint(expression.variant_tag(), expression.data(), children);
+        self.by_fingerprint
+            .get(&fingerprint)?
+            .iter()
+            .map(|id| &self.expressions[id])
+            .filter(|candidate| {
+                candidate.variant_tag() == expression.variant_tag()
+                    && candidate.data() == expression.data()
+                    && self.children(candidate.id()) == children
+            })
+            .min_by_key(|candidate| candidate.id())
+    }
+
+    /// Inserts an expression with a new ID, and returns the ID.
+    fn insert(&mut self, mut expression: E, children: Vec<i32>) -> i32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        expression.set_id(id);
+        self.insert_with_id(expression, children);
+        id
+    }
+
+    /// Inserts an expression with its own ID, and computes its fingerprint.
+    fn insert_with_id(&mut self, mut expression: E, children: Vec<i32>) {
+        let id = expression.id();
+        let fingerprint = fingerprint(expression.variant_tag(), expression.data(), &children);
+        expression.set_fingerprint(fingerprint);
+        self.by_fingerprint.entry(fingerprint).or_default().push(id);
+        self.expressions.insert(id, expression);
+        self.children.insert(id, children);
+        self.next_id = self.next_id.max(id + 1);
+    }
+
+    fn remove(&mut self, id: i32) -> Option<(E, Vec<i32>)> {
+        let expression = self.expressions.remove(&id)?;
+        let children = self.children.remove(&id).unwrap_or_default();
+        let fingerprint = fingerprint(expression.variant_tag(), expression.data(), &children);
+        if let Some(ids) = self.by_fingerprint.get_mut(&fingerprint) {
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                self.by_fingerprint.remove(&fingerprint);
+            }
+        }
+        Some((expression, children))
+    }
+
+    /// Moves the expressions of the `merged` group to the `representative` group, and replaces
+    /// the `merged` group in the children of all expressions.
+    fn move_group(&mut self, representative: i32, merged: i32) {
+        let affected: Vec<_> = self
+            .expressions
+            .values()
+            .filter(|expression| {
+                expression.group_id() == merged || self.children(expression.id()).contains(&merged)
+            })
+            .map(Expression::id)
+            .collect();
+        for id in affected {
+            // Reinserting the expression updates its fingerprint.
+            let (mut expression, mut children) = self.remove(id).unwrap();
+            if expression.group_id() == merged {
+                expression.set_group_id(representative);
+            }
+            for child in &mut children {
+                if *child == merged {
+                    *child = representative;
+                }
+            }
+            self.insert_with_id(expression, children);
+        }
+    }
+
+    /// Removes the expressions that became identical after merging groups into `group_id`, i.e.
+    /// the expressions of the group and their parent expressions. The expression with the lowest
+    /// ID is kept.
+    ///
+    /// Pushes the pairs of IDs of the removed and the kept expressions to `removed`, and returns
+    /// the pairs of groups that have identical expressions and must be merged as well.
+    fn remove_duplicates(
+        &mut self,
+        group_id: i32,
+        removed: &mut Vec<(i32, i32)>,
+    ) -> Vec<(i32, i32)> {
+        let candidates: Vec<_> = self
+            .expressions
+            .values()
+            .filter(|expression| {
+                expression.group_id() == group_id
+                    || self.children(expression.id()).contains(&group_id)
+            })
+            .map(Expression::id)
+            .collect();
+
+        let mut groups_to_merge = vec![];
+        for id in candidates {
+            let expression = &self.expressions[&id];
+            let group_id = expression.group_id();
+            // An expression is identical to itself, so this always finds one.
+            let Some(original) = self.find(expression, self.children(id)) else {
+                continue;
+            };
+            let (original_id, original_group_id) = (original.id(), original.group_id());
+            if original_id == id {
+                continue;
+            }
+            if original_group_id == group_id {
+                removed.push((id, original_id));
+                self.remove(id);
+            } else {
+                groups_to_merge.push((original_group_id, group_id));
+            }
+        }
+        groups_to_merge
+    }
+}
+
+impl State {
+    /// Returns the ID of the group that `group_id` was merged into, or `group_id` itself if it
+    /// was not merged.
+    fn get_representative_group_id(&self, group_id: i32) -> StorageResult<i32> {
+        let group = self.groups.get(&group_id).ok_or(MemoError::UnknownGroup)?;
+        Ok(group.parent_id.unwrap_or(group.id))
+    }
+
+    fn get_representative_group_ids(&self, group_ids: &[i32]) -> StorageResult<Vec<i32>> {
+        group_ids
+            .iter()
+            .map(|&group_id| self.get_representative_group_id(group_id))
+            .collect()
+    }
+
+    fn get_group(&self, group_id: i32) -> StorageResult<&cascades_group::Model> {
+        let group_id = self.get_representative_group_id(group_id)?;
+        Ok(&self.groups[&group_id])
+    }
+
+    fn new_group(&mut self) -> i32 {
+        let id = self.groups.keys().next_back().map_or(1, |id| id + 1);
+        self.groups.insert(
+            id,
+            cascades_group::Model {
+                id,
+                latest_winner: None,
+                in_progress: false,
+                is_optimized: false,
+                parent_id: None,
+            },
+        );
+        id
+    }
+
+    /// Returns the winners of a group for a set of required properties, from the oldest to the
+    /// most recent one.
+    fn get_winners<'a>(
+        &'a self,
+        group_id: i32,
+        required_properties: &'a PropertySet,
+    ) -> impl Iterator<Item = &'a group_winner::Model> {
+        self.winners
+            .iter()
+            .filter(move |(winner, properties)| {
+                winner.group_id == group_id && properties == required_properties
+            })
+            .map(|(winner, _)| winner)
+    }
+
+    /// Merges two groups and the groups that end up with identical expressions, and returns the
+    /// ID of the merged group. See [`Memo::merge_groups`].
+    fn merge_groups(&mut self, group_id_1: i32, group_id_2: i32) -> StorageResult<i32> {
+        let mut pending = vec![(group_id_1, group_id_2)];
+        while let Some((group_id_1, group_id_2)) = pending.pop() {
+            let group_id_1 = self.get_representative_group_id(group_id_1)?;
+            let group_id_2 = self.get_representative_group_id(group_id_2)?;
+            if group_id_1 == group_id_2 {
+                continue;
+            }
+            let representative = group_id_1.min(group_id_2);
+            let merged = group_id_1.max(group_id_2);
+            self.move_group(representative, merged);
+
+            // Only the physical expressions can be winners, which must refer to the kept ones.
+            pending.extend(
+                self.logical_expressions
+                    .remove_duplicates(representative, &mut vec![]),
+            );
+            let mut removed = vec![];
+            pending.extend(
+                self.physical_expressions
+                    .remove_duplicates(representative, &mut removed),
+            );
+            for (removed_id, kept_id) in removed {
+                self.replaced_physical_expressions
+                    .insert(removed_id, kept_id);
+                for group in self.groups.values_mut() {
+                    if group.latest_winner == Some(removed_id) {
+                        group.latest_winner = Some(kept_id);
+                    }
+                }
+                for (winner, _) in &mut self.winners {
+                    if winner.physical_expression_id == removed_id {
+                        winner.physical_expression_id = kept_id;
+                    }
+                }
+            }
+        }
+        self.get_representative_group_id(group_id_1)
+    }
+
+    /// Returns the ID of the physical expression that replaced `id` after it was removed as a
+    /// duplicate, or `None` if it was not removed.
+    fn get_replacing_physical_expression_id(&self, id: i32) -> Option<i32> {
+        let mut replacing_id = *self.replaced_physical_expressions.get(&id)?;
+        while let Some(&next_id) = self.replaced_physical_expressions.get(&replacing_id) {
+            replacing_id = next_id;
+        }
+        Some(replacing_id)
+    }
+
+    /// Returns the highest IDs that were allocated, including the IDs of the removed expressions.
+    fn max_ids(&self) -> MaxIds {
+        MaxIds {
+            group: self.groups.keys().next_back().copied().unwrap_or_default(),
+            logical_expression: self.logical_expressions.next_id - 1,
+            physical_expression: self.physical_expressions.next_id - 1,
+            winner: self.winners.last().map_or(0, |(winner, _)| winner.id),
+        }
+    }
+
+    /// Moves everything that refers to the `merged` group to the `representative` group, and
+    /// forwards the `merged` group to it.
+    fn move_group(&mut self, representative: i32, merged: i32) {
+        self.logical_expressions.move_group(representative, merged);
+        self.physical_expressions.move_group(representative, merged);
+
+        // Forward the merged group, and the groups that were merged into it, to the
+        // representative.
+        for group in self.groups.values_mut() {
+            if group.id == merged || group.parent_id == Some(merged) {
+                group.parent_id = Some(representative);
+            }
+        }
+
+        // The winners of both groups are now winners of the representative, and the most recent
+        // one without required properties is its latest winner.
+        for (winner, _) in &mut self.winners {
+            if winner.group_id == merged {
+                winner.group_id = representative;
+            }
+        }
+        let latest_winner = self
+            .get_winners(representative, &PropertySet::new())
+            .last()
+            .map(|winner| winner.physical_expression_id);
+
+        let merged_group = self.groups.get_mut(&merged).unwrap();
+        let merged_latest_winner = merged_group.latest_winner.take();
+        let in_progress = std::mem::take(&mut merged_group.in_progress);
+        merged_group.is_optimized = false;
+
+        let group = self.groups.get_mut(&representative).unwrap();
+        group.latest_winner = latest_winner
+            .or(group.latest_winner)
+            .or(merged_latest_winner);
+        group.in_progress |= in_progress;
+        group.is_optimized = false;
+    }
+}
+
+impl InMemoryMemo {
+    /// Creates an empty memo table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the memo table of a [`BackendManager`], i.e. its groups, its expressions with their
+    /// children and its winners with their required properties. The IDs are preserved, and the
+    /// fingerprints are recomputed.
+    pub async fn import(backend: &BackendManager) -> StorageResult<Self> {
+        let mut state = State::default();
+        for group in CascadesGroup::find().all(&backend.db).await? {
+            state.groups.insert(group.id, group);
+        }
+
+        let mut logical_children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for child in LogicalChildren::find()
+            .order_by_asc(logical_children::Column::LogicalExpressionId)
+            .order_by_asc(logical_children::Column::ChildIndex)
+            .all(&backend.db)
+            .await?
+        {
+            logical_children
+                .entry(child.logical_expression_id)
+                .or_default()
+                .push(child.group_id);
+        }
+        for expression in LogicalExpression::find().all(&backend.db).await? {
+            let children = logical_children.remove(&expression.id).unwrap_or_default();
+            state
+                .logical_expressions
+                .insert_with_id(expression, children);
+        }
+
+        let mut physical_children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for child in PhysicalChildren::find()
+            .order_by_asc(physical_children::Column::PhysicalExpressionId)
+            .order_by_asc(physical_children::Column::ChildIndex)
+            .all(&backend.db)
+            .await?
+        {
+            physical_children
+                .entry(child.physical_expression_id)
+                .or_default()
+                .push(child.group_id);
+        }
+        for expression in PhysicalExpression::find().all(&backend.db).await? {
+            let children = physical_children.remove(&expression.id).unwrap_or_default();
+            state
+                .physical_expressions
+                .insert_with_id(expression, children);
+        }
+
+        let mut winner_properties: HashMap<_, Vec<_>> = HashMap::new();
+        for property in PhysicalProperty::find()
+            .filter(physical_property::Column::GroupWinnerId.is_not_null())
+            .all(&backend.db)
+            .await?
+        {
+            winner_properties
+                .entry(property.group_winner_id)
+                .or_default()
+                .push(property);
+        }
+        for winner in GroupWinner::find()
+            .order_by_asc(group_winner::Column::Id)
+            .all(&backend.db)
+            .await?
+        {
+            let properties = winner_properties
+                .remove(&Some(winner.id))
+                .unwrap_or_default();
+            state.winners.push((winner, property_set(properties)));
+        }
+
+        state.source = Some(Source {
+            backend_id: backend.id,
+            max_ids: state.max_ids(),
+        });
+        Ok(Self {
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Writes the memo table back to the [`BackendManager`] it was imported from, replacing the
+    /// imported groups, expressions and winners, and inserting the ones that were added in
+    /// memory. The imported expressions that are not in this memo table anymore, because they
+    /// were removed as duplicates after merging groups, are deleted.
+    ///
+    /// The other tables of the backend, such as the logical properties and the costs, are left
+    /// untouched, so the rows that refer to the groups and the expressions by their IDs stay
+    /// valid. The costs and the properties of the physical expressions that were removed as
+    /// duplicates are moved to the expressions that were kept instead. The costs and the epochs
+    /// that the winners refer to must exist in the backend.
+    ///
+    /// If the memo table was not imported from `backend`, or if groups, expressions or winners
+    /// were added to `backend` since the import or the last export, which may have the IDs of
+    /// the ones added in memory, returns a [`MemoError::InvalidExport`] error and leaves `backend`
+    /// untouched.
+    pub async fn export(&self, backend: &BackendManager) -> StorageResult<()> {
+        let (
+            source,
+            max_ids,
+            groups,
+            logical_expressions,
+            physical_expressions,
+            winners,
+            replacing_ids,
+        ) = {
+            let state = self.state.lock().unwrap();
+            let replacing_ids = state
+                .replaced_physical_expressions
+                .keys()
+                .map(|&id| (id, state.get_replacing_physical_expression_id(id).unwrap()))
+                .collect::<BTreeMap<_, _>>();
+            (
+                state.source,
+                state.max_ids(),
+                state.groups.values().cloned().collect::<Vec<_>>(),
+                state
+                    .logical_expressions
+                    .expressions
+                    .values()
+                    .map(|expression| {
+                        let children = state.logical_expressions.children(expression.id).to_vec();
+                        (expression.clone(), children)
+                    })
+                    .collect::<Vec<_>>(),
+                state
+                    .physical_expressions
+                    .expressions
+                    .values()
+                    .map(|expression| {
+                        let children = state.physical_expressions.children(expression.id).to_vec();
+                        (expression.clone(), children)
+                    })
+                    .collect::<Vec<_>>(),
+                state.winners.clone(),
+                replacing_ids,
+            )
+        };
+
+        let source = source
+            .filter(|source| source.backend_id == backend.id)
+            .ok_or(MemoError::InvalidExport)?;
+
+        let txn = backend.db.begin().await?;
+        if get_max_ids(&txn).await?.any_above(&source.max_ids) {
+            Err(MemoError::InvalidExport)?
+        }
+
+        // The groups are written before the expressions that refer to them, and their winners,
+        // which refer to the expressions, after them.
+        let without_winners = groups.iter().map(|group| cascades_group::Model {
+            latest_winner: None,
+            ..group.clone()
+        });
+        upsert_groups(&txn, without_winners).await?;
+
+        let logical_ids: Vec<_> = logical_expressions
+            .iter()
+            .map(|(expression, _)| expression.id)
+            .collect();
+        let stale_ids = get_stale_ids(
+            LogicalExpression::find()
+                .select_only()
+                .column(logical_expression::Column::Id)
+                .filter(logical_expression::Column::Id.lte(source.max_ids.logical_expression)),
+            &txn,
+            &logical_ids,
+        )
+        .await?;
+        for ids in stale_ids.chunks(EXPORT_CHUNK_SIZE) {
+            LogicalExpression::delete_many()
+                .filter(logical_expression::Column::Id.is_in(ids.iter().copied()))
+                .exec(&txn)
+                .await?;
+        }
+        LogicalChildren::delete_many()
+            .filter(
+                logical_children::Column::LogicalExpressionId
+                    .lte(source.max_ids.logical_expression),
+            )
+            .exec(&txn)
+            .await?;
+        for chunk in logical_expressions.chunks(EXPORT_CHUNK_SIZE) {
+            let expressions = chunk
+                .iter()
+                .map(|(expression, _)| expression.clone().into_active_model());
+            LogicalExpression::insert_many(expressions)
+                .on_conflict(
+                    OnConflict::column(logical_expression::Column::Id)
+                        .update_columns([
+                            logical_expression::Column::GroupId,
+                            logical_expression::Column::Fingerprint,
+                            logical_expression::Column::FingerprintVersion,
+                            logical_expression::Column::VariantTag,
+                            logical_expression::Column::Data,
+                        ])
+                        .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+        let children = logical_expressions
+            .iter()
+            .flat_map(|(expression, children)| {
+                children.iter().enumerate().map(|(child_index, &group_id)| {
+                    logical_children::ActiveModel {
+                        logical_expression_id: Set(expression.id),
+                        child_index: Set(child_index as i32),
+                        group_id: Set(group_id),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for chunk in children.chunks(EXPORT_CHUNK_SIZE) {
+            LogicalChildren::insert_many(chunk.iter().cloned())
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        let physical_ids: Vec<_> = physical_expressions
+            .iter()
+            .map(|(expression, _)| expression.id)
+            .collect();
+        let stale_ids = get_stale_ids(
+            PhysicalExpression::find()
+                .select_only()
+                .column(physical_expression::Column::Id)
+                .filter(physical_expression::Column::Id.lte(source.max_ids.physical_expression)),
+            &txn,
+            &physical_ids,
+        )
+        .await?;
+        for &id in &stale_ids {
+            if let Some(&replacing_id) = replacing_ids.get(&id) {
+                orm::replace_physical_expression(&txn, id, replacing_id).await?;
+            }
+        }
+        for ids in stale_ids.chunks(EXPORT_CHUNK_SIZE) {
+            PhysicalExpression::delete_many()
+                .filter(physical_expression::Column::Id.is_in(ids.iter().copied()))
+                .exec(&txn)
+                .await?;
+        }
+        PhysicalChildren::delete_many()
+            .filter(
+                physical_children::Column::PhysicalExpressionId
+                    .lte(source.max_ids.physical_expression),
+            )
+            .exec(&txn)
+            .await?;
+        for chunk in physical_expressions.chunks(EXPORT_CHUNK_SIZE) {
+            let expressions = chunk
+                .iter()
+                .map(|(expression, _)| expression.clone().into_active_model());
+            PhysicalExpression::insert_many(expressions)
+                .on_conflict(
+                    OnConflict::column(physical_expression::Column::Id)
+                        .update_columns([
+                            physical_expression::Column::GroupId,
+                            physical_expression::Column::Fingerprint,
+                            physical_expression::Column::FingerprintVersion,
+                            physical_expression::Column::VariantTag,
+                            physical_expression::Column::Data,
+                        ])
+                        .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+        let children = physical_expressions
+            .iter()
+            .flat_map(|(expression, children)| {
+                children.iter().enumerate().map(|(child_index, &group_id)| {
+                    physical_children::ActiveModel {
+                        physical_expression_id: Set(expression.id),
+                        child_index: Set(child_index as i32),
+                        group_id: Set(group_id),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for chunk in children.chunks(EXPORT_CHUNK_SIZE) {
+            PhysicalChildren::insert_many(chunk.iter().cloned())
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        PhysicalProperty::delete_many()
+            .filter(physical_property::Column::GroupWinnerId.lte(source.max_ids.winner))
+            .exec(&txn)
+            .await?;
+        GroupWinner::delete_many()
+            .filter(group_winner::Column::Id.lte(source.max_ids.winner))
+            .exec(&txn)
+            .await?;
+        for chunk in winners.chunks(EXPORT_CHUNK_SIZE) {
+            let group_winners = chunk
+                .iter()
+                .map(|(winner, _)| winner.clone().into_active_model());
+            GroupWinner::insert_many(group_winners)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+        let properties = winners
+            .iter()
+            .flat_map(|(winner, properties)| {
+                properties
+                    .iter()
+                    .map(|(variant_tag, data)| physical_property::ActiveModel {
+                        id: NotSet,
+                        physical_expression_id: Set(winner.physical_expression_id),
+                        group_winner_id: Set(Some(winner.id)),
+                        variant_tag: Set(*variant_tag),
+                        data: Set(data.clone()),
+                    })
+            })
+            .collect::<Vec<_>>();
+        for chunk in properties.chunks(EXPORT_CHUNK_SIZE) {
+            PhysicalProperty::insert_many(chunk.iter().cloned())
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        upsert_groups(&txn, groups.into_iter()).await?;
+
+        txn.commit().await?;
+        // The rows that were added in memory are now shared with the backend.
+        self.state.lock().unwrap().source = Some(Source {
+            backend_id: backend.id,
+            max_ids,
+        });
+        Ok(())
+    }
+}
+
+async fn upsert_groups<C: ConnectionTrait>(
+    db: &C,
+    groups: impl Iterator<Item = cascades_group::Model>,
+) -> StorageResult<()> {
+    let groups: Vec<_> = groups.map(IntoActiveModel::into_active_model).collect();
+    for chunk in groups.chunks(EXPORT_CHUNK_SIZE) {
+        CascadesGroup::insert_many(chunk.iter().cloned())
+            .on_conflict(
+                OnConflict::column(cascades_group::Column::Id)
+                    .update_columns([
+                        cascades_group::Column::LatestWinner,
+                        cascades_group::Column::InProgress,
+                        cascades_group::Column::IsOptimized,
+                        cascades_group::Column::ParentId,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn get_max_ids<C: ConnectionTrait>(db: &C) -> StorageResult<MaxIds> {
+    Ok(MaxIds {
+        group: get_max_id(CascadesGroup::find(), cascades_group::Column::Id, db).await?,
+        logical_expression: get_max_id(
+            LogicalExpression::find(),
+            logical_expression::Column::Id,
+            db,
+        )
+        .await?,
+        physical_expression: get_max_id(
+            PhysicalExpression::find(),
+            physical_expression::Column::Id,
+            db,
+        )
+        .await?,
+        winner: get_max_id(GroupWinner::find(), group_winner::Column::Id, db).await?,
+    })
+}
+
+/// Returns the highest ID in the `id` column of `query`, or 0 if there are no rows.
+async fn get_max_id<E: EntityTrait, C: ConnectionTrait>(
+    query: Select<E>,
+    id: E::Column,
+    db: &C,
+) -> StorageResult<i32> {
+    let max_id: Option<Option<i32>> = query
+        .select_only()
+        .column_as(id.max(), "max_id")
+        .into_tuple()
+        .one(db)
+        .await?;
+    Ok(max_id.flatten().unwrap_or_default())
+}
+
+/// Returns the IDs selected by `ids_query` that are not in `kept_ids`, which is sorted.
+async fn get_stale_ids<E: EntityTrait, C: ConnectionTrait>(
+    ids_query: Select<E>,
+    db: &C,
+    kept_ids: &[i32],
+) -> StorageResult<Vec<i32>> {
+    Ok(ids_query
+        .into_tuple::<i32>()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|id| kept_ids.binary_search(id).is_err())
+        .collect())
+}
+
+impl Memo for InMemoryMemo {
+    type Group = cascades_group::Model;
+    type GroupId = i32;
+    type LogicalExpression = logical_expression::Model;
+    type LogicalExpressionId = i32;
+    type PhysicalExpression = physical_expression::Model;
+    type PhysicalExpressionId = i32;
+    type PhysicalProperty = physical_property::Model;
+    type GroupWinner = group_winner::Model;
+
+    async fn get_group(&self, group_id: Self::GroupId) -> StorageResult<Self::Group> {
+        let state = self.state.lock().unwrap();
+        state.get_group(group_id).cloned()
+    }
+
+    async fn get_all_groups(&self) -> StorageResult<Vec<Self::Group>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .groups
+            .values()
+            .filter(|group| group.parent_id.is_none())
+            .cloned()
+            .collect())
+    }
+
+    async fn get_representative_group_id(
+        &self,
+        group_id: Self::GroupId,
+    ) -> StorageResult<Self::GroupId> {
+        let state = self.state.lock().unwrap();
+        state.get_representative_group_id(group_id)
+    }
+
+    async fn get_logical_expression(
+        &self,
+        logical_expression_id: Self::LogicalExpressionId,
+    ) -> StorageResult<Self::LogicalExpression> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .logical_expressions
+            .get(logical_expression_id)
+            .cloned()
+            .ok_or(MemoError::UnknownLogicalExpression)?)
+    }
+
+    async fn get_physical_expression(
+        &self,
+        physical_expression_id: Self::PhysicalExpressionId,
+    ) -> StorageResult<Self::PhysicalExpression> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .physical_expressions
+            .get(physical_expression_id)
+            .cloned()
+            .ok_or(MemoError::UnknownPhysicalExpression)?)
+    }
+
+    async fn get_group_from_logical_expression(
+        &self,
+        logical_expression_id: Self::LogicalExpressionId,
+    ) -> StorageResult<Self::GroupId> {
+        Ok(self
+            .get_logical_expression(logical_expression_id)
+            .await?
+            .group_id)
+    }
+
+    async fn get_group_from_physical_expression(
+        &self,
+        physical_expression_id: Self::PhysicalExpressionId,
+    ) -> StorageResult<Self::GroupId> {
+        Ok(self
+            .get_physical_expression(physical_expression_id)
+            .await?
+            .group_id)
+    }
+
+    async fn get_group_logical_expressions(
+        &self,
+        group_id: Self::GroupId,
+    ) -> StorageResult<Vec<Self::LogicalExpression>> {
+        let state = self.state.lock().unwrap();
+        let group_id = state.get_representative_group_id(group_id)?;
+        Ok(state.logical_expressions.group_expressions(group_id))
+    }
+
+    async fn get_group_physical_expressions(
+        &self,
+        group_id: Self::GroupId,
+    ) -> StorageResult<Vec<Self::PhysicalExpression>> {
+        let state = self.state.lock().unwrap();
+        let group_id = state.get_representative_group_id(group_id)?;
+        Ok(state.physical_expressions.group_expressions(group_id))
+    }
+
+    async fn get_logical_children(
+        &self,
+        logical_expression_id: Self::LogicalExpressionId,
+    ) -> StorageResult<Vec<Self::GroupId>> {
+        let state = self.state.lock().unwrap();
+        if state
+            .logical_expressions
+            .get(logical_expression_id)
+            .is_none()
+        {
+            Err(MemoError::UnknownLogicalExpression)?
+        }
+        Ok(state
+            .logical_expressions
+            .children(logical_expression_id)
+            .to_vec())
+    }
+
+    async fn get_physical_children(
+        &self,
+        physical_expression_id: Self::PhysicalExpressionId,
+    ) -> StorageResult<Vec<Self::GroupId>> {
+        let state = self.state.lock().unwrap();
+        if state
+            .physical_expressions
+            .get(physical_expression_id)
+            .is_none()
+        {
+            Err(MemoError::UnknownPhysicalExpression)?
+        }
+        Ok(state
+            .physical_expressions
+            .children(physical_expression_id)
+            .to_vec())
+    }
+
+    async fn get_winner(
+        &self,
+        group_id: Self::GroupId,
+        required_properties: Vec<Self::PhysicalProperty>,
+    ) -> StorageResult<Option<Self::GroupWinner>> {
+        let state = self.state.lock().unwrap();
+        let group_id = state.get_representative_group_id(group_id)?;
+        Ok(state
+            .get_winners(group_id, &property_set(required_properties))
+            .last()
+            .cloned())
+    }
+
+    async fn get_winner_history(
+        &self,
+        group_id: Self::GroupId,
+        required_properties: Vec<Self::PhysicalProperty>,
+    ) -> StorageResult<Vec<Self::GroupWinner>> {
+        let state = self.state.lock().unwrap();
+        let group_id = state.get_representative_group_id(group_id)?;
+        Ok(state
+            .get_winners(group_id, &property_set(required_properties))
+            .cloned()
+            .collect())
+    }
+
+    async fn update_group_winner(
+        &self,
+        group_id: Self::GroupId,
+        required_properties: Vec<Self::PhysicalProperty>,
+        winner: Self::GroupWinner,
+    ) -> StorageResult<(Self::GroupWinner, Option<Self::GroupWinner>)> {
+        let mut state = self.state.lock().unwrap();
+
+        let group_id = state.get_representative_group_id(group_id)?;
+        let expression = state
+            .physical_expressions
+            .get(winner.physical_expression_id)
+            .ok_or(MemoError::UnknownPhysicalExpression)?;
+        if expression.group_id != group_id {
+            Err(MemoError::InvalidExpression)?
+        }
+
+        let required_properties = property_set(required_properties);
+        let previous = state
+            .get_winners(group_id, &required_properties)
+            .last()
+            .cloned();
+
+        let new_winner = group_winner::Model {
+            id: state.winners.last().map_or(1, |(winner, _)| winner.id + 1),
+            group_id,
+            properties_fingerprint: property_set_fingerprint(&required_properties),
+            ..winner
+        };
+        if required_properties.is_empty() {
+            let group = state.groups.get_mut(&group_id).unwrap();
+            group.latest_winner = Some(new_winner.physical_expression_id);
+        }
+        state
+            .winners
+            .push((new_winner.clone(), required_properties));
+        Ok((new_winner, previous))
+    }
+
+    async fn add_logical_expression_to_group(
+        &self,
+        group_id: Self::GroupId,
+        logical_expression: Self::LogicalExpression,
+        children: Vec<Self::GroupId>,
+    ) -> StorageResult<Self::LogicalExpressionId> {
+        if logical_expression.group_id != group_id {
+            Err(MemoError::InvalidExpression)?
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        // Check if the group and the child groups actually exist, and resolve merged groups.
+        let group_id = state.get_representative_group_id(group_id)?;
+        let children = state.get_representative_group_ids(&children)?;
+        let mut logical_expression = logical_expression;
+        logical_expression.group_id = group_id;
+
+        if let Some(existing) = state
+            .logical_expressions
+            .find(&logical_expression, &children)
+        {
+            if existing.group_id == group_id {
+                return Ok(existing.id);
+            }
+            // The groups are equivalent. Merging them may remove the existing expression as a
+            // duplicate, so it is looked up again.
+            let existing_group_id = existing.group_id;
+            state.merge_groups(group_id, existing_group_id)?;
+            let children = state.get_representative_group_ids(&children)?;
+            let existing = state
+                .logical_expressions
+                .find(&logical_expression, &children)
+                .ok_or(MemoError::UnknownLogicalExpression)?;
+            return Ok(existing.id);
+        }
+
+        Ok(state
+            .logical_expressions
+            .insert(logical_expression, children))
+    }
+
+    async fn add_physical_expression_to_group(
+        &self,
+        group_id: Self::GroupId,
+        physical_expression: Self::PhysicalExpression,
+        children: Vec<Self::GroupId>,
+    ) -> StorageResult<Self::PhysicalExpressionId> {
+        if physical_expression.group_id != group_id {
+            Err(MemoError::InvalidExpression)?
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        // Check if the group and the child groups actually exist, and resolve merged groups.
+        let group_id = state.get_representative_group_id(group_id)?;
+        let children = state.get_representative_group_ids(&children)?;
+        let mut physical_expression = physical_expression;
+        physical_expression.group_id = group_id;
+
+        if let Some(existing) = state
+            .physical_expressions
+            .find(&physical_expression, &children)
+        {
+            if existing.group_id == group_id {
+                return Ok(existing.id);
+            }
+            // The groups are equivalent. Merging them may remove the existing expression as a
+            // duplicate, so it is looked up again.
+            let existing_group_id = existing.group_id;
+            state.merge_groups(group_id, existing_group_id)?;
+            let children = state.get_representative_group_ids(&children)?;
+            let existing = state
+                .physical_expressions
+                .find(&physical_expression, &children)
+                .ok_or(MemoError::UnknownPhysicalExpression)?;
+            return Ok(existing.id);
+        }
+
+        Ok(state
+            .physical_expressions
+            .insert(physical_expression, children))
+    }
+
+    /// Note that in this function, we ignore the group ID that the logical expression contains.
+    async fn add_logical_expression(
+        &self,
+        expression: Self::LogicalExpression,
+        children: Vec<Self::GroupId>,
+    ) -> StorageResult<(Self::GroupId, Self::LogicalExpressionId)> {
+        let mut state = self.state.lock().unwrap();
+        let children = state.get_representative_group_ids(&children)?;
+
+        // The expression already exists, so return its data.
+        if let Some(existing_expression) = state.logical_expressions.find(&expression, &children) {
+            return Ok((existing_expression.group_id, existing_expression.id));
+        }
+
+        // The expression does not exist yet, so we need to create a new group and new expression.
+        let group_id = state.new_group();
+        let mut expression = expression;
+        expression.group_id = group_id;
+        let expression_id = state.logical_expressions.insert(expression, children);
+        Ok((group_id, expression_id))
+    }
+
+    async fn merge_groups(
+        &self,
+        group_id_1: Self::GroupId,
+        group_id_2: Self::GroupId,
+    ) -> StorageResult<Self::GroupId> {
+        let mut state = self.state.lock().unwrap();
+        state.merge_groups(group_id_1, group_id_2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use sea_orm::*;
+
+    use super::super::conformance::{
+        hash_join, join, memo_conformance_tests, scan, sorted_on, TestMemo,
+    };
+    use super::InMemoryMemo;
+    use crate::{
+        entities::{prelude::*, *},
+        get_sqlite_url, migrate, BackendError, BackendManager, Memo, MemoError,
+    };
+
+    impl TestMemo for InMemoryMemo {
+        /// The costs and the epochs are not stored in memory, so their IDs are only unique.
+        async fn new_winner(&self, physical_expression_id: i32) -> group_winner::Model {
+            static NEXT_ID: AtomicI32 = AtomicI32::new(1);
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            group_winner::Model::new(physical_expression_id, id, id)
+        }
+    }
+
+    async fn with_in_memory_memo<F: Future<Output = ()>>(
+        _test_name: &str,
+        test: impl FnOnce(InMemoryMemo) -> F,
+    ) {
+        test(InMemoryMemo::new()).await;
+    }
+
+    memo_conformance_tests!(with_in_memory_memo);
+
+    #[tokio::test]
+    async fn test_export_import() {
+        const DATABASE_FILE: &str = "test_export_import.db";
+        let _ = std::fs::remove_file(DATABASE_FILE);
+        let backend_manager = BackendManager::new(Some(&get_sqlite_url(DATABASE_FILE)))
+            .await
+            .unwrap();
+        migrate(&backend_manager.db).await.unwrap();
+
+        // The optimization starts from the empty memo table of the backend.
+        let memo = InMemoryMemo::import(&backend_manager).await.unwrap();
+        let (group_a, _) = memo
+            .add_logical_expression(scan("a"), vec![])
+            .await
+            .unwrap();
+        let (group_b, _) = memo
+            .add_logical_expression(scan("b"), vec![])
+            .await
+            .unwrap();
+        let (join_group, join_ab) = memo
+            .add_logical_expression(join(), vec![group_a, group_b])
+            .await
+            .unwrap();
+        let hash_join_ab = memo
+            .add_physical_expression_to_group(
+                join_group,
+                hash_join(join_group),
+                vec![group_a, group_b],
+            )
+            .await
+            .unwrap();
+        memo.export(&backend_manager).await.unwrap();
+
+        // The costs of the winners are stored in the database.
+        let (unordered, _) = memo
+            .update_group_winner(
+                join_group,
+                vec![],
+                backend_manager.new_winner(hash_join_ab).await,
+            )
+            .await
+            .unwrap();
+        let (sorted, _) = memo
+            .update_group_winner(
+                join_group,
+                vec![sorted_on("a")],
+                backend_manager.new_winner(hash_join_ab).await,
+            )
+            .await
+            .unwrap();
+        memo.export(&backend_manager).await.unwrap();
+
+        // The exported memo table behaves like the in-memory one.
+        assert_eq!(
+            backend_manager.get_all_groups().await.unwrap(),
+            memo.get_all_groups().await.unwrap()
+        );
+        assert_eq!(
+            backend_manager.get_logical_children(join_ab).await.unwrap(),
+            vec![group_a, group_b]
+        );
+        assert_eq!(
+            backend_manager
+                .get_winner(join_group, vec![])
+                .await
+                .unwrap(),
+            Some(unordered)
+        );
+        assert_eq!(
+            backend_manager
+                .get_winner(join_group, vec![sorted_on("a")])
+                .await
+                .unwrap(),
+            Some(sorted.clone())
+        );
+        assert_eq!(
+            backend_manager
+                .add_logical_expression(join(), vec![group_a, group_b])
+                .await
+                .unwrap(),
+            (join_group, join_ab)
+        );
+
+        // Resume the optimization in memory, where merging the scans makes the joins identical.
+        let memo = InMemoryMemo::import(&backend_manager).await.unwrap();
+        assert_eq!(
+            memo.get_winner_history(join_group, vec![sorted_on("a")])
+                .await
+                .unwrap(),
+            vec![sorted.clone()]
+        );
+        assert_eq!(
+            memo.add_logical_expression(join(), vec![group_a, group_b])
+                .await
+                .unwrap(),
+            (join_group, join_ab)
+        );
+        let (join_ba_group, _) = memo
+            .add_logical_expression(join(), vec![group_b, group_a])
+            .await
+            .unwrap();
+        let hash_join_ba = memo
+            .add_physical_expression_to_group(
+                join_ba_group,
+                hash_join(join_ba_group),
+                vec![group_b, group_a],
+            )
+            .await
+            .unwrap();
+        memo.export(&backend_manager).await.unwrap();
+        let ba_cost_id = backend_manager.new_winner(hash_join_ba).await.cost_id;
+        assert_eq!(memo.merge_groups(group_a, group_b).await.unwrap(), group_a);
+        assert_eq!(
+            memo.get_representative_group_id(join_ba_group)
+                .await
+                .unwrap(),
+            join_group
+        );
+        memo.export(&backend_manager).await.unwrap();
+
+        // The duplicates that were removed in memory are deleted from the database.
+        assert_eq!(
+            backend_manager
+                .get_group_logical_expressions(join_group)
+                .await
+                .unwrap(),
+            memo.get_group_logical_expressions(join_group)
+                .await
+                .unwrap()
+        );
+        let hash_joins = backend_manager
+            .get_group_physical_expressions(join_ba_group)
+            .await
+            .unwrap();
+        assert_eq!(hash_joins.len(), 1);
+        assert_eq!(hash_joins[0].id, hash_join_ab);
+        assert!(backend_manager
+            .get_physical_expression(hash_join_ba)
+            .await
+            .is_err());
+        let ba_cost = PlanCost::find_by_id(ba_cost_id)
+            .one(&backend_manager.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ba_cost.physical_expression_id, hash_join_ab);
+        assert_eq!(
+            backend_manager
+                .get_physical_children(hash_join_ab)
+                .await
+                .unwrap(),
+            vec![group_a, group_a]
+        );
+        assert_eq!(
+            backend_manager
+                .get_representative_group_id(group_b)
+                .await
+                .unwrap(),
+            group_a
+        );
+        assert_eq!(
+            backend_manager
+                .get_winner_history(join_ba_group, vec![sorted_on("a")])
+                .await
+                .unwrap(),
+            vec![sorted]
+        );
+
+        let _ = std::fs::remove_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_export_refuses_other_backends() {
+        const DATABASE_FILE: &str = "test_export_refuses_other_backends.db";
+        const OTHER_DATABASE_FILE: &str = "test_export_refuses_other_backends_other.db";
+        let mut backend_managers = vec![];
+        for db_file in [DATABASE_FILE, OTHER_DATABASE_FILE] {
+            let _ = std::fs::remove_file(db_file);
+            let backend_manager = BackendManager::new(Some(&get_sqlite_url(db_file)))
+                .await
+                .unwrap();
+            migrate(&backend_manager.db).await.unwrap();
+            backend_managers.push(backend_manager);
+        }
+        let (backend_manager, other_backend_manager) = (&backend_managers[0], &backend_managers[1]);
+
+        // A memo table that was not imported from the backend would overwrite its rows.
+        let memo = InMemoryMemo::new();
+        memo.add_logical_expression(scan("a"), vec![])
+            .await
+            .unwrap();
+        assert!(matches!(
+            memo.export(backend_manager).await,
+            Err(BackendError::Memo(MemoError::InvalidExport))
+        ));
+        let memo = InMemoryMemo::import(backend_manager).await.unwrap();
+        memo.add_logical_expression(scan("a"), vec![])
+            .await
+            .unwrap();
+        assert!(matches!(
+            memo.export(other_backend_manager).await,
+            Err(BackendError::Memo(MemoError::InvalidExport))
+        ));
+        assert!(other_backend_manager
+            .get_all_groups()
+            .await
+            .unwrap()
+            .is_empty());
+
+        // The rows that were added to the backend since the import are not overwritten.
+        let stale_memo = InMemoryMemo::import(backend_manager).await.unwrap();
+        stale_memo
+            .add_logical_expression(scan("b"), vec![])
+            .await
+            .unwrap();
+        memo.export(backend_manager).await.unwrap();
+        assert!(matches!(
+            stale_memo.export(backend_manager).await,
+            Err(BackendError::Memo(MemoError::InvalidExport))
+        ));
+        let (group_a, scan_a) = memo
+            .add_logical_expression(scan("a"), vec![])
+            .await
+            .unwrap();
+        assert_eq!(
+            backend_manager
+                .add_logical_expression(scan("a"), vec![])
+                .await
+                .unwrap(),
+            (group_a, scan_a)
+        );
+
+        // The memo table can be exported again after its previous export.
+        memo.add_logical_expression(scan("b"), vec![])
+            .await
+            .unwrap();
+        memo.export(backend_manager).await.unwrap();
+        assert_eq!(
+            backend_manager.get_all_groups().await.unwrap(),
+            memo.get_all_groups().await.unwrap()
+        );
+
+        for db_file in [DATABASE_FILE, OTHER_DATABASE_FILE] {
+            let _ = std::fs::remove_file(db_file);
+        }
+    }
+}
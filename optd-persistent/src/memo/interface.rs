@@ -22,15 +22,34 @@ pub trait Memo {
     type PhysicalExpression;
     /// A type representing a unique identifier for a physical expression.
     type PhysicalExpressionId;
+    /// A type representing a physical property, such as a sort order.
+    type PhysicalProperty;
+    /// A type representing a winner of a group, i.e. its best physical expression for a set of
+    /// required physical properties, along with the cost and the epoch it was chosen with.
+    type GroupWinner;
 
     /// Retrieves a [`Self::Group`] given a [`Self::GroupId`].
     ///
+    /// If the group was merged into another group, retrieves the group it was merged into. Every
+    /// method that takes a [`Self::GroupId`] resolves merged groups the same way. See
+    /// [`Self::merge_groups`].
+    ///
     /// If the group does not exist, returns a [`MemoError::UnknownGroup`] error.
     async fn get_group(&self, group_id: Self::GroupId) -> StorageResult<Self::Group>;
 
-    /// Retrieves all group IDs that are stored in the memo table.
+    /// Retrieves all groups that are stored in the memo table, except the groups that were
+    /// merged into other groups.
     async fn get_all_groups(&self) -> StorageResult<Vec<Self::Group>>;
 
+    /// Retrieves the ID of the group that a group was merged into, or the ID of the group itself
+    /// if it was not merged.
+    ///
+    /// If the group does not exist, returns a [`MemoError::UnknownGroup`] error.
+    async fn get_representative_group_id(
+        &self,
+        group_id: Self::GroupId,
+    ) -> StorageResult<Self::GroupId>;
+
     /// Retrieves a [`Self::LogicalExpression`] given a [`Self::LogicalExpressionId`].
     ///
     /// If the logical expression does not exist, returns a [`MemoError::UnknownLogicalExpression`]
@@ -83,43 +102,95 @@ pub trait Memo {
         group_id: Self::GroupId,
     ) -> StorageResult<Vec<Self::PhysicalExpression>>;
 
-    /// Retrieves the best physical query plan (winner) for a given group.
+    /// Retrieves the child groups of a logical expression, in order.
+    ///
+    /// If the logical expression does not exist, returns a [`MemoError::UnknownLogicalExpression`]
+    /// error.
+    async fn get_logical_children(
+        &self,
+        logical_expression_id: Self::LogicalExpressionId,
+    ) -> StorageResult<Vec<Self::GroupId>>;
+
+    /// Retrieves the child groups of a physical expression, in order.
+    ///
+    /// If the physical expression does not exist, returns a
+    /// [`MemoError::UnknownPhysicalExpression`] error.
+    async fn get_physical_children(
+        &self,
+        physical_expression_id: Self::PhysicalExpressionId,
+    ) -> StorageResult<Vec<Self::GroupId>>;
+
+    /// Retrieves the best physical query plan (winner) of a group for a set of required
+    /// physical properties, i.e. the winner that was chosen most recently for that set.
+    ///
+    /// The required properties are a set: their order and their duplicates are ignored, and only
+    /// their variant tags and data are compared. The empty set requires no property.
     ///
     /// If the group does not exist, returns a [`MemoError::UnknownGroup`] error.
     async fn get_winner(
         &self,
         group_id: Self::GroupId,
-    ) -> StorageResult<Option<Self::PhysicalExpressionId>>;
+        required_properties: Vec<Self::PhysicalProperty>,
+    ) -> StorageResult<Option<Self::GroupWinner>>;
 
-    /// Updates / replaces a group's best physical plan (winner). Optionally returns the previous
-    /// winner's physical expression ID.
+    /// Retrieves all of the winners of a group for a set of required physical properties, from
+    /// the oldest to the current one. See [`Self::get_winner`].
     ///
     /// If the group does not exist, returns a [`MemoError::UnknownGroup`] error.
+    async fn get_winner_history(
+        &self,
+        group_id: Self::GroupId,
+        required_properties: Vec<Self::PhysicalProperty>,
+    ) -> StorageResult<Vec<Self::GroupWinner>>;
+
+    /// Records a new best physical plan (winner) of a group for a set of required physical
+    /// properties, and returns the new winner with its ID, and the previous winner for that set.
+    ///
+    /// The physical expression, the cost and the epoch of the new winner are recorded, and its
+    /// own ID and group ID are ignored. The winner for the empty set is also the `latest_winner`
+    /// of the [`Self::Group`]. See [`Self::get_winner`].
+    ///
+    /// If the group does not exist, returns a [`MemoError::UnknownGroup`] error. If the physical
+    /// expression does not exist, returns a [`MemoError::UnknownPhysicalExpression`] error, and if
+    /// it is not in the group, returns a [`MemoError::InvalidExpression`] error.
     async fn update_group_winner(
         &self,
         group_id: Self::GroupId,
-        physical_expression_id: Self::PhysicalExpressionId,
-    ) -> StorageResult<Option<Self::PhysicalExpressionId>>;
+        required_properties: Vec<Self::PhysicalProperty>,
+        winner: Self::GroupWinner,
+    ) -> StorageResult<(Self::GroupWinner, Option<Self::GroupWinner>)>;
 
-    /// Adds a logical expression to an existing group via its [`Self::GroupId`].
+    /// Adds a logical expression with the given child groups to an existing group via its
+    /// [`Self::GroupId`], and returns the ID of the expression.
     ///
-    /// If the group does not exist, returns a [`MemoError::UnknownGroup`] error.
+    /// Two expressions are identical if they have the same operator, the same data and the same
+    /// children. If an identical expression already exists, it is not added again and its ID is
+    /// returned instead. If that expression is in another group, the two groups are equivalent,
+    /// so they are merged.
+    ///
+    /// If the group or one of the child groups does not exist, returns a
+    /// [`MemoError::UnknownGroup`] error.
     async fn add_logical_expression_to_group(
         &self,
         group_id: Self::GroupId,
         logical_expression: Self::LogicalExpression,
-        children: Vec<Self::LogicalExpressionId>,
-    ) -> StorageResult<()>;
+        children: Vec<Self::GroupId>,
+    ) -> StorageResult<Self::LogicalExpressionId>;
 
-    /// Adds a physical expression to an existing group via its [`Self::GroupId`].
+    /// Adds a physical expression with the given child groups to an existing group via its
+    /// [`Self::GroupId`], and returns the ID of the expression.
     ///
-    /// If the group does not exist, returns a [`MemoError::UnknownGroup`] error.
+    /// If an identical expression already exists, it is not added again and its ID is returned
+    /// instead. See [`Self::add_logical_expression_to_group`].
+    ///
+    /// If the group or one of the child groups does not exist, returns a
+    /// [`MemoError::UnknownGroup`] error.
     async fn add_physical_expression_to_group(
         &self,
         group_id: Self::GroupId,
         physical_expression: Self::PhysicalExpression,
-        children: Vec<Self::LogicalExpressionId>,
-    ) -> StorageResult<()>;
+        children: Vec<Self::GroupId>,
+    ) -> StorageResult<Self::PhysicalExpressionId>;
 
     /// Adds a new logical expression into the memo table, creating a new group if the expression
     /// does not already exist.
@@ -128,14 +199,36 @@ pub trait Memo {
     /// the expression has been seen before, and if it has already been created, then the parent
     /// group ID should also be retrievable.
     ///
-    /// If the expression already exists, then this function will return the [`Self::GroupId`] of
-    /// the parent group and the corresponding (already existing) [`Self::LogicalExpressionId`].
+    /// If the expression already exists with the same children, then this function will return
+    /// the [`Self::GroupId`] of the parent group and the corresponding (already existing)
+    /// [`Self::LogicalExpressionId`].
     ///
     /// If the expression does not exist, this function will create a new group and a new
-    /// expression, returning brand new IDs for both.
+    /// expression with the given child groups, returning brand new IDs for both.
+    ///
+    /// If one of the child groups does not exist, returns a [`MemoError::UnknownGroup`] error.
     async fn add_logical_expression(
         &self,
         expression: Self::LogicalExpression,
-        children: Vec<Self::LogicalExpressionId>,
+        children: Vec<Self::GroupId>,
     ) -> StorageResult<(Self::GroupId, Self::LogicalExpressionId)>;
+
+    /// Merges two equivalent groups, and returns the ID of the merged group.
+    ///
+    /// The older group is the representative of the merged group: the expressions, the parent
+    /// expressions, the logical properties and the winners of the other group are moved to it.
+    /// For each set of required properties, the winner that was chosen most recently in either
+    /// group is the winner of the merged group, which needs to be optimized again. The ID of the
+    /// other group remains valid and refers to the representative.
+    ///
+    /// Merging may make expressions identical, e.g. `Join(A, C)` and `Join(B, C)` when `A` and
+    /// `B` are merged. Such duplicates are removed, and groups that end up with identical
+    /// expressions are merged as well.
+    ///
+    /// If either group does not exist, returns a [`MemoError::UnknownGroup`] error.
+    async fn merge_groups(
+        &self,
+        group_id_1: Self::GroupId,
+        group_id_2: Self::GroupId,
+    ) -> StorageResult<Self::GroupId>;
 }
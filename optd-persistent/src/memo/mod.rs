@@ -1,4 +1,8 @@
-mod expression;
+pub(crate) mod expression;
+
+#[cfg(test)]
+mod conformance;
+pub mod in_memory;
 
 pub mod interface;
 pub mod orm;
@@ -1,8 +1,13 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::expression::{
+    fingerprint, property_set, property_set_fingerprint, PropertySet, FINGERPRINT_VERSION,
+};
 use crate::{
     entities::{prelude::*, *},
     BackendManager, {Memo, MemoError, StorageResult},
 };
-use sea_orm::*;
+use sea_orm::{sea_query::Expr, *};
 
 impl Memo for BackendManager {
     type Group = cascades_group::Model;
@@ -11,8 +16,11 @@ impl Memo for BackendManager {
     type LogicalExpressionId = i32;
     type PhysicalExpression = physical_expression::Model;
     type PhysicalExpressionId = i32;
+    type PhysicalProperty = physical_property::Model;
+    type GroupWinner = group_winner::Model;
 
     async fn get_group(&self, group_id: Self::GroupId) -> StorageResult<Self::Group> {
+        let group_id = get_representative_group_id(&self.db, group_id).await?;
         Ok(CascadesGroup::find_by_id(group_id)
             .one(&self.db)
             .await?
@@ -20,7 +28,17 @@ impl Memo for BackendManager {
     }
 
     async fn get_all_groups(&self) -> StorageResult<Vec<Self::Group>> {
-        Ok(CascadesGroup::find().all(&self.db).await?)
+        Ok(CascadesGroup::find()
+            .filter(cascades_group::Column::ParentId.is_null())
+            .all(&self.db)
+            .await?)
+    }
+
+    async fn get_representative_group_id(
+        &self,
+        group_id: Self::GroupId,
+    ) -> StorageResult<Self::GroupId> {
+        get_representative_group_id(&self.db, group_id).await
     }
 
     async fn get_logical_expression(
@@ -68,11 +86,10 @@ impl Memo for BackendManager {
         &self,
         group_id: Self::GroupId,
     ) -> StorageResult<Vec<Self::LogicalExpression>> {
-        // First retrieve the group record, and then find all related logical expressions.
-        Ok(self
-            .get_group(group_id)
-            .await?
-            .find_related(LogicalExpression)
+        // First retrieve the group record, and then find all of its logical expressions.
+        let group = self.get_group(group_id).await?;
+        Ok(LogicalExpression::find()
+            .filter(logical_expression::Column::GroupId.eq(group.id))
             .all(&self.db)
             .await?)
     }
@@ -81,114 +98,196 @@ impl Memo for BackendManager {
         &self,
         group_id: Self::GroupId,
     ) -> StorageResult<Vec<Self::PhysicalExpression>> {
-        // First retrieve the group record, and then find all related physical expressions.
-        Ok(self
-            .get_group(group_id)
-            .await?
-            .find_related(PhysicalExpression)
+        // First retrieve the group record, and then find all of its physical expressions.
+        let group = self.get_group(group_id).await?;
+        Ok(PhysicalExpression::find()
+            .filter(physical_expression::Column::GroupId.eq(group.id))
             .all(&self.db)
             .await?)
     }
 
+    async fn get_logical_children(
+        &self,
+        logical_expression_id: Self::LogicalExpressionId,
+    ) -> StorageResult<Vec<Self::GroupId>> {
+        let _ = self.get_logical_expression(logical_expression_id).await?;
+        get_logical_children(&self.db, logical_expression_id).await
+    }
+
+    async fn get_physical_children(
+        &self,
+        physical_expression_id: Self::PhysicalExpressionId,
+    ) -> StorageResult<Vec<Self::GroupId>> {
+        let _ = self.get_physical_expression(physical_expression_id).await?;
+        get_physical_children(&self.db, physical_expression_id).await
+    }
+
     async fn get_winner(
         &self,
         group_id: Self::GroupId,
-    ) -> StorageResult<Option<Self::PhysicalExpressionId>> {
-        Ok(self.get_group(group_id).await?.latest_winner)
+        required_properties: Vec<Self::PhysicalProperty>,
+    ) -> StorageResult<Option<Self::GroupWinner>> {
+        let group_id = get_representative_group_id(&self.db, group_id).await?;
+        get_winner(&self.db, group_id, &property_set(required_properties)).await
+    }
+
+    async fn get_winner_history(
+        &self,
+        group_id: Self::GroupId,
+        required_properties: Vec<Self::PhysicalProperty>,
+    ) -> StorageResult<Vec<Self::GroupWinner>> {
+        let group_id = get_representative_group_id(&self.db, group_id).await?;
+        get_winner_history(&self.db, group_id, &property_set(required_properties)).await
     }
 
     async fn update_group_winner(
         &self,
         group_id: Self::GroupId,
-        physical_expression_id: Self::PhysicalExpressionId,
-    ) -> StorageResult<Option<Self::PhysicalExpressionId>> {
-        // First retrieve the group record, and then use an `ActiveModel` to update it.
-        let mut group = self.get_group(group_id).await?.into_active_model();
-        let old_id = group.latest_winner;
+        required_properties: Vec<Self::PhysicalProperty>,
+        winner: Self::GroupWinner,
+    ) -> StorageResult<(Self::GroupWinner, Option<Self::GroupWinner>)> {
+        let txn = self.db.begin().await?;
+
+        let group_id = get_representative_group_id(&txn, group_id).await?;
+        let expression = PhysicalExpression::find_by_id(winner.physical_expression_id)
+            .one(&txn)
+            .await?
+            .ok_or(MemoError::UnknownPhysicalExpression)?;
+        if expression.group_id != group_id {
+            Err(MemoError::InvalidExpression)?
+        }
 
-        group.latest_winner = Set(Some(physical_expression_id));
-        group.update(&self.db).await?;
+        let required_properties = property_set(required_properties);
+        let previous = get_winner(&txn, group_id, &required_properties).await?;
 
-        // The old value must be set (`None` still means it has been set).
-        let old = old_id.unwrap();
-        Ok(old)
+        // Insert the winner, and then the properties it was chosen for.
+        let mut new_winner = winner.into_active_model();
+        new_winner.id = NotSet;
+        new_winner.group_id = Set(group_id);
+        new_winner.properties_fingerprint = Set(property_set_fingerprint(&required_properties));
+        let new_winner = new_winner.insert(&txn).await?;
+        insert_winner_properties(&txn, &new_winner, &required_properties).await?;
+
+        if required_properties.is_empty() {
+            CascadesGroup::update_many()
+                .col_expr(
+                    cascades_group::Column::LatestWinner,
+                    Expr::value(new_winner.physical_expression_id),
+                )
+                .filter(cascades_group::Column::Id.eq(group_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+        Ok((new_winner, previous))
     }
 
     async fn add_logical_expression_to_group(
         &self,
         group_id: Self::GroupId,
         logical_expression: Self::LogicalExpression,
-        _children: Vec<Self::LogicalExpressionId>,
-    ) -> StorageResult<()> {
+        children: Vec<Self::GroupId>,
+    ) -> StorageResult<Self::LogicalExpressionId> {
         if logical_expression.group_id != group_id {
             Err(MemoError::InvalidExpression)?
         }
 
-        // Check if the group actually exists.
-        let _ = self.get_group(group_id).await?;
+        let txn = self.db.begin().await?;
 
-        // Insert the expression.
-        let _ = logical_expression
-            .into_active_model()
-            .insert(&self.db)
-            .await?;
+        // Check if the group and the child groups actually exist, and resolve merged groups.
+        let group_id = get_representative_group_id(&txn, group_id).await?;
+        let children = get_representative_group_ids(&txn, &children).await?;
+        let mut logical_expression = logical_expression;
+        logical_expression.group_id = group_id;
+
+        if let Some(existing) =
+            find_logical_expression(&txn, &logical_expression, &children).await?
+        {
+            if existing.group_id == group_id {
+                return Ok(existing.id);
+            }
+            // The groups are equivalent. Merging them may remove the existing expression as a
+            // duplicate, so it is looked up again.
+            merge_groups(&txn, group_id, existing.group_id).await?;
+            let children = get_representative_group_ids(&txn, &children).await?;
+            let existing = find_logical_expression(&txn, &logical_expression, &children)
+                .await?
+                .ok_or(MemoError::UnknownLogicalExpression)?;
+            txn.commit().await?;
+            return Ok(existing.id);
+        }
+
+        // Insert the expression and its children.
+        let mut new_expr = logical_expression.into_active_model();
+        new_expr.id = NotSet;
+        set_logical_fingerprint(&mut new_expr, &children);
+        let new_expr = new_expr.insert(&txn).await?;
+        insert_logical_children(&txn, new_expr.id, &children).await?;
 
-        todo!("add the children of the logical expression into the children table")
+        txn.commit().await?;
+        Ok(new_expr.id)
     }
 
     async fn add_physical_expression_to_group(
         &self,
         group_id: Self::GroupId,
         physical_expression: Self::PhysicalExpression,
-        _children: Vec<Self::LogicalExpressionId>,
-    ) -> StorageResult<()> {
+        children: Vec<Self::GroupId>,
+    ) -> StorageResult<Self::PhysicalExpressionId> {
         if physical_expression.group_id != group_id {
             Err(MemoError::InvalidExpression)?
         }
 
-        // Check if the group actually exists.
-        let _ = self.get_group(group_id).await?;
+        let txn = self.db.begin().await?;
 
-        // Insert the expression.
-        let _ = physical_expression
-            .into_active_model()
-            .insert(&self.db)
-            .await?;
+        // Check if the group and the child groups actually exist, and resolve merged groups.
+        let group_id = get_representative_group_id(&txn, group_id).await?;
+        let children = get_representative_group_ids(&txn, &children).await?;
+        let mut physical_expression = physical_expression;
+        physical_expression.group_id = group_id;
+
+        if let Some(existing) =
+            find_physical_expression(&txn, &physical_expression, &children).await?
+        {
+            if existing.group_id == group_id {
+                return Ok(existing.id);
+            }
+            // The groups are equivalent. Merging them may remove the existing expression as a
+            // duplicate, so it is looked up again.
+            merge_groups(&txn, group_id, existing.group_id).await?;
+            let children = get_representative_group_ids(&txn, &children).await?;
+            let existing = find_physical_expression(&txn, &physical_expression, &children)
+                .await?
+                .ok_or(MemoError::UnknownPhysicalExpression)?;
+            txn.commit().await?;
+            return Ok(existing.id);
+        }
 
-        todo!("add the children of the logical expression into the children table")
+        // Insert the expression and its children.
+        let mut new_expr = physical_expression.into_active_model();
+        new_expr.id = NotSet;
+        set_physical_fingerprint(&mut new_expr, &children);
+        let new_expr = new_expr.insert(&txn).await?;
+        insert_physical_children(&txn, new_expr.id, &children).await?;
+
+        txn.commit().await?;
+        Ok(new_expr.id)
     }
 
     /// Note that in this function, we ignore the group ID that the logical expression contains.
     async fn add_logical_expression(
         &self,
         expression: Self::LogicalExpression,
-        _children: Vec<Self::LogicalExpressionId>,
+        children: Vec<Self::GroupId>,
     ) -> StorageResult<(Self::GroupId, Self::LogicalExpressionId)> {
-        // Lookup all expressions that have the same fingerprint. There may be false positives, but
-        // we will check for those later.
-        let fingerprint = expression.fingerprint;
-        let potential_matches = LogicalExpression::find()
-            .filter(logical_expression::Column::Fingerprint.eq(fingerprint))
-            .all(&self.db)
-            .await?;
-
-        // Of the expressions that have the same fingerprint, check if there already exists an
-        // expression that is exactly identical to the input expression.
-        let mut matches: Vec<_> = potential_matches
-            .into_iter()
-            .filter(|expr| expr == &expression)
-            .collect();
-        assert!(
-            matches.len() <= 1,
-            "there cannot be more than 1 exact logical expression match"
-        );
+        let txn = self.db.begin().await?;
+        let children = get_representative_group_ids(&txn, &children).await?;
 
         // The expression already exists, so return its data.
-        if !matches.is_empty() {
-            let existing_expression = matches
-                .pop()
-                .expect("we just checked that an element exists");
-
+        if let Some(existing_expression) =
+            find_logical_expression(&txn, &expression, &children).await?
+        {
             return Ok((existing_expression.group_id, existing_expression.id));
         }
 
@@ -201,13 +300,800 @@ impl Memo for BackendManager {
         };
 
         // Insert a new group.
-        let res = cascades_group::Entity::insert(group).exec(&self.db).await?;
+        let res = cascades_group::Entity::insert(group).exec(&txn).await?;
 
-        // Insert the input expression with the correct `group_id`.
+        // Insert the input expression with the correct `group_id`, and then its children.
         let mut new_expr = expression.into_active_model();
+        new_expr.id = NotSet;
         new_expr.group_id = Set(res.last_insert_id);
-        let new_expr = new_expr.insert(&self.db).await?;
+        set_logical_fingerprint(&mut new_expr, &children);
+        let new_expr = new_expr.insert(&txn).await?;
+        insert_logical_children(&txn, new_expr.id, &children).await?;
 
+        txn.commit().await?;
         Ok((new_expr.group_id, new_expr.id))
     }
+
+    async fn merge_groups(
+        &self,
+        group_id_1: Self::GroupId,
+        group_id_2: Self::GroupId,
+    ) -> StorageResult<Self::GroupId> {
+        let txn = self.db.begin().await?;
+        let group_id = merge_groups(&txn, group_id_1, group_id_2).await?;
+        txn.commit().await?;
+        Ok(group_id)
+    }
+}
+
+/// Returns the ID of the group that `group_id` was merged into, or `group_id` itself if it was
+/// not merged.
+///
+/// Merging always forwards the merged groups to the latest representative, so there is at most
+/// one hop.
+async fn get_representative_group_id<C: ConnectionTrait>(
+    db: &C,
+    group_id: i32,
+) -> StorageResult<i32> {
+    let group = CascadesGroup::find_by_id(group_id)
+        .one(db)
+        .await?
+        .ok_or(MemoError::UnknownGroup)?;
+    Ok(group.parent_id.unwrap_or(group.id))
+}
+
+async fn get_representative_group_ids<C: ConnectionTrait>(
+    db: &C,
+    group_ids: &[i32],
+) -> StorageResult<Vec<i32>> {
+    let mut representatives = Vec::with_capacity(group_ids.len());
+    for &group_id in group_ids {
+        representatives.push(get_representative_group_id(db, group_id).await?);
+    }
+    Ok(representatives)
+}
+
+/// Merges two groups and the groups that end up with identical expressions, and returns the ID
+/// of the merged group. See [`Memo::merge_groups`].
+async fn merge_groups<C: ConnectionTrait>(
+    db: &C,
+    group_id_1: i32,
+    group_id_2: i32,
+) -> StorageResult<i32> {
+    let mut pending = vec![(group_id_1, group_id_2)];
+    while let Some((group_id_1, group_id_2)) = pending.pop() {
+        let group_id_1 = get_representative_group_id(db, group_id_1).await?;
+        let group_id_2 = get_representative_group_id(db, group_id_2).await?;
+        if group_id_1 == group_id_2 {
+            continue;
+        }
+        let representative = group_id_1.min(group_id_2);
+        let merged = group_id_1.max(group_id_2);
+        move_group(db, representative, merged).await?;
+        pending.extend(remove_logical_duplicates(db, representative).await?);
+        pending.extend(remove_physical_duplicates(db, representative).await?);
+    }
+    get_representative_group_id(db, group_id_1).await
+}
+
+/// Moves everything that refers to the `merged` group to the `representative` group, and
+/// forwards the `merged` group to it.
+async fn move_group<C: ConnectionTrait>(
+    db: &C,
+    representative: i32,
+    merged: i32,
+) -> StorageResult<()> {
+    LogicalExpression::update_many()
+        .col_expr(
+            logical_expression::Column::GroupId,
+            Expr::value(representative),
+        )
+        .filter(logical_expression::Column::GroupId.eq(merged))
+        .exec(db)
+        .await?;
+    PhysicalExpression::update_many()
+        .col_expr(
+            physical_expression::Column::GroupId,
+            Expr::value(representative),
+        )
+        .filter(physical_expression::Column::GroupId.eq(merged))
+        .exec(db)
+        .await?;
+    LogicalChildren::update_many()
+        .col_expr(
+            logical_children::Column::GroupId,
+            Expr::value(representative),
+        )
+        .filter(logical_children::Column::GroupId.eq(merged))
+        .exec(db)
+        .await?;
+    PhysicalChildren::update_many()
+        .col_expr(
+            physical_children::Column::GroupId,
+            Expr::value(representative),
+        )
+        .filter(physical_children::Column::GroupId.eq(merged))
+        .exec(db)
+        .await?;
+    LogicalProperty::update_many()
+        .col_expr(
+            logical_property::Column::GroupId,
+            Expr::value(representative),
+        )
+        .filter(logical_property::Column::GroupId.eq(merged))
+        .exec(db)
+        .await?;
+    GroupWinner::update_many()
+        .col_expr(group_winner::Column::GroupId, Expr::value(representative))
+        .filter(group_winner::Column::GroupId.eq(merged))
+        .exec(db)
+        .await?;
+
+    // Forward the merged group, and the groups that were merged into it, to the representative.
+    CascadesGroup::update_many()
+        .col_expr(
+            cascades_group::Column::ParentId,
+            Expr::value(representative),
+        )
+        .filter(
+            Condition::any()
+                .add(cascades_group::Column::ParentId.eq(merged))
+                .add(cascades_group::Column::Id.eq(merged)),
+        )
+        .exec(db)
+        .await?;
+
+    let merged_group = CascadesGroup::find_by_id(merged)
+        .one(db)
+        .await?
+        .ok_or(MemoError::UnknownGroup)?;
+    let mut group = CascadesGroup::find_by_id(representative)
+        .one(db)
+        .await?
+        .ok_or(MemoError::UnknownGroup)?
+        .into_active_model();
+    // The winners of both groups are now winners of the representative, and the most recent one
+    // without required properties is its latest winner.
+    let latest_winner = get_winner(db, representative, &vec![])
+        .await?
+        .map(|winner| winner.physical_expression_id);
+    group.latest_winner = Set(latest_winner
+        .or(*group.latest_winner.as_ref())
+        .or(merged_group.latest_winner));
+    group.in_progress = Set(*group.in_progress.as_ref() || merged_group.in_progress);
+    group.is_optimized = Set(false);
+    group.update(db).await?;
+
+    let mut merged_group = merged_group.into_active_model();
+    merged_group.latest_winner = Set(None);
+    merged_group.in_progress = Set(false);
+    merged_group.is_optimized = Set(false);
+    merged_group.update(db).await?;
+    Ok(())
+}
+
+/// Removes the logical expressions that became identical after merging groups into
+/// `group_id`, i.e. the expressions of the group and their parent expressions.
+///
+/// Returns the pairs of groups that have identical expressions and must be merged as well.
+async fn remove_logical_duplicates<C: ConnectionTrait>(
+    db: &C,
+    group_id: i32,
+) -> StorageResult<Vec<(i32, i32)>> {
+    let parent_ids = LogicalChildren::find()
+        .filter(logical_children::Column::GroupId.eq(group_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|child| child.logical_expression_id);
+    let candidates: BTreeMap<_, _> = LogicalExpression::find()
+        .filter(
+            Condition::any()
+                .add(logical_expression::Column::GroupId.eq(group_id))
+                .add(logical_expression::Column::Id.is_in(parent_ids)),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|expr| (expr.id, expr))
+        .collect();
+
+    let mut kept = HashMap::new();
+    let mut groups_to_merge = vec![];
+    for (id, expr) in candidates {
+        let children = get_logical_children(db, id).await?;
+        // The child groups may have been merged, which changes the fingerprint.
+        let mut active_expr = expr.clone().into_active_model();
+        set_logical_fingerprint(&mut active_expr, &children);
+        if active_expr.is_changed() {
+            active_expr.update(db).await?;
+        }
+        let key = (expr.variant_tag, expr.data.clone(), children);
+        let Some(original) = kept.get(&key) else {
+            kept.insert(key, expr);
+            continue;
+        };
+        let original: &logical_expression::Model = original;
+        if original.group_id == expr.group_id {
+            LogicalChildren::delete_many()
+                .filter(logical_children::Column::LogicalExpressionId.eq(id))
+                .exec(db)
+                .await?;
+            LogicalExpression::delete_by_id(id).exec(db).await?;
+        } else {
+            groups_to_merge.push((original.group_id, expr.group_id));
+        }
+    }
+    Ok(groups_to_merge)
+}
+
+/// Removes the physical expressions that became identical after merging groups into
+/// `group_id`, and moves the winners, the properties and the costs of the removed expressions to
+/// the expressions that are kept.
+///
+/// See [`remove_logical_duplicates`].
+async fn remove_physical_duplicates<C: ConnectionTrait>(
+    db: &C,
+    group_id: i32,
+) -> StorageResult<Vec<(i32, i32)>> {
+    let parent_ids = PhysicalChildren::find()
+        .filter(physical_children::Column::GroupId.eq(group_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|child| child.physical_expression_id);
+    let candidates: BTreeMap<_, _> = PhysicalExpression::find()
+        .filter(
+            Condition::any()
+                .add(physical_expression::Column::GroupId.eq(group_id))
+                .add(physical_expression::Column::Id.is_in(parent_ids)),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|expr| (expr.id, expr))
+        .collect();
+
+    let mut kept = HashMap::new();
+    let mut groups_to_merge = vec![];
+    for (id, expr) in candidates {
+        let children = get_physical_children(db, id).await?;
+        // The child groups may have been merged, which changes the fingerprint.
+        let mut active_expr = expr.clone().into_active_model();
+        set_physical_fingerprint(&mut active_expr, &children);
+        if active_expr.is_changed() {
+            active_expr.update(db).await?;
+        }
+        let key = (expr.variant_tag, expr.data.clone(), children);
+        let Some(original) = kept.get(&key) else {
+            kept.insert(key, expr);
+            continue;
+        };
+        let original: &physical_expression::Model = original;
+        if original.group_id != expr.group_id {
+            groups_to_merge.push((original.group_id, expr.group_id));
+            continue;
+        }
+        replace_physical_expression(db, id, original.id).await?;
+        PhysicalChildren::delete_many()
+            .filter(physical_children::Column::PhysicalExpressionId.eq(id))
+            .exec(db)
+            .await?;
+        PhysicalExpression::delete_by_id(id).exec(db).await?;
+    }
+    Ok(groups_to_merge)
+}
+
+/// Moves the winners, the properties and the costs of the physical expression `removed_id` to the
+/// identical expression `kept_id`, before `removed_id` is deleted.
+pub(super) async fn replace_physical_expression<C: ConnectionTrait>(
+    db: &C,
+    removed_id: i32,
+    kept_id: i32,
+) -> StorageResult<()> {
+    GroupWinner::update_many()
+        .col_expr(
+            group_winner::Column::PhysicalExpressionId,
+            Expr::value(kept_id),
+        )
+        .filter(group_winner::Column::PhysicalExpressionId.eq(removed_id))
+        .exec(db)
+        .await?;
+    CascadesGroup::update_many()
+        .col_expr(cascades_group::Column::LatestWinner, Expr::value(kept_id))
+        .filter(cascades_group::Column::LatestWinner.eq(removed_id))
+        .exec(db)
+        .await?;
+    PhysicalProperty::update_many()
+        .col_expr(
+            physical_property::Column::PhysicalExpressionId,
+            Expr::value(kept_id),
+        )
+        .filter(physical_property::Column::PhysicalExpressionId.eq(removed_id))
+        .exec(db)
+        .await?;
+    PlanCost::update_many()
+        .col_expr(
+            plan_cost::Column::PhysicalExpressionId,
+            Expr::value(kept_id),
+        )
+        .filter(plan_cost::Column::PhysicalExpressionId.eq(removed_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Returns the most recent winner of a group for a set of required properties.
+async fn get_winner<C: ConnectionTrait>(
+    db: &C,
+    group_id: i32,
+    required_properties: &PropertySet,
+) -> StorageResult<Option<group_winner::Model>> {
+    let candidates = GroupWinner::find()
+        .filter(group_winner::Column::GroupId.eq(group_id))
+        .filter(
+            group_winner::Column::PropertiesFingerprint
+                .eq(property_set_fingerprint(required_properties)),
+        )
+        .order_by_desc(group_winner::Column::Id);
+    // The winners for other sets with the same fingerprint are skipped one by one, since
+    // fingerprints rarely collide.
+    let mut offset = 0;
+    while let Some(winner) = candidates.clone().offset(offset).one(db).await? {
+        let properties = PhysicalProperty::find()
+            .filter(physical_property::Column::GroupWinnerId.eq(winner.id))
+            .all(db)
+            .await?;
+        if property_set(properties) == *required_properties {
+            return Ok(Some(winner));
+        }
+        offset += 1;
+    }
+    Ok(None)
+}
+
+/// Returns the winners of a group for a set of required properties, from the oldest to the most
+/// recent one.
+async fn get_winner_history<C: ConnectionTrait>(
+    db: &C,
+    group_id: i32,
+    required_properties: &PropertySet,
+) -> StorageResult<Vec<group_winner::Model>> {
+    let properties_fingerprint = property_set_fingerprint(required_properties);
+    let winners = GroupWinner::find()
+        .filter(group_winner::Column::GroupId.eq(group_id))
+        .filter(group_winner::Column::PropertiesFingerprint.eq(properties_fingerprint))
+        .order_by_asc(group_winner::Column::Id)
+        .all(db)
+        .await?;
+    let mut properties: HashMap<_, Vec<_>> = HashMap::new();
+    for property in PhysicalProperty::find()
+        .inner_join(GroupWinner)
+        .filter(group_winner::Column::GroupId.eq(group_id))
+        .filter(group_winner::Column::PropertiesFingerprint.eq(properties_fingerprint))
+        .all(db)
+        .await?
+    {
+        properties
+            .entry(property.group_winner_id)
+            .or_default()
+            .push(property);
+    }
+    Ok(winners
+        .into_iter()
+        .filter(|winner| {
+            let properties = properties.remove(&Some(winner.id)).unwrap_or_default();
+            property_set(properties) == *required_properties
+        })
+        .collect())
+}
+
+async fn insert_winner_properties<C: ConnectionTrait>(
+    db: &C,
+    winner: &group_winner::Model,
+    properties: &PropertySet,
+) -> StorageResult<()> {
+    if properties.is_empty() {
+        return Ok(());
+    }
+    let properties = properties
+        .iter()
+        .map(|(variant_tag, data)| physical_property::ActiveModel {
+            id: NotSet,
+            physical_expression_id: Set(winner.physical_expression_id),
+            group_winner_id: Set(Some(winner.id)),
+            variant_tag: Set(*variant_tag),
+            data: Set(data.clone()),
+        });
+    PhysicalProperty::insert_many(properties).exec(db).await?;
+    Ok(())
+}
+
+async fn get_logical_children<C: ConnectionTrait>(
+    db: &C,
+    logical_expression_id: i32,
+) -> StorageResult<Vec<i32>> {
+    Ok(LogicalChildren::find()
+        .filter(logical_children::Column::LogicalExpressionId.eq(logical_expression_id))
+        .order_by_asc(logical_children::Column::ChildIndex)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|child| child.group_id)
+        .collect())
+}
+
+async fn get_physical_children<C: ConnectionTrait>(
+    db: &C,
+    physical_expression_id: i32,
+) -> StorageResult<Vec<i32>> {
+    Ok(PhysicalChildren::find()
+        .filter(physical_children::Column::PhysicalExpressionId.eq(physical_expression_id))
+        .order_by_asc(physical_children::Column::ChildIndex)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|child| child.group_id)
+        .collect())
+}
+
+async fn insert_logical_children<C: ConnectionTrait>(
+    db: &C,
+    logical_expression_id: i32,
+    children: &[i32],
+) -> StorageResult<()> {
+    if children.is_empty() {
+        return Ok(());
+    }
+    let children =
+        children
+            .iter()
+            .enumerate()
+            .map(|(child_index, &group_id)| logical_children::ActiveModel {
+                logical_expression_id: Set(logical_expression_id),
+                child_index: Set(child_index as i32),
+                group_id: Set(group_id),
+            });
+    LogicalChildren::insert_many(children).exec(db).await?;
+    Ok(())
+}
+
+async fn insert_physical_children<C: ConnectionTrait>(
+    db: &C,
+    physical_expression_id: i32,
+    children: &[i32],
+) -> StorageResult<()> {
+    if children.is_empty() {
+        return Ok(());
+    }
+    let children = children.iter().enumerate().map(|(child_index, &group_id)| {
+        physical_children::ActiveModel {
+            physical_expression_id: Set(physical_expression_id),
+            child_index: Set(child_index as i32),
+            group_id: Set(group_id),
+        }
+    });
+    PhysicalChildren::insert_many(children).exec(db).await?;
+    Ok(())
+}
+
+/// Sets the fingerprint of a logical expression that has the given children.
+fn set_logical_fingerprint(expression: &mut logical_expression::ActiveModel, children: &[i32]) {
+    let fingerprint = fingerprint(
+        *expression.variant_tag.as_ref(),
+        expression.data.as_ref(),
+        children,
+    );
+    expression.fingerprint.set_if_not_equals(fingerprint);
+    expression
+        .fingerprint_version
+        .set_if_not_equals(FINGERPRINT_VERSION);
+}
+
+/// Sets the fingerprint of a physical expression that has the given children.
+fn set_physical_fingerprint(expression: &mut physical_expression::ActiveModel, children: &[i32]) {
+    let fingerprint = fingerprint(
+        *expression.variant_tag.as_ref(),
+        expression.data.as_ref(),
+        children,
+    );
+    expression.fingerprint.set_if_not_equals(fingerprint);
+    expression
+        .fingerprint_version
+        .set_if_not_equals(FINGERPRINT_VERSION);
+}
+
+/// Finds a logical expression that is identical to `expression` with the given children, i.e.
+/// that has the same operator, data and child groups.
+///
+/// Expressions with the same fingerprint are looked up first. Different expressions may have the
+/// same fingerprint, so these candidates are compared with the expression.
+async fn find_logical_expression<C: ConnectionTrait>(
+    db: &C,
+    expression: &logical_expression::Model,
+    children: &[i32],
+) -> StorageResult<Option<logical_expression::Model>> {
+    let potential_matches = LogicalExpression::find()
+        .filter(logical_expression::Column::Fingerprint.eq(fingerprint(
+            expression.variant_tag,
+            &expression.data,
+            children,
+        )))
+        .filter(logical_expression::Column::FingerprintVersion.eq(FINGERPRINT_VERSION))
+        .filter(logical_expression::Column::VariantTag.eq(expression.variant_tag))
+        .all(db)
+        .await?;
+    for potential_match in potential_matches {
+        if potential_match.data == expression.data
+            && get_logical_children(db, potential_match.id).await? == children
+        {
+            return Ok(Some(potential_match));
+        }
+    }
+    Ok(None)
+}
+
+/// Finds a physical expression that is identical to `expression` with the given children.
+///
+/// See [`find_logical_expression`].
+async fn find_physical_expression<C: ConnectionTrait>(
+    db: &C,
+    expression: &physical_expression::Model,
+    children: &[i32],
+) -> StorageResult<Option<physical_expression::Model>> {
+    let potential_matches = PhysicalExpression::find()
+        .filter(physical_expression::Column::Fingerprint.eq(fingerprint(
+            expression.variant_tag,
+            &expression.data,
+            children,
+        )))
+        .filter(physical_expression::Column::FingerprintVersion.eq(FINGERPRINT_VERSION))
+        .filter(physical_expression::Column::VariantTag.eq(expression.variant_tag))
+        .all(db)
+        .await?;
+    for potential_match in potential_matches {
+        if potential_match.data == expression.data
+            && get_physical_children(db, potential_match.id).await? == children
+        {
+            return Ok(Some(potential_match));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use sea_orm::*;
+    use sea_orm_migration::MigratorTrait;
+
+    use super::super::{
+        conformance::{join, memo_conformance_tests, sorted_on, TestMemo},
+        expression::{fingerprint, LogicalOperator, PhysicalOperator, FINGERPRINT_VERSION},
+    };
+    use crate::{
+        entities::{prelude::*, *},
+        get_sqlite_url, migrate,
+        migrator::Migrator,
+        BackendManager, CostModelStorageLayer, Memo,
+    };
+
+    impl TestMemo for BackendManager {
+        async fn new_winner(&self, physical_expression_id: i32) -> group_winner::Model {
+            let epoch_id = self
+                .create_new_epoch("test".to_string(), "new winner".to_string())
+                .await
+                .unwrap();
+            let cost = plan_cost::ActiveModel {
+                physical_expression_id: Set(physical_expression_id),
+                epoch_id: Set(epoch_id),
+                cost: Set(None),
+                estimated_statistic: Set(None),
+                is_valid: Set(true),
+                ..Default::default()
+            };
+            let cost_id = PlanCost::insert(cost)
+                .exec(&self.db)
+                .await
+                .unwrap()
+                .last_insert_id;
+            group_winner::Model::new(physical_expression_id, cost_id, epoch_id)
+        }
+    }
+
+    async fn new_backend_manager(db_file: &str) -> BackendManager {
+        let _ = std::fs::remove_file(db_file);
+        let backend_manager = BackendManager::new(Some(&get_sqlite_url(db_file)))
+            .await
+            .unwrap();
+        migrate(&backend_manager.db).await.unwrap();
+        backend_manager
+    }
+
+    async fn with_backend_manager<F: Future<Output = ()>>(
+        test_name: &str,
+        test: impl FnOnce(BackendManager) -> F,
+    ) {
+        let db_file = format!("{test_name}.db");
+        test(new_backend_manager(&db_file).await).await;
+        let _ = std::fs::remove_file(db_file);
+    }
+
+    memo_conformance_tests!(with_backend_manager);
+
+    /// Creates a backend manager whose database only has the schema of the original migrations,
+    /// like the databases created before the later migrations were added.
+    async fn new_baseline_backend_manager(db_file: &str) -> BackendManager {
+        let _ = std::fs::remove_file(db_file);
+        let backend_manager = BackendManager::new(Some(&get_sqlite_url(db_file)))
+            .await
+            .unwrap();
+        let baseline_migration_cnt = Migrator::migrations()
+            .iter()
+            .filter(|migration| migration.name().starts_with("m20241029_"))
+            .count();
+        Migrator::up(&backend_manager.db, Some(baseline_migration_cnt as u32))
+            .await
+            .unwrap();
+        backend_manager
+    }
+
+    #[tokio::test]
+    async fn test_migration_orders_children() {
+        const DATABASE_FILE: &str = "test_migration_orders_children.db";
+        let memo = new_baseline_backend_manager(DATABASE_FILE).await;
+
+        // The original schema has no `data` and no `child_index` columns.
+        memo.db
+            .execute_unprepared(
+                "INSERT INTO cascades_group (id, latest_winner, in_progress, is_optimized) \
+                 VALUES (1, NULL, false, false), (2, NULL, false, false), (3, NULL, false, false);
+                 INSERT INTO logical_expression (id, group_id, fingerprint, variant_tag) \
+                 VALUES (1, 1, 0, 0), (2, 2, 0, 0), (3, 3, 0, 1);
+                 INSERT INTO logical_children (logical_expression_id, group_id) \
+                 VALUES (3, 2), (3, 1);
+                 INSERT INTO physical_expression (id, group_id, fingerprint, variant_tag) \
+                 VALUES (1, 3, 0, 1);
+                 INSERT INTO physical_children (physical_expression_id, group_id) \
+                 VALUES (1, 2), (1, 1);",
+            )
+            .await
+            .unwrap();
+
+        Migrator::up(&memo.db, None).await.unwrap();
+
+        // The existing children are numbered in the order of their group IDs.
+        assert_eq!(memo.get_logical_children(3).await.unwrap(), vec![1, 2]);
+        assert_eq!(memo.get_physical_children(1).await.unwrap(), vec![1, 2]);
+        assert_eq!(
+            memo.get_logical_expression(1).await.unwrap().data,
+            serde_json::Value::Null
+        );
+
+        // The same group can now be a child of an expression more than once.
+        let (self_join_group, self_join) = memo
+            .add_logical_expression(join(), vec![1, 1])
+            .await
+            .unwrap();
+        assert_ne!(self_join_group, 3);
+        assert_eq!(
+            memo.get_logical_children(self_join).await.unwrap(),
+            vec![1, 1]
+        );
+
+        let _ = std::fs::remove_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_migration_keeps_winners() {
+        const DATABASE_FILE: &str = "test_migration_keeps_winners.db";
+        let memo = new_baseline_backend_manager(DATABASE_FILE).await;
+
+        // The original schema stores the properties of the expressions, but not the properties
+        // that the winners were chosen for.
+        memo.db
+            .execute_unprepared(
+                "INSERT INTO cascades_group (id, latest_winner, in_progress, is_optimized) \
+                 VALUES (1, NULL, false, false);
+                 INSERT INTO physical_expression (id, group_id, fingerprint, variant_tag) \
+                 VALUES (1, 1, 0, 0);
+                 UPDATE cascades_group SET latest_winner = 1;
+                 INSERT INTO physical_property (id, physical_expression_id, variant_tag, data) \
+                 VALUES (1, 1, 0, '[\"a\"]');
+                 INSERT INTO event (epoch_id, timestamp, source_variant, data) \
+                 VALUES (1, '2024-11-20 00:00:00', 'test', 'null');
+                 INSERT INTO plan_cost (id, physical_expression_id, epoch_id, is_valid) \
+                 VALUES (1, 1, 1, true);
+                 INSERT INTO group_winner (id, group_id, physical_expression_id, cost_id, epoch_id) \
+                 VALUES (1, 1, 1, 1, 1);",
+            )
+            .await
+            .unwrap();
+
+        Migrator::up(&memo.db, None).await.unwrap();
+
+        // The existing winners were chosen without required properties.
+        let winner = memo.get_winner(1, vec![]).await.unwrap().unwrap();
+        assert_eq!((winner.id, winner.physical_expression_id), (1, 1));
+        assert_eq!(
+            memo.get_winner(1, vec![sorted_on("a")]).await.unwrap(),
+            None
+        );
+        let property = PhysicalProperty::find_by_id(1)
+            .one(&memo.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(property.group_winner_id, None);
+        assert_eq!(property.data, serde_json::json!(["a"]));
+
+        let (sorted, previous) = memo
+            .update_group_winner(1, vec![sorted_on("a")], winner.clone())
+            .await
+            .unwrap();
+        assert_eq!(previous, None);
+        assert_eq!(
+            memo.get_winner(1, vec![sorted_on("a")]).await.unwrap(),
+            Some(sorted)
+        );
+        assert_eq!(memo.get_winner(1, vec![]).await.unwrap(), Some(winner));
+
+        let _ = std::fs::remove_file(DATABASE_FILE);
+    }
+
+    #[tokio::test]
+    async fn test_migration_recomputes_fingerprints() {
+        const DATABASE_FILE: &str = "test_migration_recomputes_fingerprints.db";
+        let memo = new_baseline_backend_manager(DATABASE_FILE).await;
+
+        // Insert expressions with fingerprints computed by an older version.
+        memo.db
+            .execute_unprepared(
+                "INSERT INTO cascades_group (id, latest_winner, in_progress, is_optimized) \
+                 VALUES (1, NULL, false, false), (2, NULL, false, false);
+                 INSERT INTO logical_expression (id, group_id, fingerprint, variant_tag) \
+                 VALUES (1, 1, 42, 0), (2, 2, 42, 1);
+                 INSERT INTO logical_children (logical_expression_id, group_id) VALUES (2, 1);
+                 INSERT INTO physical_expression (id, group_id, fingerprint, variant_tag) \
+                 VALUES (1, 2, 42, 3);
+                 INSERT INTO physical_children (physical_expression_id, group_id) VALUES (1, 1);",
+            )
+            .await
+            .unwrap();
+
+        Migrator::up(&memo.db, None).await.unwrap();
+
+        // The fingerprints are recomputed, so the existing expressions are found again.
+        for expr in LogicalExpression::find().all(&memo.db).await.unwrap() {
+            let children = memo.get_logical_children(expr.id).await.unwrap();
+            assert_eq!(expr.fingerprint_version, FINGERPRINT_VERSION);
+            assert_eq!(
+                expr.fingerprint,
+                fingerprint(expr.variant_tag, &expr.data, &children)
+            );
+        }
+        let scan = logical_expression::Model::new(LogicalOperator::Scan, serde_json::Value::Null);
+        assert_eq!(
+            memo.add_logical_expression(scan, vec![]).await.unwrap(),
+            (1, 1)
+        );
+        let join = logical_expression::Model::new(LogicalOperator::Join, serde_json::Value::Null);
+        assert_eq!(
+            memo.add_logical_expression(join, vec![1]).await.unwrap(),
+            (2, 2)
+        );
+        let hash_join = physical_expression::Model {
+            group_id: 2,
+            ..physical_expression::Model::new(PhysicalOperator::HashJoin, serde_json::Value::Null)
+        };
+        assert_eq!(
+            memo.add_physical_expression_to_group(2, hash_join, vec![1])
+                .await
+                .unwrap(),
+            1
+        );
+
+        let _ = std::fs::remove_file(DATABASE_FILE);
+    }
 }
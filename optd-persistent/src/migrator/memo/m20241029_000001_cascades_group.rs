@@ -49,8 +49,20 @@
 //! ### Best Physical Plan (Winner)
 //!
 //! The `cascades_group` entity also stores a `latest_winner` _nullable_ foreign key reference to
-//! a physical expression. This represents the most recent best query plan we have computed. The
-//! reason it is nullable is because we may not have come up with any best query plan yet.
+//! a physical expression. This represents the most recent best query plan we have computed when no
+//! physical property is required. The reason it is nullable is because we may not have come up with
+//! any best query plan yet.
+//!
+//! A group has a best query plan for each set of required physical properties. All of them are
+//! stored in the [`group_winner`] entity, along with the cost and the epoch they were chosen with,
+//! and their required properties are stored in the [`physical_property`] entity.
+//!
+//! ### Merged Groups
+//!
+//! Two groups may turn out to be equivalent, e.g. when a transformation rule produces an
+//! expression that already exists in another group. The groups are then merged: everything that
+//! refers to one of them is moved to the other one, its representative. The merged group is kept
+//! with a `parent_id` reference to its representative, so that its ID can still be resolved.
 //!
 //! ### Logical Properties
 //!
@@ -64,6 +76,8 @@
 //! [`logical_children`]: super::logical_children
 //! [`physical_children`]: super::physical_children
 //! [`logical_property`]: super::logical_property
+//! [`group_winner`]: super::group_winner
+//! [`physical_property`]: super::physical_property
 
 use crate::migrator::memo::physical_expression::PhysicalExpression;
 use sea_orm_migration::{prelude::*, schema::*};
@@ -23,10 +23,13 @@
 //! A group winner is defined by the [`cascades_group`] it belongs to (`group_id`), the unique ID of
 //! the [`physical_expression`] (`physical_expression_id`), the ID of the cost record in the
 //! [`plan_cost`] table (`cost_id`), and the monotonically-increasing epoch ID in the [`event`]
-//! table (`epoch_id`).
+//! table (`epoch_id`). A group has a winner for each set of required physical properties, which are
+//! the [`physical_property`] records that refer to the winner. The current winner for a set is the
+//! most recent one.
 //!
 //! [`cascades_group`]: super::cascades_group
 //! [`physical_expression`]: super::physical_expression
+//! [`physical_property`]: super::physical_property
 //! [`plan_cost`]: super::super::cost_model::plan_cost
 //! [`event`]: super::super::cost_model::event
 
@@ -22,7 +22,8 @@
 //!
 //! The more interesting column is the `fingerprint` column, in which we store a hashed fingerprint
 //! value that can be used to efficiently check equality between two potentially equivalent physical
-//! expressions (hash-consing). See ???TODO??? for more information on expression fingerprints.
+//! expressions (hash-consing). See [`fingerprint_version`] for more information on expression
+//! fingerprints.
 //!
 //! Finally, since there are many different types of operators, we store a variant tag and a data
 //! column as JSON to represent the semi-structured data fields of logical operators.
@@ -34,6 +35,7 @@
 //! details about this in the module-level documentation for [`cascades_group`].
 //!
 //! [`cascades_group`]: super::cascades_group
+//! [`fingerprint_version`]: super::fingerprint_version
 //! [`logical_expression`]: super::logical_expression
 
 use crate::migrator::memo::cascades_group::CascadesGroup;
@@ -1,7 +1,14 @@
 //! An entity representing a physical property of a physical expression in the Cascades framework.
 //!
-//! TODO what exactly are we storing in here?
-//! TODO why is it linked to only physical expressions and not cascades groups?
+//! A physical property is a property of the output of a physical expression that does not depend
+//! on its logical content, such as its sort order. Like operators, properties are stored as a
+//! variant tag and a JSON data column.
+//!
+//! The properties that a [`group_winner`] was chosen for also refer to the winner, see
+//! [`winner_properties`].
+//!
+//! [`group_winner`]: super::group_winner
+//! [`winner_properties`]: super::winner_properties
 
 use crate::migrator::memo::physical_expression::PhysicalExpression;
 use sea_orm_migration::{prelude::*, schema::*};
@@ -0,0 +1,297 @@
+//! Stores the position of every child group of the logical and physical expressions, and adds the
+//! `data` column to [`logical_expression`] and [`physical_expression`].
+//!
+//! The order of the children matters (e.g. the left and right inputs of a join), and the same group
+//! may be a child of an expression more than once (e.g. in a self-join). [`logical_children`] and
+//! [`physical_children`] are therefore rebuilt with a `child_index` column, which replaces the
+//! group ID in their primary keys. The rebuilt [`logical_children`] table also refers to the
+//! logical expressions through its `logical_expression_id` column, instead of its `group_id`
+//! column.
+//!
+//! The order of the existing children was never stored, so they are numbered in the order of their
+//! group IDs.
+//!
+//! [`logical_expression`]: super::logical_expression
+//! [`physical_expression`]: super::physical_expression
+//! [`logical_children`]: super::logical_children
+//! [`physical_children`]: super::physical_children
+
+use crate::migrator::memo::{
+    cascades_group::CascadesGroup, logical_children, logical_children::LogicalChildren,
+    logical_expression::LogicalExpression, physical_children, physical_children::PhysicalChildren,
+    physical_expression::PhysicalExpression,
+};
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::ConnectionTrait};
+
+/// The number of children that are copied per statement, so that a statement stays below the
+/// limit of SQLite on the number of bound variables.
+const COPY_CHUNK_SIZE: usize = 1000;
+
+#[derive(DeriveIden)]
+pub enum Children {
+    ChildIndex,
+}
+
+#[derive(DeriveIden)]
+pub enum Expression {
+    Data,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            LogicalExpression::Table.into_iden(),
+            PhysicalExpression::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(json(Expression::Data).default("null"))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        let old_table = Alias::new("logical_children_old");
+        manager
+            .rename_table(
+                Table::rename()
+                    .table(LogicalChildren::Table, old_table.clone())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(LogicalChildren::Table)
+                    .col(integer(LogicalChildren::LogicalExpressionId))
+                    .col(integer(Children::ChildIndex))
+                    .col(integer(LogicalChildren::GroupId))
+                    .primary_key(
+                        Index::create()
+                            .col(LogicalChildren::LogicalExpressionId)
+                            .col(Children::ChildIndex),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LogicalChildren::Table, LogicalChildren::LogicalExpressionId)
+                            .to(LogicalExpression::Table, LogicalExpression::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LogicalChildren::Table, LogicalChildren::GroupId)
+                            .to(CascadesGroup::Table, CascadesGroup::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        number_children(
+            manager,
+            old_table,
+            LogicalChildren::Table,
+            LogicalChildren::LogicalExpressionId,
+        )
+        .await?;
+
+        let old_table = Alias::new("physical_children_old");
+        manager
+            .rename_table(
+                Table::rename()
+                    .table(PhysicalChildren::Table, old_table.clone())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(PhysicalChildren::Table)
+                    .col(integer(PhysicalChildren::PhysicalExpressionId))
+                    .col(integer(Children::ChildIndex))
+                    .col(integer(PhysicalChildren::GroupId))
+                    .primary_key(
+                        Index::create()
+                            .col(PhysicalChildren::PhysicalExpressionId)
+                            .col(Children::ChildIndex),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                PhysicalChildren::Table,
+                                PhysicalChildren::PhysicalExpressionId,
+                            )
+                            .to(PhysicalExpression::Table, PhysicalExpression::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PhysicalChildren::Table, PhysicalChildren::GroupId)
+                            .to(CascadesGroup::Table, CascadesGroup::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        number_children(
+            manager,
+            old_table,
+            PhysicalChildren::Table,
+            PhysicalChildren::PhysicalExpressionId,
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The children tables are recreated by their original migrations, and an expression keeps
+        // a single row for each distinct child group.
+        let old_table = Alias::new("logical_children_old");
+        manager
+            .rename_table(
+                Table::rename()
+                    .table(LogicalChildren::Table, old_table.clone())
+                    .to_owned(),
+            )
+            .await?;
+        logical_children::Migration.up(manager).await?;
+        copy_distinct_children(
+            manager,
+            old_table,
+            LogicalChildren::Table,
+            LogicalChildren::LogicalExpressionId,
+        )
+        .await?;
+
+        let old_table = Alias::new("physical_children_old");
+        manager
+            .rename_table(
+                Table::rename()
+                    .table(PhysicalChildren::Table, old_table.clone())
+                    .to_owned(),
+            )
+            .await?;
+        physical_children::Migration.up(manager).await?;
+        copy_distinct_children(
+            manager,
+            old_table,
+            PhysicalChildren::Table,
+            PhysicalChildren::PhysicalExpressionId,
+        )
+        .await?;
+
+        for table in [
+            LogicalExpression::Table.into_iden(),
+            PhysicalExpression::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Expression::Data)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Copies the children of `old_table` to `table`, numbering the children of every expression in
+/// the order of their group IDs, and drops `old_table`.
+///
+/// The logical and physical tables share the name of the `group_id` column.
+async fn number_children(
+    manager: &SchemaManager<'_>,
+    old_table: impl IntoIden,
+    table: impl IntoIden,
+    expression_id: impl IntoIden,
+) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = db.get_database_backend();
+    let old_table = old_table.into_iden();
+    let table = table.into_iden();
+    let expression_id = expression_id.into_iden();
+
+    let select = Query::select()
+        .column(expression_id.clone())
+        .column(LogicalChildren::GroupId)
+        .from(old_table.clone())
+        .order_by(expression_id.clone(), Order::Asc)
+        .order_by(LogicalChildren::GroupId, Order::Asc)
+        .to_owned();
+    let mut children = vec![];
+    let mut previous_expression_id = None;
+    let mut child_index = 0;
+    for row in db.query_all(backend.build(&select)).await? {
+        let id: i32 = row.try_get_by_index(0)?;
+        let group_id: i32 = row.try_get_by_index(1)?;
+        if previous_expression_id != Some(id) {
+            previous_expression_id = Some(id);
+            child_index = 0;
+        }
+        children.push([id, child_index, group_id]);
+        child_index += 1;
+    }
+
+    for chunk in children.chunks(COPY_CHUNK_SIZE) {
+        let mut insert = Query::insert()
+            .into_table(table.clone())
+            .columns([
+                expression_id.clone(),
+                Children::ChildIndex.into_iden(),
+                LogicalChildren::GroupId.into_iden(),
+            ])
+            .to_owned();
+        for child in chunk {
+            insert.values_panic(child.map(Into::into));
+        }
+        db.execute(backend.build(&insert)).await?;
+    }
+
+    manager
+        .drop_table(Table::drop().table(old_table).to_owned())
+        .await
+}
+
+/// Copies the distinct pairs of expressions and child groups of `old_table` to `table`, and drops
+/// `old_table`.
+async fn copy_distinct_children(
+    manager: &SchemaManager<'_>,
+    old_table: impl IntoIden,
+    table: impl IntoIden,
+    expression_id: impl IntoIden,
+) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = db.get_database_backend();
+    let old_table = old_table.into_iden();
+    let table = table.into_iden();
+    let expression_id = expression_id.into_iden();
+
+    let insert = Query::insert()
+        .into_table(table)
+        .columns([expression_id.clone(), LogicalChildren::GroupId.into_iden()])
+        .select_from(
+            Query::select()
+                .distinct()
+                .column(expression_id)
+                .column(LogicalChildren::GroupId)
+                .from(old_table.clone())
+                .to_owned(),
+        )
+        .map_err(|err| DbErr::Migration(err.to_string()))?
+        .to_owned();
+    db.execute(backend.build(&insert)).await?;
+
+    manager
+        .drop_table(Table::drop().table(old_table).to_owned())
+        .await
+}
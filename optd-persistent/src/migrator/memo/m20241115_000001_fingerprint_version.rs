@@ -0,0 +1,216 @@
+//! Adds the `fingerprint_version` column to [`logical_expression`] and [`physical_expression`],
+//! and recomputes the fingerprints of the existing expressions.
+//!
+//! Fingerprints used to be computed with [`std::hash::DefaultHasher`], whose output may change
+//! between Rust releases, and did not cover the child groups of the expressions. Fingerprints
+//! are now computed with [`fingerprint`], and each one is stored with the
+//! [`FINGERPRINT_VERSION`] it was computed with. The rows that existed before this migration
+//! have version 0 until they are recomputed.
+//!
+//! The existing rows are recomputed with a copy of version 1 of [`fingerprint`], so that this
+//! migration keeps doing the same thing when a later version changes the encoding or the hash
+//! function. Such a version comes with its own migration.
+//!
+//! [`logical_expression`]: super::logical_expression
+//! [`physical_expression`]: super::physical_expression
+//! [`fingerprint`]: crate::memo::expression::fingerprint
+//! [`FINGERPRINT_VERSION`]: crate::memo::expression::FINGERPRINT_VERSION
+
+use std::collections::HashMap;
+
+use crate::migrator::memo::{
+    logical_children::LogicalChildren,
+    logical_expression::LogicalExpression,
+    ordered_children::{self, Children},
+    physical_children::PhysicalChildren,
+    physical_expression::PhysicalExpression,
+};
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::ConnectionTrait};
+
+#[derive(DeriveIden)]
+enum Expression {
+    FingerprintVersion,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            LogicalExpression::Table.into_iden(),
+            PhysicalExpression::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(small_integer(Expression::FingerprintVersion).default(0))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        recompute_fingerprints(
+            manager,
+            LogicalExpression::Table,
+            LogicalChildren::Table,
+            LogicalChildren::LogicalExpressionId,
+        )
+        .await?;
+        recompute_fingerprints(
+            manager,
+            PhysicalExpression::Table,
+            PhysicalChildren::Table,
+            PhysicalChildren::PhysicalExpressionId,
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            LogicalExpression::Table.into_iden(),
+            PhysicalExpression::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Expression::FingerprintVersion)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// The version of the fingerprints that are computed by this migration.
+const FINGERPRINT_VERSION: i16 = 1;
+
+/// Recomputes the fingerprints of the expressions stored in `table`, whose ordered child groups
+/// are stored in `children_table`.
+///
+/// The logical and physical tables share the names of the columns that are read here.
+async fn recompute_fingerprints(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    children_table: impl IntoIden,
+    children_expression_id: impl IntoIden,
+) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = db.get_database_backend();
+    let table = table.into_iden();
+    let children_table = children_table.into_iden();
+    let children_expression_id = children_expression_id.into_iden();
+
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    let select = Query::select()
+        .column(children_expression_id.clone())
+        .column(LogicalChildren::GroupId)
+        .from(children_table)
+        .order_by(children_expression_id, Order::Asc)
+        .order_by(Children::ChildIndex, Order::Asc)
+        .to_owned();
+    for row in db.query_all(backend.build(&select)).await? {
+        let expression_id: i32 = row.try_get_by_index(0)?;
+        children
+            .entry(expression_id)
+            .or_default()
+            .push(row.try_get_by_index(1)?);
+    }
+
+    let select = Query::select()
+        .columns([
+            LogicalExpression::Id.into_iden(),
+            LogicalExpression::VariantTag.into_iden(),
+            ordered_children::Expression::Data.into_iden(),
+        ])
+        .from(table.clone())
+        .to_owned();
+    for row in db.query_all(backend.build(&select)).await? {
+        let id: i32 = row.try_get_by_index(0)?;
+        let variant_tag: i16 = row.try_get_by_index(1)?;
+        let data: serde_json::Value = row.try_get_by_index(2)?;
+        let children = children.get(&id).map(Vec::as_slice).unwrap_or_default();
+        let update = Query::update()
+            .table(table.clone())
+            .value(
+                LogicalExpression::Fingerprint,
+                fingerprint(variant_tag, &data, children),
+            )
+            .value(Expression::FingerprintVersion, FINGERPRINT_VERSION)
+            .and_where(Expr::col(LogicalExpression::Id).eq(id))
+            .to_owned();
+        db.execute(backend.build(&update)).await?;
+    }
+    Ok(())
+}
+
+/// Version 1 of [`fingerprint`]: the 64-bit FNV-1a hash of a canonical encoding of the operator
+/// tag, the data and the child groups of an expression.
+///
+/// [`fingerprint`]: crate::memo::expression::fingerprint
+fn fingerprint(variant_tag: i16, data: &serde_json::Value, children: &[i32]) -> i64 {
+    let mut hash = Fnv1a(0xcbf29ce484222325);
+    hash.write(&variant_tag.to_le_bytes());
+    hash.write_json(data);
+    hash.write_len(children.len());
+    for child in children {
+        hash.write(&child.to_le_bytes());
+    }
+    hash.0 as i64
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+
+    fn write_json(&mut self, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Null => self.write(b"n"),
+            serde_json::Value::Bool(false) => self.write(b"f"),
+            serde_json::Value::Bool(true) => self.write(b"t"),
+            serde_json::Value::Number(n) => {
+                self.write(b"d");
+                self.write_str(&n.to_string());
+            }
+            serde_json::Value::String(s) => {
+                self.write(b"s");
+                self.write_str(s);
+            }
+            serde_json::Value::Array(values) => {
+                self.write(b"a");
+                self.write_len(values.len());
+                for value in values {
+                    self.write_json(value);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                self.write(b"o");
+                self.write_len(map.len());
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by_key(|(key, _)| *key);
+                for (key, value) in entries {
+                    self.write_str(key);
+                    self.write_json(value);
+                }
+            }
+        }
+    }
+}
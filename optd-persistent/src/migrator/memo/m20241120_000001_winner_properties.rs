@@ -0,0 +1,181 @@
+//! Stores the required physical properties that the winners of the groups were chosen for.
+//!
+//! A group is optimized for a set of _required_ physical properties, e.g. sorted on `a` or
+//! unordered (the empty set), and has a different [`group_winner`] for each set. The properties
+//! that a winner was chosen for are stored as [`physical_property`] rows that refer to the winner
+//! (`group_winner_id`) and to the [`physical_expression`] of the winner, which delivers them. The
+//! rows of the other properties have a null `group_winner_id`. SQLite cannot add a foreign key to
+//! an existing table, so [`physical_property`] is rebuilt.
+//!
+//! A winner also stores the fingerprint of its set of properties (`properties_fingerprint`), so
+//! that the most recent winner for a set is found through an index on the group, the fingerprint
+//! and the ID, without loading the properties of the other winners. The existing winners were
+//! chosen without required properties, so they get the fingerprint of the empty set.
+//!
+//! [`group_winner`]: super::group_winner
+//! [`physical_property`]: super::physical_property
+//! [`physical_expression`]: super::physical_expression
+
+use crate::migrator::memo::{
+    group_winner::GroupWinner, physical_expression::PhysicalExpression, physical_property,
+    physical_property::PhysicalProperty,
+};
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::ConnectionTrait};
+
+/// The fingerprint of the empty set of properties, see `property_set_fingerprint`.
+const EMPTY_PROPERTY_SET_FINGERPRINT: i64 = -6284781860667377211;
+
+const PROPERTIES_INDEX: &str = "idx-group_winner-group_id-properties_fingerprint";
+
+#[derive(DeriveIden)]
+pub enum Winner {
+    PropertiesFingerprint,
+}
+
+#[derive(DeriveIden)]
+pub enum Property {
+    GroupWinnerId,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupWinner::Table)
+                    .add_column(
+                        big_integer(Winner::PropertiesFingerprint)
+                            .default(EMPTY_PROPERTY_SET_FINGERPRINT),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name(PROPERTIES_INDEX)
+                    .table(GroupWinner::Table)
+                    .col(GroupWinner::GroupId)
+                    .col(Winner::PropertiesFingerprint)
+                    .col(GroupWinner::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        let old_table = Alias::new("physical_property_old");
+        manager
+            .rename_table(
+                Table::rename()
+                    .table(PhysicalProperty::Table, old_table.clone())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(PhysicalProperty::Table)
+                    .col(pk_auto(PhysicalProperty::Id))
+                    .col(integer(PhysicalProperty::PhysicalExpressionId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                PhysicalProperty::Table,
+                                PhysicalProperty::PhysicalExpressionId,
+                            )
+                            .to(PhysicalExpression::Table, PhysicalExpression::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer_null(Property::GroupWinnerId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PhysicalProperty::Table, Property::GroupWinnerId)
+                            .to(GroupWinner::Table, GroupWinner::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(small_integer(PhysicalProperty::VariantTag))
+                    .col(json(PhysicalProperty::Data))
+                    .to_owned(),
+            )
+            .await?;
+        copy_properties(manager, old_table, Condition::all()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The properties of the winners are dropped with the table of their original migration.
+        let old_table = Alias::new("physical_property_old");
+        manager
+            .rename_table(
+                Table::rename()
+                    .table(PhysicalProperty::Table, old_table.clone())
+                    .to_owned(),
+            )
+            .await?;
+        physical_property::Migration.up(manager).await?;
+        copy_properties(
+            manager,
+            old_table,
+            Condition::all().add(Expr::col(Property::GroupWinnerId).is_null()),
+        )
+        .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(PROPERTIES_INDEX)
+                    .table(GroupWinner::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupWinner::Table)
+                    .drop_column(Winner::PropertiesFingerprint)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Copies the properties of `old_table` that match `condition`, without their winners, to
+/// [`physical_property`], and drops `old_table`.
+async fn copy_properties(
+    manager: &SchemaManager<'_>,
+    old_table: impl IntoIden,
+    condition: Condition,
+) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = db.get_database_backend();
+    let old_table = old_table.into_iden();
+    let columns = [
+        PhysicalProperty::Id,
+        PhysicalProperty::PhysicalExpressionId,
+        PhysicalProperty::VariantTag,
+        PhysicalProperty::Data,
+    ]
+    .map(IntoIden::into_iden);
+
+    let insert = Query::insert()
+        .into_table(PhysicalProperty::Table)
+        .columns(columns.clone())
+        .select_from(
+            Query::select()
+                .columns(columns)
+                .from(old_table.clone())
+                .cond_where(condition)
+                .to_owned(),
+        )
+        .map_err(|err| DbErr::Migration(err.to_string()))?
+        .to_owned();
+    db.execute(backend.build(&insert)).await?;
+
+    manager
+        .drop_table(Table::drop().table(old_table).to_owned())
+        .await
+}
@@ -13,6 +13,9 @@ pub(crate) mod m20241029_000001_predicate;
 pub(crate) mod m20241029_000001_predicate_children;
 pub(crate) mod m20241029_000001_predicate_logical_expression_junction;
 pub(crate) mod m20241029_000001_predicate_physical_expression_junction;
+pub(crate) mod m20241112_000001_ordered_children;
+pub(crate) mod m20241115_000001_fingerprint_version;
+pub(crate) mod m20241120_000001_winner_properties;
 
 pub(crate) use m20241029_000001_cascades_group as cascades_group;
 pub(crate) use m20241029_000001_group_winner as group_winner;
@@ -26,3 +29,6 @@ pub(crate) use m20241029_000001_predicate as predicate;
 pub(crate) use m20241029_000001_predicate_children as predicate_children;
 pub(crate) use m20241029_000001_predicate_logical_expression_junction as predicate_logical_expression_junction;
 pub(crate) use m20241029_000001_predicate_physical_expression_junction as predicate_physical_expression_junction;
+pub(crate) use m20241112_000001_ordered_children as ordered_children;
+pub(crate) use m20241115_000001_fingerprint_version as fingerprint_version;
+pub(crate) use m20241120_000001_winner_properties as winner_properties;
@@ -37,6 +37,9 @@ impl MigratorTrait for Migrator {
             Box::new(memo::predicate_children::Migration),
             Box::new(memo::predicate_logical_expression_junction::Migration),
             Box::new(memo::predicate_physical_expression_junction::Migration),
+            Box::new(memo::ordered_children::Migration),
+            Box::new(memo::fingerprint_version::Migration),
+            Box::new(memo::winner_properties::Migration),
         ]
     }
 }